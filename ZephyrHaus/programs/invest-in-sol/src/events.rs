@@ -0,0 +1,343 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Role;
+
+/// what a protocol fee was charged on.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FeeKind {
+    Deposit,
+    Conversion,
+}
+
+#[event]
+pub struct FeeCharged {
+    pub kind: FeeKind,
+    pub payer: Pubkey,
+    /// the mint the fee was paid in, or None for SOL.
+    pub mint: Option<Pubkey>,
+    /// the amount the fee was charged on.
+    pub base_amount: u64,
+    pub fee_bps: u16,
+    pub fee_amount: u64,
+}
+
+#[event]
+pub struct FeesUpdated {
+    pub fee: Option<u16>,
+    pub conversion_fee: Option<u16>,
+    pub fee_recipient: Pubkey,
+}
+
+#[event]
+pub struct DepositCapsUpdated {
+    pub max_total_deposited_sol: Option<u64>,
+    pub max_wallet_deposit: Option<u64>,
+    pub max_epoch_deposit: Option<u64>,
+}
+
+#[event]
+pub struct AllowlistRootUpdated {
+    /// the new root, None when deposits are open to anyone again.
+    pub allowlist_root: Option<[u8; 32]>,
+}
+
+#[event]
+pub struct ClaimWindowUpdated {
+    /// seconds a depositor has to claim a new receipt's option.
+    pub claim_window: i64,
+}
+
+#[event]
+pub struct DepositReceiptClosed {
+    pub depositor: Pubkey,
+    /// the nonce of the closed receipt.
+    pub nonce: u64,
+    pub closed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositReceiptExpired {
+    pub depositor: Pubkey,
+    /// the nonce of the expired receipt.
+    pub nonce: u64,
+    /// the CN the deposit minted, left with the depositor.
+    pub cn_amount: u64,
+    /// the PT that was backing the unclaimed option.
+    pub pt_burned: u64,
+    pub expired_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub member: Pubkey,
+    pub role: Role,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub member: Pubkey,
+    pub role: Role,
+}
+
+#[event]
+pub struct FeesClaimed {
+    pub recipient: Pubkey,
+    /// the mint claimed, or None for SOL.
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    /// the proposed authority, or None if the proposal was cancelled.
+    pub pending_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Option<Pubkey>,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityRenounced {
+    pub previous_authority: Pubkey,
+}
+
+#[event]
+pub struct YieldDistributed {
+    pub amount: u64,
+    /// the outstanding option amount the yield was spread over.
+    pub total_option_amount: u64,
+    /// config.yield_per_unit after the distribution.
+    pub yield_per_unit: u128,
+}
+
+#[event]
+pub struct OptionYieldClaimed {
+    pub recipient: Pubkey,
+    pub option_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct Initialized {
+    pub authority: Pubkey,
+    pub config: Pubkey,
+    pub treasury: Pubkey,
+    pub cn_mint: Pubkey,
+    pub pt_mint: Pubkey,
+    pub collection_mint: Pubkey,
+    pub nav: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Deposited {
+    pub depositor: Pubkey,
+    /// the mint deposited, or None for SOL.
+    pub mint: Option<Pubkey>,
+    /// the amount deposited after the fee, in the deposited mint's base units.
+    pub amount: u64,
+    /// the SOL value of `amount`, in lamports.
+    pub value_lamports: u64,
+    pub cn_minted: u64,
+    pub pt_minted: u64,
+    /// the NAV the deposit was priced at.
+    pub nav: u64,
+    /// the deposit nonce keying the receipt / option mint.
+    pub nonce: u64,
+    pub option_duration: u32,
+    /// the option's expiration, None until the option is issued from the receipt.
+    pub expiration: Option<i64>,
+    /// when the receipt can no longer be claimed, None when the option was issued directly.
+    pub claim_deadline: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositCancelled {
+    pub depositor: Pubkey,
+    /// the nonce of the cancelled deposit's receipt.
+    pub nonce: u64,
+    pub cn_burned: u64,
+    pub pt_burned: u64,
+    /// the SOL value refunded, the lower of the deposit's value and its CN at `nav`.
+    pub refund: u64,
+    pub nav: u64,
+    pub timestamp: i64,
+    /// the asset refunded, or None for SOL.
+    pub mint: Option<Pubkey>,
+    /// the amount refunded, in `mint`'s base units (lamports for SOL).
+    pub amount_refunded: u64,
+}
+
+#[event]
+pub struct OptionIssued {
+    pub owner: Pubkey,
+    pub option_mint: Pubkey,
+    pub option_data: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub expiration: i64,
+    pub conversion_multiplier_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OptionSplit {
+    pub holder: Pubkey,
+    /// the option the amount was taken from.
+    pub option_mint: Pubkey,
+    /// the option issued with the split amount.
+    pub new_option_mint: Pubkey,
+    pub amount: u64,
+    /// the amount left on `option_mint`.
+    pub remaining_amount: u64,
+    pub expiration: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OptionsMerged {
+    pub holder: Pubkey,
+    /// the option kept.
+    pub option_mint: Pubkey,
+    /// the option burned into `option_mint`.
+    pub merged_option_mint: Pubkey,
+    pub merged_amount: u64,
+    /// the amount on `option_mint` after the merge.
+    pub total_amount: u64,
+    pub expiration: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OptionFrozen {
+    pub option_mint: Pubkey,
+    /// the token account frozen, holding the NFT.
+    pub token_account: Pubkey,
+    pub frozen_by: Pubkey,
+    /// when anyone can thaw it, None if only the authority or a metadata manager can.
+    pub unlock_at: Option<i64>,
+    /// whether it was frozen because the option expired.
+    pub expired: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OptionThawed {
+    pub option_mint: Pubkey,
+    pub token_account: Pubkey,
+    pub thawed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PartiallyConverted {
+    pub converter: Pubkey,
+    pub option_mint: Pubkey,
+    pub cn_burned: u64,
+    pub pt_transferred: u64,
+    /// the amount left on the option.
+    pub remaining_amount: u64,
+    /// the treasury NAV at the time of the conversion.
+    pub nav: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FullyConverted {
+    pub converter: Pubkey,
+    pub option_mint: Pubkey,
+    pub cn_burned: u64,
+    pub pt_transferred: u64,
+    /// the option's settled yield paid to the converter.
+    pub yield_paid: u64,
+    /// the treasury NAV at the time of the conversion.
+    pub nav: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Redeemed {
+    pub redeemer: Pubkey,
+    pub cn_burned: u64,
+    pub pt_burned: u64,
+    pub payout: u64,
+    pub nav: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExpiredOptionsSynced {
+    pub synced_count: u64,
+    pub pt_burned: u64,
+    pub total_option_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LocksUpdated {
+    pub authority: Pubkey,
+    pub locked: bool,
+    pub deposit_locked: bool,
+    pub convert_locked: bool,
+    pub redeem_locked: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OptionAccountClosed {
+    pub option_mint: Pubkey,
+    pub option_data: Pubkey,
+    pub receiver: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawalQueued {
+    pub authority: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub executable_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawalExecuted {
+    pub authority: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// the treasury NAV after the withdrawal.
+    pub nav: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawalCancelled {
+    pub authority: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalDelayUpdated {
+    pub authority: Pubkey,
+    pub withdrawal_delay: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    /// the migrated Config, Treasury, OptionData or DepositReceipt account.
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
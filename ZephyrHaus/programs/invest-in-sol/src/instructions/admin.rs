@@ -1,6 +1,34 @@
 use anchor_lang::prelude::*;
-use crate::state::Config;
+use crate::events::{
+    AllowlistRootUpdated, ClaimWindowUpdated, DepositCapsUpdated, FeesUpdated, LocksUpdated,
+};
+use crate::state::{Config, Role, Roles, MAX_CLAIM_WINDOW, MAX_FEE_BPS};
 
+/// fails unless `signer` is the config authority.
+pub fn require_authority(config: &Config, signer: &Pubkey) -> Result<()> {
+    require!(config.is_authority(signer), AdminError::Unauthorized);
+    Ok(())
+}
+
+/// fails unless `signer` is the config authority or holds one of `roles_allowed`.
+/// the one access check every admin instruction goes through. roles are granted by
+/// the authority, so they lapse with it once the authority is renounced.
+pub fn require_role(
+    config: &Config,
+    roles: &Roles,
+    signer: &Pubkey,
+    roles_allowed: &[Role],
+) -> Result<()> {
+    require!(config.authority.is_some(), AdminError::AuthorityRenounced);
+    require!(
+        config.is_authority(signer)
+            || roles_allowed.iter().any(|role| roles.has_role(signer, *role)),
+        AdminError::Unauthorized
+    );
+    Ok(())
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateLocks<'info> {
     #[account(mut)]
@@ -11,8 +39,15 @@ pub struct UpdateLocks<'info> {
         seeds = [Config::SEED_PREFIX], // use constant
         bump = config.bump, // use correct field name
         // has_one = authority @ AdminError::Unauthorized, // removed: manual check needed due to Option<Pubkey>
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
     )]
     pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
 }
 
 impl<'info> UpdateLocks<'info> {
@@ -21,16 +56,23 @@ impl<'info> UpdateLocks<'info> {
         locked: Option<bool>,
         deposit_locked: Option<bool>,
         convert_locked: Option<bool>,
+        redeem_locked: Option<bool>,
     ) -> Result<()> {
-        // manual authority check
-        require!(
-            ctx.accounts.config.authority.is_some(),
-            AdminError::Unauthorized
-        );
-        require!(
-            ctx.accounts.config.authority.unwrap() == ctx.accounts.authority.key(),
-            AdminError::Unauthorized
-        );
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.roles,
+            &ctx.accounts.authority.key(),
+            &[Role::PauseGuardian],
+        )?;
+        // the pause guardian can only lock, unlocking stays with the authority
+        if !ctx.accounts.config.is_authority(&ctx.accounts.authority.key()) {
+            require!(
+                [locked, deposit_locked, convert_locked, redeem_locked]
+                    .iter()
+                    .all(|val| *val != Some(false)),
+                AdminError::GuardianCanOnlyLock
+            );
+        }
 
         let config = &mut ctx.accounts.config;
 
@@ -46,13 +88,206 @@ impl<'info> UpdateLocks<'info> {
             config.convert_locked = val;
             msg!("convert lock updated to: {}", val);
         }
+        if let Some(val) = redeem_locked {
+            config.redeem_locked = val;
+            msg!("redeem lock updated to: {}", val);
+        }
+
+        let config = &ctx.accounts.config;
+        emit_cpi!(LocksUpdated {
+            authority: ctx.accounts.authority.key(),
+            locked: config.locked,
+            deposit_locked: config.deposit_locked,
+            convert_locked: config.convert_locked,
+            redeem_locked: config.redeem_locked,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateFees<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+}
+
+impl<'info> UpdateFees<'info> {
+    pub fn handler(
+        ctx: Context<UpdateFees>,
+        fee: Option<u16>,
+        conversion_fee: Option<u16>,
+        fee_recipient: Option<Pubkey>,
+    ) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.roles,
+            &ctx.accounts.authority.key(),
+            &[Role::FeeManager],
+        )?;
+
+        let config = &mut ctx.accounts.config;
+
+        // a fee of 0 bps disables that fee
+        if let Some(val) = fee {
+            require!(val <= MAX_FEE_BPS, AdminError::FeeTooHigh);
+            config.fee = if val == 0 { None } else { Some(val) };
+            msg!("deposit fee updated to: {} bps", val);
+        }
+        if let Some(val) = conversion_fee {
+            require!(val <= MAX_FEE_BPS, AdminError::FeeTooHigh);
+            config.conversion_fee = if val == 0 { None } else { Some(val) };
+            msg!("conversion fee updated to: {} bps", val);
+        }
+        if let Some(val) = fee_recipient {
+            config.fee_recipient = val;
+            msg!("fee recipient updated to: {}", val);
+        }
+
+        let config = &ctx.accounts.config;
+        emit_cpi!(FeesUpdated {
+            fee: config.fee,
+            conversion_fee: config.conversion_fee,
+            fee_recipient: config.fee_recipient,
+        });
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateDepositCaps<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> UpdateDepositCaps<'info> {
+    pub fn handler(
+        ctx: Context<UpdateDepositCaps>,
+        max_total_deposited_sol: Option<u64>,
+        max_wallet_deposit: Option<u64>,
+        max_epoch_deposit: Option<u64>,
+    ) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        let config = &mut ctx.accounts.config;
+
+        // a cap of 0 removes that cap
+        if let Some(val) = max_total_deposited_sol {
+            config.max_total_deposited_sol = if val == 0 { None } else { Some(val) };
+            msg!("total deposit cap updated to: {} lamports", val);
+        }
+        if let Some(val) = max_wallet_deposit {
+            config.max_wallet_deposit = if val == 0 { None } else { Some(val) };
+            msg!("per-wallet deposit cap updated to: {} lamports", val);
+        }
+        if let Some(val) = max_epoch_deposit {
+            config.max_epoch_deposit = if val == 0 { None } else { Some(val) };
+            msg!("per-epoch deposit limit updated to: {} lamports", val);
+        }
+
+        let config = &ctx.accounts.config;
+        emit_cpi!(DepositCapsUpdated {
+            max_total_deposited_sol: config.max_total_deposited_sol,
+            max_wallet_deposit: config.max_wallet_deposit,
+            max_epoch_deposit: config.max_epoch_deposit,
+        });
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetAllowlistRoot<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetAllowlistRoot<'info> {
+    pub fn handler(ctx: Context<SetAllowlistRoot>, allowlist_root: Option<[u8; 32]>) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        // a new root starts every address's allocation over
+        ctx.accounts.config.allowlist_root = allowlist_root;
+        match allowlist_root {
+            Some(_) => msg!("deposit allowlist enabled"),
+            None => msg!("deposit allowlist disabled"),
+        }
+
+        emit_cpi!(AllowlistRootUpdated { allowlist_root });
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateClaimWindow<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> UpdateClaimWindow<'info> {
+    pub fn handler(ctx: Context<UpdateClaimWindow>, claim_window: i64) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+        require!(
+            claim_window > 0 && claim_window <= MAX_CLAIM_WINDOW,
+            AdminError::InvalidClaimWindow
+        );
+
+        // only applies to receipts issued from now on
+        ctx.accounts.config.claim_window = claim_window;
 
+        emit_cpi!(ClaimWindowUpdated { claim_window });
+        msg!("claim window updated to: {} seconds", claim_window);
         Ok(())
     }
 }
 
 #[error_code]
 pub enum AdminError {
-    #[msg("unauthorized: signer is not the config authority.")]
+    #[msg("unauthorized: signer is not the config authority or a holder of the required role.")]
     Unauthorized,
+    #[msg("fee exceeds the maximum allowed fee.")]
+    FeeTooHigh,
+    #[msg("the pause guardian can only set locks, not lift them.")]
+    GuardianCanOnlyLock,
+    #[msg("claim window must be between 1 second and 90 days.")]
+    InvalidClaimWindow,
+    #[msg("the authority has been renounced, roles no longer grant access.")]
+    AuthorityRenounced,
 }
\ No newline at end of file
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+
+use crate::events::{AuthorityProposed, AuthorityRenounced, AuthorityTransferred};
+use crate::instructions::admin::require_authority;
+use crate::state::{Config, Treasury};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> ProposeAuthority<'info> {
+    pub fn handler(ctx: Context<ProposeAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        // the handover only happens once the new authority accepts,
+        // so a mistyped key can never take over the protocol
+        let config = &mut ctx.accounts.config;
+        config.pending_authority = new_authority;
+
+        match new_authority {
+            Some(key) => msg!("proposed new authority: {}", key),
+            None => msg!("pending authority proposal cancelled"),
+        }
+        emit_cpi!(AuthorityProposed {
+            authority: ctx.accounts.authority.key(),
+            pending_authority: new_authority,
+        });
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+impl<'info> AcceptAuthority<'info> {
+    pub fn handler(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let pending_authority = ctx
+            .accounts
+            .config
+            .pending_authority
+            .ok_or(AuthorityError::NoPendingAuthority)?;
+        require_keys_eq!(
+            pending_authority,
+            ctx.accounts.new_authority.key(),
+            AuthorityError::PendingAuthorityMismatch
+        );
+
+        let previous_authority = ctx.accounts.config.authority;
+
+        // config and treasury always share the same authority
+        let config = &mut ctx.accounts.config;
+        config.authority = Some(pending_authority);
+        config.pending_authority = None;
+        ctx.accounts.treasury.authority = Some(pending_authority);
+
+        msg!("authority transferred to: {}", pending_authority);
+        emit_cpi!(AuthorityTransferred {
+            previous_authority,
+            new_authority: pending_authority,
+        });
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RenounceAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+impl<'info> RenounceAuthority<'info> {
+    pub fn handler(ctx: Context<RenounceAuthority>) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        // irreversible: every authority-gated instruction is disabled from here on
+        let config = &mut ctx.accounts.config;
+        config.authority = None;
+        config.pending_authority = None;
+        ctx.accounts.treasury.authority = None;
+
+        msg!("authority renounced by: {}", ctx.accounts.authority.key());
+        emit_cpi!(AuthorityRenounced {
+            previous_authority: ctx.accounts.authority.key(),
+        });
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum AuthorityError {
+    #[msg("no authority transfer is pending.")]
+    NoPendingAuthority,
+    #[msg("signer is not the pending authority.")]
+    PendingAuthorityMismatch,
+}
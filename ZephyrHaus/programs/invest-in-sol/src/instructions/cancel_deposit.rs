@@ -0,0 +1,423 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    burn, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::events::DepositCancelled;
+use crate::instructions::token_transfer::transfer_checked;
+use crate::state::{
+    mul_div, pt_for_cn, Config, DepositReceipt, DurationTier, Rounding, Treasury, TreasuryAsset,
+    UserPosition,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CancelDeposit<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    // depositor's CN ATA (source of the CN minted by the deposit)
+    #[account(
+        mut,
+        associated_token::mint = cn_mint,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_cn_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // the receipt is closed, its nonce is never handed out again
+    #[account(
+        mut,
+        seeds = [
+            DepositReceipt::SEED_PREFIX,
+            depositor.key().as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        bump = deposit_receipt.bump,
+        close = depositor,
+        constraint = deposit_receipt.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub deposit_receipt: Account<'info, DepositReceipt>,
+
+    #[account(
+        mut, // the deposit comes off the epoch deposit limit
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    // treasury PDA holds the SOL refunded to the depositor, and owns the asset vaults
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    // mints (checked against config)
+    #[account(
+        mut,
+        address = config.cn_mint @ CancelDepositError::AddressMismatch
+    )]
+    pub cn_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = config.pt_mint @ CancelDepositError::AddressMismatch
+    )]
+    pub pt_mint: InterfaceAccount<'info, Mint>,
+
+    // protocol's PT ATA (source of the PT minted by the deposit)
+    #[account(
+        mut,
+        associated_token::mint = pt_mint,
+        associated_token::authority = config, // config PDA owns the protocol's PT ATA
+        associated_token::token_program = token_program,
+    )]
+    pub protocol_pt_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // depositor's running deposit totals the deposit was counted against
+    #[account(
+        mut,
+        seeds = [UserPosition::SEED_PREFIX, depositor.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    // tier the deposit reserved its option amount in
+    #[account(
+        mut,
+        seeds = [DurationTier::SEED_PREFIX, &deposit_receipt.option_duration.to_le_bytes()],
+        bump = duration_tier.bump,
+    )]
+    pub duration_tier: Account<'info, DurationTier>,
+
+    // the deposited asset's accounts, only passed to cancel an SPL deposit, which is
+    // refunded in kind from the treasury's vault. checked against the receipt in refund_asset
+    #[account(
+        mut,
+        seeds = [TreasuryAsset::SEED_PREFIX, treasury_asset.mint.as_ref()],
+        bump = treasury_asset.bump,
+    )]
+    pub treasury_asset: Option<Box<Account<'info, TreasuryAsset>>>,
+
+    pub asset_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    pub treasury_asset_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::authority = depositor,
+    )]
+    pub depositor_asset_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // programs
+    pub token_program: Interface<'info, TokenInterface>, // CN and PT
+    // the deposited asset's token program, may differ from CN/PT's
+    pub asset_token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelDeposit<'info> {
+    pub fn handler(
+        mut ctx: Context<'_, '_, 'info, 'info, CancelDeposit<'info>>,
+        nonce: u64,
+    ) -> Result<()> {
+        // a refund pays out SOL like a redemption, so it follows the same locks
+        require!(
+            !ctx.accounts.config.locked,
+            CancelDepositError::ProtocolLocked
+        );
+        require!(
+            !ctx.accounts.config.redeem_locked,
+            CancelDepositError::RedemptionsLocked
+        );
+        require!(
+            !ctx.accounts.deposit_receipt.nft_issued,
+            CancelDepositError::OptionAlreadyIssued
+        );
+
+        let receipt = &ctx.accounts.deposit_receipt;
+        let cn_amount = receipt.amount;
+        // the same PT the deposit minted for this CN
+        let pt_amount = pt_for_cn(cn_amount, receipt.conversion_multiplier_bps, Rounding::Up)?;
+        require!(
+            ctx.accounts.depositor_cn_ata.amount >= cn_amount,
+            CancelDepositError::InsufficientCnBalance
+        );
+        require!(
+            ctx.accounts.protocol_pt_ata.amount >= pt_amount,
+            CancelDepositError::InsufficientPtBalance
+        );
+
+        let refund = Self::calculate_refund(&mut ctx, cn_amount)?;
+        Self::burn_tokens(&ctx, cn_amount, pt_amount)?;
+        // a deposit is refunded in what was deposited
+        let asset_mint = ctx.accounts.deposit_receipt.asset_mint;
+        let amount_refunded = match asset_mint {
+            Some(asset_mint) => Self::refund_asset(&mut ctx, asset_mint, refund)?,
+            None => Self::refund_sol(&mut ctx, refund)?,
+        };
+        Self::release_deposit(&mut ctx);
+
+        emit_cpi!(DepositCancelled {
+            depositor: ctx.accounts.depositor.key(),
+            nonce,
+            cn_burned: cn_amount,
+            pt_burned: pt_amount,
+            refund,
+            nav: ctx.accounts.treasury.nav,
+            timestamp: Clock::get()?.unix_timestamp,
+            mint: asset_mint,
+            amount_refunded,
+        });
+        msg!("cancelled deposit {} - refunded {} lamports", nonce, refund);
+        Ok(())
+    }
+
+    /// the deposit's CN valued at the current NAV, capped at what was deposited,
+    /// so a cancelled deposit never takes yield the treasury earned in the meantime.
+    pub fn calculate_refund(ctx: &mut Context<CancelDeposit>, cn_amount: u64) -> Result<u64> {
+        // nav and refund are both rounded down so rounding always favours the protocol
+        let idle_sol = Treasury::idle_sol(&ctx.accounts.treasury.to_account_info())?;
+        let nav = ctx.accounts.treasury.calculate_nav(
+            idle_sol,
+            ctx.accounts.cn_mint.supply,
+            Rounding::Down,
+        )?;
+        let refund = Treasury::lamports_for_tokens(cn_amount, nav)?
+            .min(ctx.accounts.deposit_receipt.value_lamports);
+        require!(refund > 0, CancelDepositError::RefundTooSmall);
+        ctx.accounts.treasury.update_nav(nav)?;
+
+        msg!("calculated NAV: {}", nav);
+        msg!("SOL to refund: {}", refund);
+        Ok(refund)
+    }
+
+    pub fn burn_tokens(ctx: &Context<CancelDeposit>, cn_amount: u64, pt_amount: u64) -> Result<()> {
+        // burn CN tokens from the depositor's ATA, depositor signs
+        let cpi_accounts_cn = Burn {
+            mint: ctx.accounts.cn_mint.to_account_info(),
+            from: ctx.accounts.depositor_cn_ata.to_account_info(),
+            authority: ctx.accounts.depositor.to_account_info(),
+        };
+        let cpi_ctx_cn = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts_cn,
+        );
+        burn(cpi_ctx_cn, cn_amount)?;
+        msg!("burned {} CN tokens from depositor", cn_amount);
+
+        // burn the matching PT tokens from the protocol's PT ATA, config signs
+        let bump_seed = [ctx.accounts.config.bump];
+        let config_seeds_with_bump = Config::get_seeds_with_bump(&bump_seed);
+        let signer_seeds = &[&config_seeds_with_bump[..]];
+        let cpi_accounts_pt = Burn {
+            mint: ctx.accounts.pt_mint.to_account_info(),
+            from: ctx.accounts.protocol_pt_ata.to_account_info(),
+            authority: ctx.accounts.config.to_account_info(),
+        };
+        let cpi_ctx_pt = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts_pt,
+            signer_seeds,
+        );
+        burn(cpi_ctx_pt, pt_amount)?;
+        msg!("burned {} PT tokens from protocol's ATA", pt_amount);
+
+        Ok(())
+    }
+
+    /// returns the lamports refunded.
+    pub fn refund_sol(ctx: &mut Context<CancelDeposit>, refund: u64) -> Result<u64> {
+        // only lamports above the treasury's rent-exempt minimum and not reserved
+        // for option yield can be paid out
+        let idle_sol = Treasury::idle_sol(&ctx.accounts.treasury.to_account_info())?;
+        require!(
+            ctx.accounts.treasury.unreserved_sol(idle_sol) >= refund,
+            CancelDepositError::InsufficientTreasuryFunds
+        );
+
+        // the treasury is owned by this program, so lamports are moved directly
+        ctx.accounts.treasury.sub_lamports(refund)?;
+        ctx.accounts.depositor.add_lamports(refund)?;
+        msg!("transferred {} SOL from treasury to depositor", refund);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.total_deposited_sol = treasury.total_deposited_sol.saturating_sub(refund);
+        Ok(refund)
+    }
+
+    /// refunds an SPL deposit in kind: the asset that reached the vault, scaled down by
+    /// the refund when the deposit's CN is worth less than it was deposited at.
+    /// returns the amount of the asset refunded.
+    pub fn refund_asset(
+        ctx: &mut Context<'_, '_, 'info, 'info, CancelDeposit<'info>>,
+        asset_mint_key: Pubkey,
+        refund: u64,
+    ) -> Result<u64> {
+        let accounts = &ctx.accounts;
+        let (
+            Some(treasury_asset),
+            Some(asset_mint),
+            Some(treasury_asset_vault),
+            Some(depositor_asset_ata),
+            Some(asset_token_program),
+        ) = (
+            accounts.treasury_asset.as_ref(),
+            accounts.asset_mint.as_ref(),
+            accounts.treasury_asset_vault.as_ref(),
+            accounts.depositor_asset_ata.as_ref(),
+            accounts.asset_token_program.as_ref(),
+        )
+        else {
+            return err!(CancelDepositError::IncompleteAssetAccounts);
+        };
+        require_keys_eq!(
+            asset_mint.key(),
+            asset_mint_key,
+            CancelDepositError::AddressMismatch
+        );
+        require_keys_eq!(
+            treasury_asset.mint,
+            asset_mint_key,
+            CancelDepositError::AddressMismatch
+        );
+        require_keys_eq!(
+            treasury_asset_vault.key(),
+            treasury_asset.vault,
+            CancelDepositError::AddressMismatch
+        );
+        require_keys_eq!(
+            depositor_asset_ata.mint,
+            asset_mint_key,
+            CancelDepositError::AddressMismatch
+        );
+        require_keys_eq!(
+            *asset_mint.to_account_info().owner,
+            asset_token_program.key(),
+            CancelDepositError::AddressMismatch
+        );
+
+        let receipt = &accounts.deposit_receipt;
+        let amount = mul_div(
+            receipt.asset_amount as u128,
+            refund as u128,
+            receipt.value_lamports as u128,
+            Rounding::Down,
+        )?;
+        let amount = u64::try_from(amount).map_err(|_| CancelDepositError::Overflow)?;
+        require!(amount > 0, CancelDepositError::RefundTooSmall);
+        require!(
+            treasury_asset_vault.amount >= amount,
+            CancelDepositError::InsufficientTreasuryFunds
+        );
+
+        // the vault is owned by the treasury PDA, which signs the transfer back
+        let bump_seed = [accounts.treasury.treasury_bump];
+        let treasury_seeds_with_bump = Treasury::get_seeds_with_bump(&bump_seed);
+        let signer_seeds = &[&treasury_seeds_with_bump[..]];
+        let transfer_accounts = TransferChecked {
+            from: treasury_asset_vault.to_account_info(),
+            to: depositor_asset_ata.to_account_info(),
+            authority: accounts.treasury.to_account_info(),
+            mint: asset_mint.to_account_info(),
+        };
+        // remaining accounts carry the asset's transfer hook accounts, if it has one
+        let cpi_ctx = CpiContext::new_with_signer(
+            asset_token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked(cpi_ctx, amount, asset_mint.decimals)?;
+        msg!(
+            "transferred {} of asset {} from treasury vault to depositor",
+            amount,
+            asset_mint_key
+        );
+
+        // the asset left the vault, so it comes off the treasury's holdings
+        let accounts = &mut *ctx.accounts;
+        let treasury_asset = accounts
+            .treasury_asset
+            .as_mut()
+            .ok_or(CancelDepositError::IncompleteAssetAccounts)?;
+        treasury_asset.total_deposited = treasury_asset.total_deposited.saturating_sub(amount);
+        treasury_asset.revalue(&mut accounts.treasury)?;
+        Ok(amount)
+    }
+
+    /// takes the cancelled deposit back off everything it was counted against: the SOL
+    /// backing CN, the depositor's caps, the epoch limit and the duration tier's cap.
+    pub fn release_deposit(ctx: &mut Context<CancelDeposit>) {
+        let accounts = &mut *ctx.accounts;
+        let treasury = &mut accounts.treasury;
+        treasury.backing_sol = treasury
+            .backing_sol
+            .saturating_sub(accounts.deposit_receipt.value_lamports);
+
+        Self::release_caps(
+            &accounts.deposit_receipt,
+            &mut accounts.config,
+            &mut accounts.user_position,
+            &mut accounts.duration_tier,
+        );
+    }
+
+    /// gives back the capacity a receipt's deposit took from the depositor's caps, the
+    /// epoch limit and the duration tier's cap, and takes its CN off the unclaimed
+    /// receipt total.
+    pub fn release_caps(
+        receipt: &DepositReceipt,
+        config: &mut Config,
+        user_position: &mut UserPosition,
+        duration_tier: &mut DurationTier,
+    ) {
+        let value = receipt.value_lamports;
+
+        user_position.total_deposited = user_position.total_deposited.saturating_sub(value);
+        // the allowlist count starts over under a new root, so only the root the deposit
+        // was counted against gets it back
+        if receipt.allowlist_root == Some(user_position.allowlist_root) {
+            user_position.allowlist_used = user_position.allowlist_used.saturating_sub(value);
+        }
+
+        // likewise the epoch limit only counts the latest epoch deposited in
+        if config.deposit_epoch == receipt.deposit_epoch {
+            config.epoch_deposited = config.epoch_deposited.saturating_sub(value);
+        }
+
+        duration_tier.total_issued = duration_tier.total_issued.saturating_sub(receipt.amount);
+        config.total_receipt_amount = config.total_receipt_amount.saturating_sub(receipt.amount);
+    }
+}
+
+#[error_code]
+pub enum CancelDepositError {
+    #[msg("account address mismatch.")]
+    AddressMismatch,
+    #[msg("protocol is locked.")]
+    ProtocolLocked,
+    #[msg("redemptions are currently locked.")]
+    RedemptionsLocked,
+    #[msg("the option for this deposit has already been issued.")]
+    OptionAlreadyIssued,
+    #[msg("insufficient CN token balance to cancel the deposit.")]
+    InsufficientCnBalance,
+    #[msg("insufficient PT tokens held by the protocol.")]
+    InsufficientPtBalance,
+    #[msg("treasury does not hold enough idle SOL for this refund.")]
+    InsufficientTreasuryFunds,
+    #[msg("deposit is too small to refund any SOL at the current NAV.")]
+    RefundTooSmall,
+    #[msg("an SPL deposit needs its asset's treasury accounts to be refunded.")]
+    IncompleteAssetAccounts,
+    #[msg("calculation overflow")]
+    Overflow,
+}
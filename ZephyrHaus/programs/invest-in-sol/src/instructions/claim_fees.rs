@@ -0,0 +1,193 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::events::FeesClaimed;
+use crate::instructions::admin::require_role;
+use crate::instructions::token_transfer::transfer_checked;
+use crate::state::{Config, FeeVault, Role, Roles};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimFees<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        mut,
+        seeds = [FeeVault::SEED_PREFIX],
+        bump = fee_vault.bump,
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: only receives lamports, checked against config.fee_recipient
+    #[account(
+        mut,
+        address = config.fee_recipient @ ClaimFeesError::FeeRecipientMismatch,
+    )]
+    pub fee_recipient: UncheckedAccount<'info>,
+}
+
+impl<'info> ClaimFees<'info> {
+    pub fn handler(ctx: Context<ClaimFees>) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.roles,
+            &ctx.accounts.authority.key(),
+            &[Role::FeeManager],
+        )?;
+
+        let amount = FeeVault::claimable_sol(&ctx.accounts.fee_vault.to_account_info())?;
+        require!(amount > 0, ClaimFeesError::NoFeesToClaim);
+
+        // the fee vault is program-owned, so lamports are moved directly
+        ctx.accounts.fee_vault.sub_lamports(amount)?;
+        ctx.accounts.fee_recipient.add_lamports(amount)?;
+
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        fee_vault.total_claimed_sol = fee_vault
+            .total_claimed_sol
+            .checked_add(amount)
+            .ok_or(ClaimFeesError::Overflow)?;
+
+        emit_cpi!(FeesClaimed {
+            recipient: ctx.accounts.fee_recipient.key(),
+            mint: None,
+            amount,
+        });
+        msg!(
+            "claimed {} lamports of fees to {}",
+            amount,
+            ctx.accounts.fee_recipient.key()
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimAssetFees<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        seeds = [FeeVault::SEED_PREFIX],
+        bump = fee_vault.bump,
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    #[account(mint::token_program = token_program)]
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+
+    // fee vault's ATA holding the fees collected in this asset
+    #[account(
+        mut,
+        associated_token::mint = asset_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub fee_vault_asset_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: only used as the owner of the recipient ATA, checked against config.fee_recipient
+    #[account(address = config.fee_recipient @ ClaimFeesError::FeeRecipientMismatch)]
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = asset_mint,
+        associated_token::authority = fee_recipient,
+        token::token_program = token_program,
+    )]
+    pub fee_recipient_asset_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // programs
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimAssetFees<'info> {
+    pub fn handler(ctx: Context<'_, '_, 'info, 'info, ClaimAssetFees<'info>>) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.roles,
+            &ctx.accounts.authority.key(),
+            &[Role::FeeManager],
+        )?;
+
+        let amount = ctx.accounts.fee_vault_asset_ata.amount;
+        require!(amount > 0, ClaimFeesError::NoFeesToClaim);
+
+        // prepare PDA signer seeds using helper
+        let bump_seed = [ctx.accounts.fee_vault.bump];
+        let fee_vault_seeds_with_bump = FeeVault::get_seeds_with_bump(&bump_seed);
+        let signer_seeds = &[&fee_vault_seeds_with_bump[..]];
+
+        let transfer_accounts = TransferChecked {
+            from: ctx.accounts.fee_vault_asset_ata.to_account_info(),
+            to: ctx.accounts.fee_recipient_asset_ata.to_account_info(),
+            authority: ctx.accounts.fee_vault.to_account_info(),
+            mint: ctx.accounts.asset_mint.to_account_info(),
+        };
+        // remaining accounts carry the asset's transfer hook accounts, if it has one.
+        // a Token-2022 transfer fee is withheld from what the recipient receives
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked(cpi_ctx, amount, ctx.accounts.asset_mint.decimals)?;
+
+        emit_cpi!(FeesClaimed {
+            recipient: ctx.accounts.fee_recipient.key(),
+            mint: Some(ctx.accounts.asset_mint.key()),
+            amount,
+        });
+        msg!(
+            "claimed {} of asset {} fees to {}",
+            amount,
+            ctx.accounts.asset_mint.key(),
+            ctx.accounts.fee_recipient.key()
+        );
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum ClaimFeesError {
+    #[msg("fee recipient does not match config.")]
+    FeeRecipientMismatch,
+    #[msg("no fees to claim.")]
+    NoFeesToClaim,
+    #[msg("calculation overflow")]
+    Overflow,
+}
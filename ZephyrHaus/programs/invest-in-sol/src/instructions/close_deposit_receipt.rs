@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::events::DepositReceiptClosed;
+use crate::state::DepositReceipt;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CloseDepositReceipt<'info> {
+    // anyone can close a consumed receipt, the rent always goes back to the depositor
+    pub signer: Signer<'info>,
+
+    /// CHECK: only receives the receipt's rent, tied to the receipt by its seeds.
+    #[account(mut)]
+    pub depositor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            DepositReceipt::SEED_PREFIX,
+            depositor.key().as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        bump = deposit_receipt.bump,
+        close = depositor,
+        constraint = deposit_receipt.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub deposit_receipt: Account<'info, DepositReceipt>,
+}
+
+impl<'info> CloseDepositReceipt<'info> {
+    pub fn handler(ctx: Context<CloseDepositReceipt>, nonce: u64) -> Result<()> {
+        // initialize_option closes receipts itself now, this sweeps the ones claimed
+        // before it did. unclaimed receipts go through cancel_deposit or expire_deposit_receipt.
+        require!(
+            ctx.accounts.deposit_receipt.nft_issued,
+            CloseDepositReceiptError::OptionNotIssued
+        );
+
+        emit_cpi!(DepositReceiptClosed {
+            depositor: ctx.accounts.depositor.key(),
+            nonce,
+            closed_by: ctx.accounts.signer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("closed deposit receipt {}", nonce);
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum CloseDepositReceiptError {
+    #[msg("the option for this deposit has not been issued yet.")]
+    OptionNotIssued,
+}
@@ -1,15 +1,13 @@
 use anchor_lang::prelude::*;
-use crate::state::{Config, OptionData};
+use crate::events::OptionAccountClosed;
+use crate::state::OptionData;
 use crate::ErrorCode;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseOptionAccount<'info> {
-    // Authority: The Config PDA, which signs to authorize the closure.
-    #[account(
-        seeds = [Config::SEED_PREFIX],
-        bump = config.bump
-    )]
-    pub config: Account<'info, Config>,
+    // Anyone can close a fully spent option, the rent always goes back to its payer.
+    pub signer: Signer<'info>,
 
     // OptionData PDA to be closed.
     #[account(
@@ -18,7 +16,11 @@ pub struct CloseOptionAccount<'info> {
         bump = option_data.bump,
         // Constraint: Ensure the option is fully spent.
         constraint = option_data.amount == 0 @ ErrorCode::OptionNotFullyConverted,
-        // Close to the receiver (which will be the config.authority).
+        // Constraint: Ensure no settled yield would be lost with the account.
+        constraint = option_data.accrued_yield == 0 @ ErrorCode::UnclaimedOptionYield,
+        // Constraint: Ensure the account has been migrated to the current layout.
+        constraint = option_data.is_current() @ ErrorCode::AccountVersionOutdated,
+        // Close to the receiver (the account's rent payer).
         close = receiver
     )]
     pub option_data: Account<'info, OptionData>,
@@ -28,32 +30,27 @@ pub struct CloseOptionAccount<'info> {
     pub option_mint: AccountInfo<'info>,
 
     // Receiver of the lamports from the closed option_data account.
-    // This will be the system account designated by config.authority.
-    #[account(mut)]
+    // This must be the wallet that paid option_data's rent.
+    #[account(
+        mut,
+        address = option_data.rent_payer @ ErrorCode::ReceiverRentPayerMismatch,
+    )]
     pub receiver: SystemAccount<'info>, // This MUST be a SystemAccount
 
     // System program, required by Anchor for account closure.
     pub system_program: Program<'info, System>,
 }
 
-// Additional constraint to verify receiver is the config.authority
-impl<'info> CloseOptionAccount<'info> {
-    pub fn validate_receiver(&self) -> Result<()> {
-        require_keys_eq!(
-            self.receiver.key(),
-            self.config.authority.ok_or(ErrorCode::AuthorityNotSet)?, // Assuming authority in Config is Option<Pubkey>
-            ErrorCode::ReceiverAuthorityMismatch
-        );
-        Ok(())
-    }
-}
-
 pub fn handler(ctx: Context<CloseOptionAccount>) -> Result<()> {
-    ctx.accounts.validate_receiver()?; // Perform the custom validation
-
     msg!("OptionData account for mint {} will be closed by Anchor.",
          ctx.accounts.option_mint.key());
     msg!("Lamports will be transferred to {}.", ctx.accounts.receiver.key());
+    emit_cpi!(OptionAccountClosed {
+        option_mint: ctx.accounts.option_mint.key(),
+        option_data: ctx.accounts.option_data.key(),
+        receiver: ctx.accounts.receiver.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     // Anchor handles the account closure and lamport transfer automatically.
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,17 +1,23 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program_option::COption, system_program};
 use anchor_spl::{
     associated_token::AssociatedToken,
     metadata::Metadata as MetaplexMetadataProgram,
-    token::Token,
-    token_interface::{burn, transfer_checked, Burn, Mint, TokenAccount, TransferChecked},
+    token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 use mpl_token_metadata::{
     accounts::{MasterEdition, Metadata},
     instructions::BurnV1CpiBuilder,
 }; // use BurnV1 for pNFTs
 
-use crate::state::{Config, OptionData}; // assuming treasury state is not needed directly here yet
+use crate::events::{
+    FeeCharged, FeeKind, FullyConverted, OptionYieldClaimed, PartiallyConverted,
+};
+use crate::instructions::option_nft::OptionMetadataAccounts;
+use crate::instructions::option_yield::pay_accrued_yield;
+use crate::instructions::token_transfer::transfer_checked;
+use crate::state::{Config, FeeVault, OptionData, Rounding, Treasury};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(amount_to_convert_ui: u64)]
 pub struct Convert<'info> {
@@ -22,6 +28,7 @@ pub struct Convert<'info> {
         mut,
         associated_token::mint = cn_mint,
         associated_token::authority = converter,
+        associated_token::token_program = token_program,
     )]
     pub converter_cn_ata: InterfaceAccount<'info, TokenAccount>,
 
@@ -29,7 +36,11 @@ pub struct Convert<'info> {
         mut,
         associated_token::mint = nft_mint, // NFT being burned
         associated_token::authority = converter,
-        token::token_program = token_program, // using token program, not 2022
+        associated_token::token_program = token_program, // the protocol's token program
+        constraint = !converter_option_ata.is_frozen() @ ConvertError::OptionFrozen,
+        // without the config as delegate the NFT could no longer be frozen
+        constraint = converter_option_ata.delegate == COption::Some(config.key())
+            @ crate::ErrorCode::OptionNotDelegated,
     )]
     pub converter_option_ata: InterfaceAccount<'info, TokenAccount>,
 
@@ -46,6 +57,7 @@ pub struct Convert<'info> {
         mut, // needs mut to decrement option_count
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
     )]
     pub config: Account<'info, Config>,
 
@@ -54,10 +66,28 @@ pub struct Convert<'info> {
         mut,
         associated_token::mint = pt_mint,
         associated_token::authority = config, // owned by config PDA
-        token::token_program = token_program, // use token program, not 2022
+        associated_token::token_program = token_program,
     )]
     pub protocol_pt_ata: InterfaceAccount<'info, TokenAccount>,
 
+    // treasury (read for the NAV used to value the conversion fee, tracks the converted CN
+    // and pays out the option's yield on a full conversion)
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    // collects the conversion fee
+    #[account(
+        mut,
+        seeds = [FeeVault::SEED_PREFIX],
+        bump = fee_vault.bump,
+    )]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+
     // mints
     #[account(
         mut, // needs mut for burn?
@@ -82,10 +112,18 @@ pub struct Convert<'info> {
         mut,
         seeds = [OptionData::SEED_PREFIX, nft_mint.key().as_ref()],
         bump = option_data.bump,
-        // no 'close = converter' constraint - we handle closure manually in separate instruction
+        // no 'close' constraint - only a full conversion closes it, handled in the handler
+        constraint = option_data.is_current() @ crate::ErrorCode::AccountVersionOutdated,
     )]
     pub option_data: Account<'info, OptionData>,
 
+    /// CHECK: receives option_data's rent on a full conversion, must be the wallet that paid it.
+    #[account(
+        mut,
+        address = option_data.rent_payer @ ConvertError::RentPayerMismatch,
+    )]
+    pub option_rent_payer: UncheckedAccount<'info>,
+
     // Metaplex accounts needed for BurnV1
     /// CHECK: checked by Metaplex CPI. PDA derived from nft_mint.
     #[account(
@@ -107,7 +145,7 @@ pub struct Convert<'info> {
     pub collection_metadata: UncheckedAccount<'info>, // required for BurnV1
 
     // programs
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub metadata_program: Program<'info, MetaplexMetadataProgram>,
@@ -117,7 +155,10 @@ pub struct Convert<'info> {
 }
 
 impl<'info> Convert<'info> {
-    pub fn handler(mut ctx: Context<Convert>, amount_to_convert_ui: u64) -> Result<()> {
+    pub fn handler(
+        mut ctx: Context<'_, '_, 'info, 'info, Convert<'info>>,
+        amount_to_convert_ui: u64,
+    ) -> Result<()> {
         // check locks first
         require!(!ctx.accounts.config.locked, ConvertError::ProtocolLocked);
         require!(
@@ -125,11 +166,16 @@ impl<'info> Convert<'info> {
             ConvertError::ConversionsLocked
         );
 
+        // settle the option's yield before its amount changes, so the amount
+        // being converted still earns everything distributed up to now
+        let yield_per_unit = ctx.accounts.config.yield_per_unit;
+        ctx.accounts.option_data.settle_yield(yield_per_unit)?;
+
         // get clock and check expiration
         let clock = Clock::get()?;
         let option_data = &ctx.accounts.option_data;
         require!(
-            !option_data.is_expired(clock.unix_timestamp),
+            !option_data.expired && !option_data.is_expired(clock.unix_timestamp),
             ConvertError::OptionExpired
         );
 
@@ -148,6 +194,12 @@ impl<'info> Convert<'info> {
             option_data.expiration
         );
 
+        // PT paid out is scaled by the option's conversion multiplier, rounded down
+        let pt_to_transfer = option_data.pt_for_cn(amount_to_convert_ui, Rounding::Down)?;
+
+        // charge the conversion fee (if any) before anything is burned
+        Self::charge_conversion_fee(&mut ctx, amount_to_convert_ui)?;
+
         // 1. burn CN tokens from converter's ATA
         let burn_cn_accounts = Burn {
             mint: ctx.accounts.cn_mint.to_account_info(),
@@ -161,6 +213,15 @@ impl<'info> Convert<'info> {
         burn(burn_cn_ctx, amount_to_convert_ui)?;
         msg!("burned {} CN tokens", amount_to_convert_ui);
 
+        // the burned CN's SOL stays in the treasury behind the PT, so it still counts
+        // towards the NAV's backing units
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.converted_cn = treasury
+            .converted_cn
+            .checked_add(amount_to_convert_ui)
+            .ok_or(ConvertError::ArithmeticOverflow)?;
+        ctx.accounts.cn_mint.reload()?;
+
         // prepare PDA signer seeds for PT transfer using helper and longer-lived binding
         let bump_seed = [ctx.accounts.config.bump];
         let config_seeds_with_bump = Config::get_seeds_with_bump(&bump_seed);
@@ -177,15 +238,20 @@ impl<'info> Convert<'info> {
             ctx.accounts.token_program.to_account_info(),
             transfer_pt_accounts,
             signer_seeds, // sign with config PDA seeds
-        );
-        transfer_checked(
+        )
+        // extra accounts of a PT transfer hook, if any
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        // a Token-2022 transfer fee on PT is withheld from what the converter receives,
+        // the protocol's PT always goes down by exactly pt_to_transfer
+        let pt_transfer_fee = transfer_checked(
             transfer_pt_ctx,
-            amount_to_convert_ui, // transfer the same amount as CN burned
+            pt_to_transfer, // CN burned scaled by the option's multiplier
             ctx.accounts.pt_mint.decimals, // decimals needed for transfer_checked
         )?;
         msg!(
-            "transferred {} PT tokens from protocol to converter",
-            amount_to_convert_ui
+            "transferred {} PT tokens from protocol to converter (transfer fee: {})",
+            pt_to_transfer,
+            pt_transfer_fee
         );
 
         // 3. handle NFT and OptionData based on conversion type
@@ -212,7 +278,7 @@ impl<'info> Convert<'info> {
                 .invoke() // invoke without signer seeds, converter signs
                 .map_err(|e| {
                     msg!("error burning nft: {:?}", e);
-                    ProgramError::from(e) // map metaplex error to program error
+                    e // already a program error
                 })?;
             msg!("burned NFT Option {}", ctx.accounts.nft_mint.key());
 
@@ -220,12 +286,44 @@ impl<'info> Convert<'info> {
             let option_data = &mut ctx.accounts.option_data;
             option_data.amount = 0;
 
+            // the NFT is gone, so nobody could claim the settled yield later
+            let yield_paid = pay_accrued_yield(
+                &mut ctx.accounts.treasury,
+                &ctx.accounts.converter.to_account_info(),
+                option_data,
+            )?;
+            if yield_paid > 0 {
+                emit_cpi!(OptionYieldClaimed {
+                    recipient: ctx.accounts.converter.key(),
+                    option_mint: ctx.accounts.nft_mint.key(),
+                    amount: yield_paid,
+                });
+                msg!("paid {} lamports of option yield to converter", yield_paid);
+            }
+
             // decrement the option count and total amount in the config account
             Self::decrement_config_option_count(&mut ctx)?;
             Self::decrement_total_option_amount(&mut ctx, amount_to_convert_ui)?;
 
-            msg!("OptionData for mint {} marked as fully spent (amount = 0). Use close_option_account to reclaim rent.",
-                 ctx.accounts.nft_mint.key());
+            emit_cpi!(FullyConverted {
+                converter: ctx.accounts.converter.key(),
+                option_mint: ctx.accounts.nft_mint.key(),
+                cn_burned: amount_to_convert_ui,
+                pt_transferred: pt_to_transfer,
+                yield_paid,
+                nav: Self::current_nav(&ctx)?,
+                timestamp: clock.unix_timestamp,
+            });
+
+            // nothing is left on the option, so its data goes with the NFT
+            ctx.accounts
+                .option_data
+                .close(ctx.accounts.option_rent_payer.to_account_info())?;
+            msg!(
+                "closed OptionData for mint {}, rent returned to {}",
+                ctx.accounts.nft_mint.key(),
+                ctx.accounts.option_rent_payer.key()
+            );
         } else {
             // Partial Conversion: Decrement OptionData.amount, don't burn NFT
             msg!(
@@ -246,8 +344,113 @@ impl<'info> Convert<'info> {
 
             // Decrement the total option amount in the config account
             Self::decrement_total_option_amount(&mut ctx, amount_to_convert_ui)?;
+
+            // the NFT stays with the holder, so its metadata shows the remaining amount
+            Self::update_option_metadata(&ctx, clock.unix_timestamp)?;
+
+            emit_cpi!(PartiallyConverted {
+                converter: ctx.accounts.converter.key(),
+                option_mint: ctx.accounts.nft_mint.key(),
+                cn_burned: amount_to_convert_ui,
+                pt_transferred: pt_to_transfer,
+                remaining_amount: ctx.accounts.option_data.amount,
+                nav: Self::current_nav(&ctx)?,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// rewrites the option NFT's metadata after a partial conversion, paid by the converter.
+    pub fn update_option_metadata(ctx: &Context<Convert>, current_timestamp: i64) -> Result<()> {
+        let converter = ctx.accounts.converter.to_account_info();
+        let config = ctx.accounts.config.to_account_info();
+        let nft_mint = ctx.accounts.nft_mint.to_account_info();
+        let nft_metadata = ctx.accounts.nft_metadata.to_account_info();
+        let nft_master_edition = ctx.accounts.nft_master_edition.to_account_info();
+        let metadata_program = ctx.accounts.metadata_program.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let sysvar_instructions = ctx.accounts.sysvar_instructions.to_account_info();
+
+        OptionMetadataAccounts {
+            payer: &converter,
+            config: &config,
+            option_mint: &nft_mint,
+            option_metadata: &nft_metadata,
+            option_master_edition: &nft_master_edition,
+            token_metadata_program: &metadata_program,
+            system_program: &system_program,
+            sysvar_instructions: &sysvar_instructions,
+        }
+        .update(
+            &ctx.accounts.option_data.key(),
+            &ctx.accounts.option_data,
+            current_timestamp,
+            ctx.accounts.config.bump,
+        )
+    }
+
+    /// the treasury nav after the conversion, rounded down. reported on the conversion events.
+    pub fn current_nav(ctx: &Context<Convert>) -> Result<u64> {
+        let idle_sol = Treasury::idle_sol(&ctx.accounts.treasury.to_account_info())?;
+        ctx.accounts.treasury.calculate_nav(
+            idle_sol,
+            ctx.accounts.cn_mint.supply,
+            Rounding::Down,
+        )
+    }
+
+    /// charges config.conversion_fee (bps) on the SOL value of the converted CN,
+    /// paid by the converter into the fee vault.
+    pub fn charge_conversion_fee(ctx: &mut Context<Convert>, amount_to_convert: u64) -> Result<()> {
+        let Some(fee_bps) = ctx.accounts.config.conversion_fee else {
+            return Ok(());
+        };
+
+        // value the converted CN at the current nav, rounded in the protocol's favour
+        let idle_sol = Treasury::idle_sol(&ctx.accounts.treasury.to_account_info())?;
+        let nav = ctx.accounts.treasury.calculate_nav(
+            idle_sol,
+            ctx.accounts.cn_mint.supply,
+            Rounding::Up,
+        )?;
+        let converted_value = Treasury::lamports_for_tokens(amount_to_convert, nav)?;
+        let fee = FeeVault::calculate_fee(converted_value, Some(fee_bps))?;
+        if fee == 0 {
+            return Ok(());
         }
 
+        let transfer_accounts = system_program::Transfer {
+            from: ctx.accounts.converter.to_account_info(),
+            to: ctx.accounts.fee_vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            transfer_accounts,
+        );
+        system_program::transfer(cpi_ctx, fee)?;
+
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        fee_vault.total_collected_sol = fee_vault
+            .total_collected_sol
+            .checked_add(fee)
+            .ok_or(ConvertError::ArithmeticOverflow)?;
+
+        emit_cpi!(FeeCharged {
+            kind: FeeKind::Conversion,
+            payer: ctx.accounts.converter.key(),
+            mint: None,
+            base_amount: converted_value,
+            fee_bps,
+            fee_amount: fee,
+        });
+        msg!(
+            "charged conversion fee of {} lamports on {} lamports converted",
+            fee,
+            converted_value
+        );
+
         Ok(())
     }
 
@@ -316,6 +519,8 @@ pub enum ConvertError {
     ConversionsLocked,
     #[msg("option has expired.")]
     OptionExpired,
+    #[msg("option NFT is frozen.")]
+    OptionFrozen,
     #[msg("amount to convert must be greater than zero.")]
     ZeroAmountToConvert,
     #[msg("amount to convert exceeds remaining amount on the option NFT.")]
@@ -324,4 +529,6 @@ pub enum ConvertError {
     InsufficientTotalOptionAmount,
     #[msg("arithmetic overflow occurred.")]
     ArithmeticOverflow,
+    #[msg("option rent payer does not match the option data.")]
+    RentPayerMismatch,
 }
@@ -1,12 +1,16 @@
 use anchor_lang::{prelude::*, system_program};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::Token,
-    token_interface::{mint_to, Mint, MintTo, TokenAccount},
+    token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface},
 };
 
-use crate::state::{Config, DepositReceipt, Treasury};
+use crate::events::{Deposited, FeeCharged, FeeKind};
+use crate::state::{
+    allowlist_leaf, pt_for_cn, verify_allowlist_proof, AllowlistProof, Config, DepositReceipt,
+    DurationTier, FeeVault, Rounding, Treasury, UserPosition,
+};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(amount: u64, option_duration: u32)]
 pub struct Deposit<'info> {
@@ -27,18 +31,26 @@ pub struct Deposit<'info> {
     )]
     pub depositor_cn_ata: InterfaceAccount<'info, TokenAccount>,
 
+    // a fresh receipt per deposit, keyed by the global deposit nonce. the nonce only goes
+    // up, so a closed receipt is never re-created (plain init fails on a live one)
     #[account(
-        init_if_needed,
+        init,
         payer = depositor,
-        seeds = [DepositReceipt::SEED_PREFIX, depositor.key().as_ref()],
+        seeds = [
+            DepositReceipt::SEED_PREFIX,
+            depositor.key().as_ref(),
+            &config.deposit_nonce.to_le_bytes(),
+        ],
         bump,
         space = 8 + DepositReceipt::INIT_SPACE,
     )]
     pub deposit_receipt: Account<'info, DepositReceipt>,
 
     #[account(
+        mut, // needs mut to advance the deposit nonce
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
     )]
     pub config: Account<'info, Config>,
 
@@ -46,9 +58,36 @@ pub struct Deposit<'info> {
         mut,
         seeds = [b"treasury"],
         bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
     )]
     pub treasury: Account<'info, Treasury>,
 
+    // collects the protocol fee
+    #[account(
+        mut,
+        seeds = [FeeVault::SEED_PREFIX],
+        bump = fee_vault.bump,
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    // tier for the requested option duration (must have been added by the authority)
+    #[account(
+        mut,
+        seeds = [DurationTier::SEED_PREFIX, &option_duration.to_le_bytes()],
+        bump = duration_tier.bump,
+    )]
+    pub duration_tier: Account<'info, DurationTier>,
+
+    // depositor's running deposit total, for the per-wallet cap (created on the first deposit)
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [UserPosition::SEED_PREFIX, depositor.key().as_ref()],
+        bump,
+        space = 8 + UserPosition::INIT_SPACE,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
     // mints (checked against config)
     #[account(
         mut,
@@ -73,7 +112,7 @@ pub struct Deposit<'info> {
     pub protocol_pt_ata: InterfaceAccount<'info, TokenAccount>,
 
     // programs
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -88,129 +127,438 @@ impl<'info> Deposit<'info> {
             DepositError::DepositsLocked
         );
         require!(
-            ctx.accounts.deposit_receipt.initialized == false,
-            DepositError::UnclaimedDepositPending
+            ctx.accounts.duration_tier.enabled,
+            DepositError::DurationTierDisabled
         );
         Ok(())
     }
 
-    pub fn deposit_sol(ctx: &mut Context<Deposit>, amount: u64) -> Result<()> {
-        require!(amount > 0, DepositError::ZeroAmount);
+    /// moves the protocol fee on `amount` from `payer` into the fee vault and returns it.
+    pub fn pay_sol_fee<'a>(
+        config: &Config,
+        payer: AccountInfo<'a>,
+        fee_vault: &mut Account<'a, FeeVault>,
+        system_program: AccountInfo<'a>,
+        amount: u64,
+    ) -> Result<u64> {
+        let fee = FeeVault::calculate_fee(amount, config.fee)?;
+        if fee == 0 {
+            return Ok(0);
+        }
+
         let transfer_accounts = system_program::Transfer {
-            from: ctx.accounts.depositor_sol_account.to_account_info(),
-            to: ctx.accounts.treasury.to_account_info(),
+            from: payer,
+            to: fee_vault.to_account_info(),
         };
-        let cpi_ctx = CpiContext::new(
+        let cpi_ctx = CpiContext::new(system_program, transfer_accounts);
+        system_program::transfer(cpi_ctx, fee)?;
+
+        fee_vault.total_collected_sol = fee_vault
+            .total_collected_sol
+            .checked_add(fee)
+            .ok_or(DepositError::Overflow)?;
+        msg!("charged deposit fee of {} lamports", fee);
+        Ok(fee)
+    }
+
+    /// the `FeeCharged` event for a deposit fee of `fee` on `amount`.
+    /// `mint` is the deposited asset, `None` for SOL.
+    pub fn deposit_fee_charged(
+        config: &Config,
+        payer: Pubkey,
+        mint: Option<Pubkey>,
+        amount: u64,
+        fee: u64,
+    ) -> FeeCharged {
+        FeeCharged {
+            kind: FeeKind::Deposit,
+            payer,
+            mint,
+            base_amount: amount,
+            fee_bps: config.fee.unwrap_or_default(),
+            fee_amount: fee,
+        }
+    }
+
+    /// pays the protocol fee on `amount` into the fee vault.
+    /// returns the amount left to deposit into the treasury.
+    pub fn charge_deposit_fee(ctx: &mut Context<Deposit>, amount: u64) -> Result<u64> {
+        require!(amount > 0, DepositError::ZeroAmount);
+        let fee = Self::pay_sol_fee(
+            &ctx.accounts.config,
+            ctx.accounts.depositor_sol_account.to_account_info(),
+            &mut ctx.accounts.fee_vault,
             ctx.accounts.system_program.to_account_info(),
-            transfer_accounts,
-        );
+            amount,
+        )?;
+        if fee > 0 {
+            emit_cpi!(Self::deposit_fee_charged(
+                &ctx.accounts.config,
+                ctx.accounts.depositor.key(),
+                None,
+                amount,
+                fee,
+            ));
+        }
+
+        amount
+            .checked_sub(fee)
+            .ok_or(DepositError::Overflow.into())
+    }
+
+    /// moves `amount` lamports from `from` into the treasury and records them as
+    /// deposited and as backing for the CN issued for them.
+    pub fn transfer_sol_to_treasury<'a>(
+        from: AccountInfo<'a>,
+        treasury: &mut Account<'a, Treasury>,
+        system_program: AccountInfo<'a>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, DepositError::ZeroAmount);
+        let transfer_accounts = system_program::Transfer {
+            from,
+            to: treasury.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(system_program, transfer_accounts);
         system_program::transfer(cpi_ctx, amount)?;
         msg!("transferred {} SOL to treasury vault", amount);
 
         // update treasury state to track total sol deposits
-        let treasury = &mut ctx.accounts.treasury;
         treasury.total_deposited_sol = treasury
             .total_deposited_sol
             .checked_add(amount)
-            .ok_or(ProgramError::ArithmeticOverflow)?;
-
+            .ok_or(DepositError::Overflow)?;
+        treasury.add_backing(amount)?;
         Ok(())
     }
 
-    pub fn calculate_tokens_to_mint(ctx: &Context<Deposit>, amount: u64) -> Result<u64> {
-        // calculate nav and determine tokens to mint
-        let nav = ctx.accounts.treasury.calculate_nav()?;
-        // todo: add precision handling here. for now, nav=1 so amount/nav = amount
-        let tokens_to_mint = amount
-            .checked_div(nav)
-            .ok_or(ProgramError::ArithmeticOverflow)?; // placeholder calculation
+    pub fn deposit_sol(ctx: &mut Context<Deposit>, amount: u64) -> Result<()> {
+        Self::transfer_sol_to_treasury(
+            ctx.accounts.depositor_sol_account.to_account_info(),
+            &mut ctx.accounts.treasury,
+            ctx.accounts.system_program.to_account_info(),
+            amount,
+        )
+    }
+
+    /// the CN to mint for a deposit worth `value` lamports at the current NAV.
+    /// `cn_supply` is the CN supply before the deposit.
+    pub fn tokens_for_value<'a>(
+        treasury: &mut Account<'a, Treasury>,
+        cn_supply: u64,
+        value: u64,
+    ) -> Result<u64> {
+        // calculate nav and determine tokens to mint.
+        // nav is rounded up and tokens down so rounding always favours the protocol.
+        let idle_sol = Treasury::idle_sol(&treasury.to_account_info())?;
+        let nav = treasury.calculate_nav(idle_sol, cn_supply, Rounding::Up)?;
+        let tokens_to_mint = Treasury::tokens_for_lamports(value, nav)?;
+        require!(tokens_to_mint > 0, DepositError::DepositTooSmall);
+        treasury.update_nav(nav)?;
 
         msg!("calculated NAV: {}", nav);
         msg!("tokens to mint: {}", tokens_to_mint);
         Ok(tokens_to_mint)
     }
 
-    pub fn set_deposit_receipt(ctx: &mut Context<Deposit>, amount: u64, option_duration: u32) -> Result<()> {
-        // Define allowed durations (in seconds)
-        const THREE_MONTHS: u32 = 3 * 30 * 24 * 60 * 60; // 7,776,000 seconds
-        const SIX_MONTHS: u32 = 6 * 30 * 24 * 60 * 60;   // 15,552,000 seconds
-        const TWELVE_MONTHS: u32 = 12 * 30 * 24 * 60 * 60; // 31,104,000 seconds
-        const TWENTY_FOUR_MONTHS: u32 = 24 * 30 * 24 * 60 * 60; // 62,208,000 seconds
+    pub fn calculate_tokens_to_mint(ctx: &mut Context<Deposit>, amount: u64) -> Result<u64> {
+        Self::tokens_for_value(
+            &mut ctx.accounts.treasury,
+            ctx.accounts.cn_mint.supply,
+            amount,
+        )
+    }
+
+    /// counts `value` (lamports, after fees) against the deposit caps: the treasury-wide
+    /// cap, the depositor's lifetime cap and the per-epoch limit.
+    pub fn enforce_deposit_caps(
+        config: &mut Config,
+        treasury: &Treasury,
+        user_position: &mut UserPosition,
+        depositor: Pubkey,
+        bump: u8,
+        value: u64,
+    ) -> Result<()> {
+        // a new position is set up on the depositor's first deposit
+        if user_position.owner == Pubkey::default() {
+            user_position.owner = depositor;
+            user_position.bump = bump;
+        }
+
+        let total_deposits = treasury.total_deposits().ok_or(DepositError::Overflow)?;
+        require!(
+            config.has_deposit_capacity(total_deposits, value),
+            DepositError::TotalDepositCapExceeded
+        );
+        require!(
+            user_position.has_capacity(config.max_wallet_deposit, value),
+            DepositError::WalletDepositCapExceeded
+        );
+
+        let epoch = Clock::get()?.epoch;
+        let epoch_deposited = config
+            .epoch_deposited_after(epoch, value)
+            .ok_or(DepositError::Overflow)?;
+        require!(
+            config.has_epoch_capacity(epoch_deposited),
+            DepositError::EpochDepositLimitExceeded
+        );
+
+        config.deposit_epoch = epoch;
+        config.epoch_deposited = epoch_deposited;
+        user_position.total_deposited = user_position
+            .total_deposited
+            .checked_add(value)
+            .ok_or(DepositError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn apply_deposit_caps(ctx: &mut Context<Deposit>, value: u64) -> Result<()> {
+        Self::enforce_deposit_caps(
+            &mut ctx.accounts.config,
+            &ctx.accounts.treasury,
+            &mut ctx.accounts.user_position,
+            ctx.accounts.depositor.key(),
+            ctx.bumps.user_position,
+            value,
+        )
+    }
+
+    /// while the allowlist is on, checks the depositor's proof and counts `value`
+    /// (lamports, after fees) against their allocation. does nothing while it is off.
+    pub fn enforce_allowlist(
+        config: &Config,
+        user_position: &mut UserPosition,
+        depositor: &Pubkey,
+        allowlist_proof: Option<AllowlistProof>,
+        value: u64,
+    ) -> Result<()> {
+        let root = match config.allowlist_root {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+        let allowlist_proof = allowlist_proof.ok_or(DepositError::NotAllowlisted)?;
+        let leaf = allowlist_leaf(depositor, allowlist_proof.allocation);
+        require!(
+            verify_allowlist_proof(&root, leaf, &allowlist_proof.proof),
+            DepositError::NotAllowlisted
+        );
+
+        // an allocation of 0 allows the address without a limit
+        let allowlist_used = user_position
+            .allowlist_used_after(&root, value)
+            .ok_or(DepositError::Overflow)?;
+        require!(
+            allowlist_proof.allocation == 0 || allowlist_used <= allowlist_proof.allocation,
+            DepositError::AllowlistAllocationExceeded
+        );
+
+        user_position.allowlist_root = root;
+        user_position.allowlist_used = allowlist_used;
+        Ok(())
+    }
+
+    pub fn apply_allowlist(
+        ctx: &mut Context<Deposit>,
+        value: u64,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
+        Self::enforce_allowlist(
+            &ctx.accounts.config,
+            &mut ctx.accounts.user_position,
+            &ctx.accounts.depositor.key(),
+            allowlist_proof,
+            value,
+        )
+    }
 
-        // Validate that option_duration is one of the allowed values
+    /// counts `amount` against the duration tier's cap.
+    pub fn reserve_tier_capacity(duration_tier: &mut DurationTier, amount: u64) -> Result<()> {
         require!(
-            option_duration == THREE_MONTHS ||
-            option_duration == SIX_MONTHS ||
-            option_duration == TWELVE_MONTHS ||
-            option_duration == TWENTY_FOUR_MONTHS,
-            DepositError::InvalidOptionDuration
+            duration_tier.has_capacity(amount),
+            DepositError::DurationTierCapExceeded
         );
-        
+        duration_tier.total_issued = duration_tier
+            .total_issued
+            .checked_add(amount)
+            .ok_or(DepositError::Overflow)?;
+        Ok(())
+    }
+
+    /// counts `amount` against the duration tier's cap and returns the expiration of an
+    /// option issued now.
+    pub fn calculate_expiration(duration_tier: &mut DurationTier, amount: u64) -> Result<i64> {
+        Self::reserve_tier_capacity(duration_tier, amount)?;
+
         let clock = Clock::get()?;
-        let current_timestamp = clock.unix_timestamp;
-        let expiration = current_timestamp
-            .checked_add(option_duration as i64)
+        duration_tier
+            .expiration_from(clock.unix_timestamp)
+            .ok_or(DepositError::Overflow.into())
+    }
+
+    /// counts a new receipt's CN as set aside for the option it can be claimed for.
+    pub fn add_receipt_amount(config: &mut Config, amount: u64) -> Result<()> {
+        config.total_receipt_amount = config
+            .total_receipt_amount
+            .checked_add(amount)
+            .ok_or(DepositError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn set_deposit_receipt(
+        ctx: &mut Context<Deposit>,
+        amount: u64,
+        value_lamports: u64,
+    ) -> Result<()> {
+        // the option's expiration is only set once it is issued, the receipt just
+        // has to be claimed within the claim window
+        Self::reserve_tier_capacity(&mut ctx.accounts.duration_tier, amount)?;
+        let claim_deadline = Clock::get()?
+            .unix_timestamp
+            .checked_add(ctx.accounts.config.claim_window)
             .ok_or(DepositError::Overflow)?;
 
+        let nonce = ctx
+            .accounts
+            .config
+            .take_deposit_nonce()
+            .ok_or(DepositError::Overflow)?;
+        Self::add_receipt_amount(&mut ctx.accounts.config, amount)?;
+
         *ctx.accounts.deposit_receipt = DepositReceipt {
             initialized: true,
             nft_issued: false,
             amount,
-            expiration,
+            value_lamports,
+            option_duration: ctx.accounts.duration_tier.duration,
+            claim_deadline,
+            conversion_multiplier_bps: ctx.accounts.duration_tier.conversion_multiplier_bps,
+            nonce,
             bump: ctx.bumps.deposit_receipt,
+            version: DepositReceipt::VERSION,
+            asset_mint: None,
+            asset_amount: value_lamports,
+            // what cancel_deposit needs to take the deposit back off the caps
+            deposit_epoch: ctx.accounts.config.deposit_epoch,
+            allowlist_root: ctx.accounts.config.allowlist_root,
         };
 
         msg!(
-            "issuing deposit receipt - amount: {}, expiration: {}",
+            "issuing deposit receipt - nonce: {}, amount: {}, claim deadline: {}",
+            nonce,
             amount,
-            expiration
+            claim_deadline
         );
         Ok(())
     }
 
-    pub fn mint_cn_to_depositor(ctx: &Context<Deposit>, tokens_to_mint: u64) -> Result<()> {
+    /// mints `amount` of `mint` to `to`, signed by the config PDA that is its mint authority.
+    fn mint_as_config<'a>(
+        config: &Account<'a, Config>,
+        mint: AccountInfo<'a>,
+        to: AccountInfo<'a>,
+        token_program: AccountInfo<'a>,
+        amount: u64,
+    ) -> Result<()> {
         // prepare PDA signer seeds using helper
-        let bump_seed = [ctx.accounts.config.bump];
+        let bump_seed = [config.bump];
         let config_seeds_with_bump = Config::get_seeds_with_bump(&bump_seed);
         let signer_seeds = &[&config_seeds_with_bump[..]];
 
-        // mint CN tokens to depositor's CN ATA
-        let cpi_accounts_cn = MintTo {
-            mint: ctx.accounts.cn_mint.to_account_info(),
-            to: ctx.accounts.depositor_cn_ata.to_account_info(),
-            authority: ctx.accounts.config.to_account_info(),
+        let cpi_accounts = MintTo {
+            mint,
+            to,
+            authority: config.to_account_info(),
         };
-        let cpi_ctx_cn = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            cpi_accounts_cn,
-            signer_seeds,
-        );
-        mint_to(cpi_ctx_cn, tokens_to_mint)?;
-        msg!("minted {} CN tokens to depositor", tokens_to_mint);
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        mint_to(cpi_ctx, amount)
+    }
 
+    /// mints the CN issued for a deposit to the depositor's CN ATA.
+    pub fn mint_cn<'a>(
+        config: &Account<'a, Config>,
+        cn_mint: &InterfaceAccount<'a, Mint>,
+        depositor_cn_ata: &InterfaceAccount<'a, TokenAccount>,
+        token_program: &Interface<'a, TokenInterface>,
+        tokens_to_mint: u64,
+    ) -> Result<()> {
+        Self::mint_as_config(
+            config,
+            cn_mint.to_account_info(),
+            depositor_cn_ata.to_account_info(),
+            token_program.to_account_info(),
+            tokens_to_mint,
+        )?;
+        msg!("minted {} CN tokens to depositor", tokens_to_mint);
         Ok(())
     }
 
-    pub fn mint_pt_to_protocol(ctx: &Context<Deposit>, tokens_to_mint: u64) -> Result<()> {
-        // prepare PDA signer seeds using helper
-        let bump_seed = [ctx.accounts.config.bump];
-        let config_seeds_with_bump = Config::get_seeds_with_bump(&bump_seed);
-        let signer_seeds = &[&config_seeds_with_bump[..]];
+    /// mints the PT backing `tokens_to_mint` CN to the protocol's PT ATA.
+    /// returns the amount of PT minted.
+    pub fn mint_pt<'a>(
+        config: &Account<'a, Config>,
+        duration_tier: &DurationTier,
+        pt_mint: &InterfaceAccount<'a, Mint>,
+        protocol_pt_ata: &InterfaceAccount<'a, TokenAccount>,
+        token_program: &Interface<'a, TokenInterface>,
+        tokens_to_mint: u64,
+    ) -> Result<u64> {
+        // PT is scaled by the tier's conversion multiplier so every conversion stays fully backed
+        let pt_to_mint = pt_for_cn(
+            tokens_to_mint,
+            duration_tier.conversion_multiplier_bps,
+            Rounding::Up,
+        )?;
 
-        // mint PT tokens to protocol's PT ATA
-        let cpi_accounts_pt = MintTo {
-            mint: ctx.accounts.pt_mint.to_account_info(),
-            to: ctx.accounts.protocol_pt_ata.to_account_info(),
-            authority: ctx.accounts.config.to_account_info(),
-        };
-        let cpi_ctx_pt = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            cpi_accounts_pt,
-            signer_seeds,
-        );
-        mint_to(cpi_ctx_pt, tokens_to_mint)?;
-        msg!("minted {} PT tokens to protocol's ATA", tokens_to_mint);
+        Self::mint_as_config(
+            config,
+            pt_mint.to_account_info(),
+            protocol_pt_ata.to_account_info(),
+            token_program.to_account_info(),
+            pt_to_mint,
+        )?;
+        msg!("minted {} PT tokens to protocol's ATA", pt_to_mint);
+        Ok(pt_to_mint)
+    }
 
+    pub fn mint_cn_to_depositor(ctx: &Context<Deposit>, tokens_to_mint: u64) -> Result<()> {
+        Self::mint_cn(
+            &ctx.accounts.config,
+            &ctx.accounts.cn_mint,
+            &ctx.accounts.depositor_cn_ata,
+            &ctx.accounts.token_program,
+            tokens_to_mint,
+        )
+    }
+
+    /// returns the amount of PT minted.
+    pub fn mint_pt_to_protocol(ctx: &Context<Deposit>, tokens_to_mint: u64) -> Result<u64> {
+        Self::mint_pt(
+            &ctx.accounts.config,
+            &ctx.accounts.duration_tier,
+            &ctx.accounts.pt_mint,
+            &ctx.accounts.protocol_pt_ata,
+            &ctx.accounts.token_program,
+            tokens_to_mint,
+        )
+    }
+
+    pub fn emit_deposited(ctx: &Context<Deposit>, amount: u64, pt_minted: u64) -> Result<()> {
+        let receipt = &ctx.accounts.deposit_receipt;
+        emit_cpi!(Deposited {
+            depositor: ctx.accounts.depositor.key(),
+            mint: None,
+            amount,
+            value_lamports: amount,
+            cn_minted: receipt.amount,
+            pt_minted,
+            nav: ctx.accounts.treasury.nav,
+            nonce: receipt.nonce,
+            option_duration: receipt.option_duration,
+            expiration: None,
+            claim_deadline: Some(receipt.claim_deadline),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 }
@@ -227,8 +575,24 @@ pub enum DepositError {
     DepositsLocked,
     #[msg("calculation overflow")]
     Overflow,
-    #[msg("unclaimed deposit receipt pending.")]
-    UnclaimedDepositPending,
-    #[msg("invalid option duration - must be 3, 6, 12, or 24 months")]
-    InvalidOptionDuration,
+    #[msg("the duration tier for this option duration is disabled.")]
+    DurationTierDisabled,
+    #[msg("deposit amount is too small to mint any tokens at the current NAV.")]
+    DepositTooSmall,
+    #[msg("deposits of this asset are disabled.")]
+    AssetDepositsDisabled,
+    #[msg("asset price is stale - it must be refreshed by the price authority.")]
+    StaleAssetPrice,
+    #[msg("the duration tier's cap would be exceeded by this deposit.")]
+    DurationTierCapExceeded,
+    #[msg("the protocol's total deposit cap would be exceeded by this deposit.")]
+    TotalDepositCapExceeded,
+    #[msg("the per-wallet deposit cap would be exceeded by this deposit.")]
+    WalletDepositCapExceeded,
+    #[msg("the deposit limit for the current epoch would be exceeded by this deposit.")]
+    EpochDepositLimitExceeded,
+    #[msg("depositor is not on the allowlist or the proof is invalid.")]
+    NotAllowlisted,
+    #[msg("the depositor's allowlist allocation would be exceeded by this deposit.")]
+    AllowlistAllocationExceeded,
 }
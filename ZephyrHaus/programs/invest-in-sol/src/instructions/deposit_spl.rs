@@ -0,0 +1,413 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::events::Deposited;
+use crate::instructions::deposit::{Deposit, DepositError};
+use crate::instructions::token_transfer::{transfer_checked, transfer_fee};
+use crate::state::{
+    AllowlistProof, Config, DepositReceipt, DurationTier, FeeVault, Treasury, TreasuryAsset,
+    UserPosition,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(amount: u64, option_duration: u32)]
+pub struct DepositSpl<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    // depositor's ATA for the deposited asset
+    #[account(
+        mut,
+        associated_token::mint = asset_mint,
+        associated_token::authority = depositor,
+        associated_token::token_program = asset_token_program,
+    )]
+    pub depositor_asset_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // depositor's CN ATA (initialized if needed)
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = cn_mint,
+        associated_token::authority = depositor,
+        token::token_program = token_program, // specify token program for ATA
+    )]
+    pub depositor_cn_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // a fresh receipt per deposit, keyed by the global deposit nonce. the nonce only goes
+    // up, so a closed receipt is never re-created (plain init fails on a live one)
+    #[account(
+        init,
+        payer = depositor,
+        seeds = [
+            DepositReceipt::SEED_PREFIX,
+            depositor.key().as_ref(),
+            &config.deposit_nonce.to_le_bytes(),
+        ],
+        bump,
+        space = 8 + DepositReceipt::INIT_SPACE,
+    )]
+    pub deposit_receipt: Account<'info, DepositReceipt>,
+
+    #[account(
+        mut, // needs mut to advance the deposit nonce
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    // whitelisted asset (must have been added by the authority)
+    #[account(
+        mut,
+        seeds = [TreasuryAsset::SEED_PREFIX, asset_mint.key().as_ref()],
+        bump = treasury_asset.bump,
+    )]
+    pub treasury_asset: Account<'info, TreasuryAsset>,
+
+    #[account(mint::token_program = asset_token_program)]
+    pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // treasury-owned vault receiving the deposit
+    #[account(
+        mut,
+        address = treasury_asset.vault @ DepositError::AddressMismatch,
+        token::token_program = asset_token_program,
+    )]
+    pub treasury_asset_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [FeeVault::SEED_PREFIX],
+        bump = fee_vault.bump,
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    // tier for the requested option duration (must have been added by the authority)
+    #[account(
+        mut,
+        seeds = [DurationTier::SEED_PREFIX, &option_duration.to_le_bytes()],
+        bump = duration_tier.bump,
+    )]
+    pub duration_tier: Account<'info, DurationTier>,
+
+    // depositor's running deposit total, for the per-wallet cap (created on the first deposit)
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [UserPosition::SEED_PREFIX, depositor.key().as_ref()],
+        bump,
+        space = 8 + UserPosition::INIT_SPACE,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    // fee vault's ATA collecting the protocol fee in this asset
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = asset_mint,
+        associated_token::authority = fee_vault, // fee vault PDA owns its asset ATAs
+        associated_token::token_program = asset_token_program,
+    )]
+    pub fee_vault_asset_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // mints (checked against config)
+    #[account(
+        mut,
+        address = config.cn_mint @ DepositError::AddressMismatch
+    )]
+    pub cn_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = config.pt_mint @ DepositError::AddressMismatch
+    )]
+    pub pt_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // protocol's PT ATA
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = pt_mint,
+        associated_token::authority = config, // config PDA owns the protocol's PT ATA
+        token::token_program = token_program, // specify token program for ATA
+    )]
+    pub protocol_pt_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // programs
+    pub token_program: Interface<'info, TokenInterface>, // CN and PT
+    // the deposited asset's token program, may differ from CN/PT's
+    pub asset_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> DepositSpl<'info> {
+    pub fn assert_protocol_state(ctx: &Context<DepositSpl>) -> Result<()> {
+        // Ensure the protocol is not locked
+        require!(!ctx.accounts.config.locked, DepositError::ProtocolLocked);
+        require!(
+            !ctx.accounts.config.deposit_locked,
+            DepositError::DepositsLocked
+        );
+        require!(
+            ctx.accounts.duration_tier.enabled,
+            DepositError::DurationTierDisabled
+        );
+
+        // Ensure the asset is accepted and its price can be trusted
+        let treasury_asset = &ctx.accounts.treasury_asset;
+        require!(treasury_asset.enabled, DepositError::AssetDepositsDisabled);
+        require!(
+            treasury_asset.is_price_fresh(Clock::get()?.unix_timestamp),
+            DepositError::StaleAssetPrice
+        );
+        Ok(())
+    }
+
+    /// pays the protocol fee on `amount` into the fee vault's ATA for the asset.
+    /// returns the amount left to deposit into the treasury.
+    pub fn charge_deposit_fee(
+        ctx: &Context<'_, '_, 'info, 'info, DepositSpl<'info>>,
+        amount: u64,
+    ) -> Result<u64> {
+        require!(amount > 0, DepositError::ZeroAmount);
+        let fee = FeeVault::calculate_fee(amount, ctx.accounts.config.fee)?;
+        if fee == 0 {
+            return Ok(amount);
+        }
+
+        let transfer_accounts = TransferChecked {
+            from: ctx.accounts.depositor_asset_ata.to_account_info(),
+            to: ctx.accounts.fee_vault_asset_ata.to_account_info(),
+            authority: ctx.accounts.depositor.to_account_info(),
+            mint: ctx.accounts.asset_mint.to_account_info(),
+        };
+        // remaining accounts carry the asset's transfer hook accounts, if it has one
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.asset_token_program.to_account_info(),
+            transfer_accounts,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked(cpi_ctx, fee, ctx.accounts.asset_mint.decimals)?;
+
+        emit_cpi!(Deposit::deposit_fee_charged(
+            &ctx.accounts.config,
+            ctx.accounts.depositor.key(),
+            Some(ctx.accounts.asset_mint.key()),
+            amount,
+            fee,
+        ));
+        msg!("charged deposit fee of {} in asset {}", fee, ctx.accounts.asset_mint.key());
+
+        amount
+            .checked_sub(fee)
+            .ok_or(DepositError::Overflow.into())
+    }
+
+    pub fn apply_allowlist(
+        ctx: &mut Context<DepositSpl>,
+        value: u64,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
+        Deposit::enforce_allowlist(
+            &ctx.accounts.config,
+            &mut ctx.accounts.user_position,
+            &ctx.accounts.depositor.key(),
+            allowlist_proof,
+            value,
+        )
+    }
+
+    pub fn apply_deposit_caps(ctx: &mut Context<DepositSpl>, value: u64) -> Result<()> {
+        Deposit::enforce_deposit_caps(
+            &mut ctx.accounts.config,
+            &ctx.accounts.treasury,
+            &mut ctx.accounts.user_position,
+            ctx.accounts.depositor.key(),
+            ctx.bumps.user_position,
+            value,
+        )
+    }
+
+    pub fn calculate_tokens_to_mint(
+        ctx: &mut Context<DepositSpl>,
+        amount: u64,
+    ) -> Result<(u64, u64)> {
+        require!(amount > 0, DepositError::ZeroAmount);
+
+        // a Token-2022 transfer fee is withheld from what reaches the vault, so only
+        // the amount received is valued
+        let received = Self::amount_received(ctx, amount)?;
+
+        // value the deposit in SOL terms using the asset's configured price
+        let value_lamports = ctx
+            .accounts
+            .treasury_asset
+            .value_in_lamports(received)?;
+
+        msg!("deposit valued at {} lamports", value_lamports);
+        let tokens_to_mint = Deposit::tokens_for_value(
+            &mut ctx.accounts.treasury,
+            ctx.accounts.cn_mint.supply,
+            value_lamports,
+        )?;
+        Ok((tokens_to_mint, value_lamports))
+    }
+
+    /// the part of a transfer of `amount` that reaches the vault after the asset's transfer fee.
+    pub fn amount_received(ctx: &Context<DepositSpl>, amount: u64) -> Result<u64> {
+        let fee = transfer_fee(&ctx.accounts.asset_mint.to_account_info(), amount)?;
+        amount
+            .checked_sub(fee)
+            .ok_or(DepositError::Overflow.into())
+    }
+
+    /// returns the amount that reached the vault.
+    pub fn deposit_asset(
+        ctx: &mut Context<'_, '_, 'info, 'info, DepositSpl<'info>>,
+        amount: u64,
+        value_lamports: u64,
+    ) -> Result<u64> {
+        // transfer the asset from the depositor into the treasury's vault
+        let transfer_accounts = TransferChecked {
+            from: ctx.accounts.depositor_asset_ata.to_account_info(),
+            to: ctx.accounts.treasury_asset_vault.to_account_info(),
+            authority: ctx.accounts.depositor.to_account_info(),
+            mint: ctx.accounts.asset_mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.asset_token_program.to_account_info(),
+            transfer_accounts,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        let transfer_fee = transfer_checked(cpi_ctx, amount, ctx.accounts.asset_mint.decimals)?;
+        let received = amount
+            .checked_sub(transfer_fee)
+            .ok_or(DepositError::Overflow)?;
+        msg!(
+            "transferred {} of asset {} to treasury vault (received: {})",
+            amount,
+            ctx.accounts.asset_mint.key(),
+            received
+        );
+
+        // update the per-asset balance and re-value the treasury's SOL-valued holdings
+        let treasury_asset = &mut ctx.accounts.treasury_asset;
+        treasury_asset.total_deposited = treasury_asset
+            .total_deposited
+            .checked_add(received)
+            .ok_or(DepositError::Overflow)?;
+        treasury_asset.revalue(&mut ctx.accounts.treasury)?;
+        // the deposit's value is what its CN was issued against
+        ctx.accounts.treasury.add_backing(value_lamports)?;
+
+        Ok(received)
+    }
+
+    pub fn set_deposit_receipt(
+        ctx: &mut Context<DepositSpl>,
+        amount: u64,
+        value_lamports: u64,
+        received: u64,
+    ) -> Result<()> {
+        // the option's expiration is only set once it is issued, the receipt just
+        // has to be claimed within the claim window
+        Deposit::reserve_tier_capacity(&mut ctx.accounts.duration_tier, amount)?;
+        let claim_deadline = Clock::get()?
+            .unix_timestamp
+            .checked_add(ctx.accounts.config.claim_window)
+            .ok_or(DepositError::Overflow)?;
+
+        let nonce = ctx
+            .accounts
+            .config
+            .take_deposit_nonce()
+            .ok_or(DepositError::Overflow)?;
+        Deposit::add_receipt_amount(&mut ctx.accounts.config, amount)?;
+
+        *ctx.accounts.deposit_receipt = DepositReceipt {
+            initialized: true,
+            nft_issued: false,
+            amount,
+            value_lamports,
+            option_duration: ctx.accounts.duration_tier.duration,
+            claim_deadline,
+            conversion_multiplier_bps: ctx.accounts.duration_tier.conversion_multiplier_bps,
+            nonce,
+            bump: ctx.bumps.deposit_receipt,
+            version: DepositReceipt::VERSION,
+            asset_mint: Some(ctx.accounts.asset_mint.key()),
+            asset_amount: received,
+            // what cancel_deposit needs to take the deposit back off the caps
+            deposit_epoch: ctx.accounts.config.deposit_epoch,
+            allowlist_root: ctx.accounts.config.allowlist_root,
+        };
+
+        msg!(
+            "issuing deposit receipt - nonce: {}, amount: {}, claim deadline: {}",
+            nonce,
+            amount,
+            claim_deadline
+        );
+        Ok(())
+    }
+
+    pub fn mint_cn_to_depositor(ctx: &Context<DepositSpl>, tokens_to_mint: u64) -> Result<()> {
+        Deposit::mint_cn(
+            &ctx.accounts.config,
+            &ctx.accounts.cn_mint,
+            &ctx.accounts.depositor_cn_ata,
+            &ctx.accounts.token_program,
+            tokens_to_mint,
+        )
+    }
+
+    /// returns the amount of PT minted.
+    pub fn mint_pt_to_protocol(ctx: &Context<DepositSpl>, tokens_to_mint: u64) -> Result<u64> {
+        Deposit::mint_pt(
+            &ctx.accounts.config,
+            &ctx.accounts.duration_tier,
+            &ctx.accounts.pt_mint,
+            &ctx.accounts.protocol_pt_ata,
+            &ctx.accounts.token_program,
+            tokens_to_mint,
+        )
+    }
+
+    pub fn emit_deposited(
+        ctx: &Context<DepositSpl>,
+        amount: u64,
+        value_lamports: u64,
+        pt_minted: u64,
+    ) -> Result<()> {
+        let receipt = &ctx.accounts.deposit_receipt;
+        emit_cpi!(Deposited {
+            depositor: ctx.accounts.depositor.key(),
+            mint: Some(ctx.accounts.asset_mint.key()),
+            amount,
+            value_lamports,
+            cn_minted: receipt.amount,
+            pt_minted,
+            nav: ctx.accounts.treasury.nav,
+            nonce: receipt.nonce,
+            option_duration: receipt.option_duration,
+            expiration: None,
+            claim_deadline: Some(receipt.claim_deadline),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,415 @@
+use anchor_lang::{prelude::*, solana_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use mpl_token_metadata::{
+    accounts::{MasterEdition, Metadata},
+    ID as MPL_TOKEN_METADATA_ID,
+};
+
+use crate::events::{Deposited, OptionIssued};
+use crate::instructions::deposit::{Deposit, DepositError};
+use crate::instructions::option_nft::OptionNftAccounts;
+use crate::state::{
+    AllowlistProof, Config, DurationTier, FeeVault, OptionData, Treasury, UserPosition,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(amount: u64, option_duration: u32)]
+pub struct DepositWithOption<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    // depositor's CN ATA (initialized if needed)
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = cn_mint,
+        associated_token::authority = depositor,
+        token::token_program = token_program, // specify token program for ATA
+    )]
+    pub depositor_cn_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut, // needs mut to increment option_count
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    // collects the protocol fee
+    #[account(
+        mut,
+        seeds = [FeeVault::SEED_PREFIX],
+        bump = fee_vault.bump,
+    )]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+
+    // tier for the requested option duration (must have been added by the authority)
+    #[account(
+        mut,
+        seeds = [DurationTier::SEED_PREFIX, &option_duration.to_le_bytes()],
+        bump = duration_tier.bump,
+    )]
+    pub duration_tier: Box<Account<'info, DurationTier>>,
+
+    // depositor's running deposit total, for the per-wallet cap (created on the first deposit)
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [UserPosition::SEED_PREFIX, depositor.key().as_ref()],
+        bump,
+        space = 8 + UserPosition::INIT_SPACE,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    // mints (checked against config)
+    #[account(
+        mut,
+        address = config.cn_mint @ DepositError::AddressMismatch
+    )]
+    pub cn_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = config.pt_mint @ DepositError::AddressMismatch
+    )]
+    pub pt_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // protocol's PT ATA
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = pt_mint,
+        associated_token::authority = config, // config PDA owns the protocol's PT ATA
+        token::token_program = token_program, // specify token program for ATA
+    )]
+    pub protocol_pt_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // --- option NFT accounts ---
+    #[account(
+        init,
+        seeds = [
+            b"option_mint",
+            depositor.key().as_ref(),
+            &config.deposit_nonce.to_le_bytes(),
+        ],
+        bump,
+        payer = depositor,
+        mint::decimals = 0, // NFTs have 0 decimals
+        mint::authority = config, // PDA is mint authority
+        mint::freeze_authority = config, // PDA is freeze authority
+        token::token_program = token_program, // specify token program
+    )]
+    pub option_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed, // initialize ATA if it doesn't exist
+        payer = depositor,
+        associated_token::mint = option_mint,
+        associated_token::authority = depositor, // user owns the ATA
+        token::token_program = token_program, // specify token program
+    )]
+    pub depositor_option_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: checked via CPI to token metadata program
+    #[account(
+        mut,
+        address = Metadata::find_pda(&option_mint.key()).0 @ DepositError::AddressMismatch,
+    )]
+    pub option_metadata_account: UncheckedAccount<'info>,
+
+    /// CHECK: checked in constraints and CPI
+    #[account(
+        mut,
+        address = MasterEdition::find_pda(&option_mint.key()).0 @ DepositError::AddressMismatch,
+    )]
+    pub option_master_edition: UncheckedAccount<'info>,
+
+    // --- main collection accounts ---
+    /// CHECK: checked against config
+    #[account(
+        mut,
+        address = config.collection_mint @ DepositError::AddressMismatch,
+    )]
+    pub main_collection_mint: UncheckedAccount<'info>,
+    /// CHECK: checked in constraints and CPI
+    #[account(
+        mut, // verification might change collection metadata account (e.g., size)
+        address = Metadata::find_pda(&main_collection_mint.key()).0 @ DepositError::AddressMismatch,
+    )]
+    pub main_collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: checked in constraints and CPI
+    #[account(
+        mut,
+        address = MasterEdition::find_pda(&main_collection_mint.key()).0 @ DepositError::AddressMismatch,
+    )]
+    pub main_collection_master_edition: UncheckedAccount<'info>,
+
+    // --- option data PDA ---
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + OptionData::INIT_SPACE,
+        seeds = [OptionData::SEED_PREFIX, option_mint.key().as_ref()],
+        bump
+    )]
+    pub option_data: Box<Account<'info, OptionData>>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// CHECK: address checked
+    #[account(address = MPL_TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Anchor will verify this is the sysvar instruction account
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> DepositWithOption<'info> {
+    pub fn assert_protocol_state(ctx: &Context<DepositWithOption>) -> Result<()> {
+        // Ensure the protocol is not locked
+        require!(!ctx.accounts.config.locked, DepositError::ProtocolLocked);
+        require!(
+            !ctx.accounts.config.deposit_locked,
+            DepositError::DepositsLocked
+        );
+        require!(
+            ctx.accounts.duration_tier.enabled,
+            DepositError::DurationTierDisabled
+        );
+        Ok(())
+    }
+
+    /// pays the protocol fee on `amount` into the fee vault.
+    /// returns the amount left to deposit into the treasury.
+    pub fn charge_deposit_fee(ctx: &mut Context<DepositWithOption>, amount: u64) -> Result<u64> {
+        require!(amount > 0, DepositError::ZeroAmount);
+        let fee = Deposit::pay_sol_fee(
+            &ctx.accounts.config,
+            ctx.accounts.depositor.to_account_info(),
+            &mut ctx.accounts.fee_vault,
+            ctx.accounts.system_program.to_account_info(),
+            amount,
+        )?;
+        if fee > 0 {
+            emit_cpi!(Deposit::deposit_fee_charged(
+                &ctx.accounts.config,
+                ctx.accounts.depositor.key(),
+                None,
+                amount,
+                fee,
+            ));
+        }
+
+        amount.checked_sub(fee).ok_or(DepositError::Overflow.into())
+    }
+
+    pub fn apply_allowlist(
+        ctx: &mut Context<DepositWithOption>,
+        value: u64,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
+        Deposit::enforce_allowlist(
+            &ctx.accounts.config,
+            &mut ctx.accounts.user_position,
+            &ctx.accounts.depositor.key(),
+            allowlist_proof,
+            value,
+        )
+    }
+
+    pub fn apply_deposit_caps(ctx: &mut Context<DepositWithOption>, value: u64) -> Result<()> {
+        Deposit::enforce_deposit_caps(
+            &mut ctx.accounts.config,
+            &ctx.accounts.treasury,
+            &mut ctx.accounts.user_position,
+            ctx.accounts.depositor.key(),
+            ctx.bumps.user_position,
+            value,
+        )
+    }
+
+    pub fn calculate_tokens_to_mint(
+        ctx: &mut Context<DepositWithOption>,
+        amount: u64,
+    ) -> Result<u64> {
+        Deposit::tokens_for_value(
+            &mut ctx.accounts.treasury,
+            ctx.accounts.cn_mint.supply,
+            amount,
+        )
+    }
+
+    pub fn deposit_sol(ctx: &mut Context<DepositWithOption>, amount: u64) -> Result<()> {
+        Deposit::transfer_sol_to_treasury(
+            ctx.accounts.depositor.to_account_info(),
+            &mut ctx.accounts.treasury,
+            ctx.accounts.system_program.to_account_info(),
+            amount,
+        )
+    }
+
+    pub fn mint_cn_to_depositor(
+        ctx: &Context<DepositWithOption>,
+        tokens_to_mint: u64,
+    ) -> Result<()> {
+        Deposit::mint_cn(
+            &ctx.accounts.config,
+            &ctx.accounts.cn_mint,
+            &ctx.accounts.depositor_cn_ata,
+            &ctx.accounts.token_program,
+            tokens_to_mint,
+        )
+    }
+
+    /// returns the amount of PT minted.
+    pub fn mint_pt_to_protocol(
+        ctx: &Context<DepositWithOption>,
+        tokens_to_mint: u64,
+    ) -> Result<u64> {
+        Deposit::mint_pt(
+            &ctx.accounts.config,
+            &ctx.accounts.duration_tier,
+            &ctx.accounts.pt_mint,
+            &ctx.accounts.protocol_pt_ata,
+            &ctx.accounts.token_program,
+            tokens_to_mint,
+        )
+    }
+
+    pub fn issue_option_nft(ctx: &Context<DepositWithOption>) -> Result<()> {
+        // mint the NFT, create its metadata and verify it with the collection
+        let depositor = ctx.accounts.depositor.to_account_info();
+        let config = ctx.accounts.config.to_account_info();
+        let option_mint = ctx.accounts.option_mint.to_account_info();
+        let depositor_option_ata = ctx.accounts.depositor_option_ata.to_account_info();
+        let option_metadata = ctx.accounts.option_metadata_account.to_account_info();
+        let option_master_edition = ctx.accounts.option_master_edition.to_account_info();
+        let collection_mint = ctx.accounts.main_collection_mint.to_account_info();
+        let collection_metadata = ctx.accounts.main_collection_metadata.to_account_info();
+        let collection_master_edition = ctx
+            .accounts
+            .main_collection_master_edition
+            .to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let sysvar_instructions = ctx.accounts.sysvar_instructions.to_account_info();
+
+        OptionNftAccounts {
+            payer: &depositor,
+            owner: &depositor,
+            config: &config,
+            option_mint: &option_mint,
+            owner_option_ata: &depositor_option_ata,
+            option_metadata: &option_metadata,
+            option_master_edition: &option_master_edition,
+            collection_mint: &collection_mint,
+            collection_metadata: &collection_metadata,
+            collection_master_edition: &collection_master_edition,
+            token_program: &token_program,
+            token_metadata_program: &token_metadata_program,
+            system_program: &system_program,
+            sysvar_instructions: &sysvar_instructions,
+        }
+        .issue(ctx.accounts.config.bump, ctx.program_id)
+    }
+
+    /// returns the deposit nonce the option was issued under.
+    pub fn set_option_data(
+        ctx: &mut Context<DepositWithOption>,
+        amount: u64,
+    ) -> Result<u64> {
+        let expiration = Deposit::calculate_expiration(&mut ctx.accounts.duration_tier, amount)?;
+        let nonce = ctx
+            .accounts
+            .config
+            .take_deposit_nonce()
+            .ok_or(DepositError::Overflow)?;
+
+        **ctx.accounts.option_data = OptionData {
+            mint: ctx.accounts.option_mint.key(),
+            amount,
+            expiration,
+            expired: false,
+            conversion_multiplier_bps: ctx.accounts.duration_tier.conversion_multiplier_bps,
+            yield_checkpoint: ctx.accounts.config.yield_per_unit, // only earns yield distributed from now on
+            accrued_yield: 0,
+            unlock_at: None,
+            rent_payer: ctx.accounts.depositor.key(),
+            bump: ctx.bumps.option_data,
+            version: OptionData::VERSION,
+        };
+
+        // track the new option in the config totals
+        let config = &mut ctx.accounts.config;
+        config.option_count = config
+            .option_count
+            .checked_add(1)
+            .ok_or(DepositError::Overflow)?;
+        config.total_option_amount = config
+            .total_option_amount
+            .checked_add(amount)
+            .ok_or(DepositError::Overflow)?;
+
+        msg!(
+            "option nft issued and added to collection. nonce: {}, mint: {}, amount: {}, expiration: {}",
+            nonce,
+            ctx.accounts.option_mint.key(),
+            amount,
+            expiration
+        );
+        Ok(nonce)
+    }
+
+    pub fn emit_deposited(
+        ctx: &Context<DepositWithOption>,
+        amount: u64,
+        pt_minted: u64,
+        nonce: u64,
+    ) -> Result<()> {
+        let option_data = &ctx.accounts.option_data;
+        let timestamp = Clock::get()?.unix_timestamp;
+        emit_cpi!(Deposited {
+            depositor: ctx.accounts.depositor.key(),
+            mint: None,
+            amount,
+            value_lamports: amount,
+            cn_minted: option_data.amount,
+            pt_minted,
+            nav: ctx.accounts.treasury.nav,
+            nonce,
+            option_duration: ctx.accounts.duration_tier.duration,
+            expiration: Some(option_data.expiration),
+            claim_deadline: None,
+            timestamp,
+        });
+        emit_cpi!(OptionIssued {
+            owner: ctx.accounts.depositor.key(),
+            option_mint: ctx.accounts.option_mint.key(),
+            option_data: option_data.key(),
+            nonce,
+            amount: option_data.amount,
+            expiration: option_data.expiration,
+            conversion_multiplier_bps: option_data.conversion_multiplier_bps,
+            timestamp,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+
+use crate::instructions::admin::require_authority;
+use crate::state::{
+    Config, DurationTier, BASE_CONVERSION_MULTIPLIER_BPS, MAX_CONVERSION_MULTIPLIER_BPS,
+};
+
+#[derive(Accounts)]
+#[instruction(duration: u32)]
+pub struct AddDurationTier<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [DurationTier::SEED_PREFIX, &duration.to_le_bytes()],
+        bump,
+        space = 8 + DurationTier::INIT_SPACE,
+    )]
+    pub duration_tier: Account<'info, DurationTier>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddDurationTier<'info> {
+    pub fn handler(
+        ctx: Context<AddDurationTier>,
+        duration: u32,
+        cap: Option<u64>,
+        conversion_multiplier_bps: u16,
+    ) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+        require!(duration > 0, DurationTierError::InvalidDuration);
+        require!(
+            (BASE_CONVERSION_MULTIPLIER_BPS..=MAX_CONVERSION_MULTIPLIER_BPS)
+                .contains(&conversion_multiplier_bps),
+            DurationTierError::InvalidConversionMultiplier
+        );
+
+        *ctx.accounts.duration_tier = DurationTier {
+            duration,
+            enabled: true,
+            cap: cap.filter(|val| *val > 0), // 0 means uncapped
+            total_issued: 0,
+            conversion_multiplier_bps,
+            bump: ctx.bumps.duration_tier,
+        };
+
+        msg!(
+            "added duration tier: {} seconds (cap: {:?}, multiplier: {} bps)",
+            duration,
+            ctx.accounts.duration_tier.cap,
+            conversion_multiplier_bps
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateDurationTier<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [DurationTier::SEED_PREFIX, &duration_tier.duration.to_le_bytes()],
+        bump = duration_tier.bump,
+    )]
+    pub duration_tier: Account<'info, DurationTier>,
+}
+
+impl<'info> UpdateDurationTier<'info> {
+    pub fn handler(
+        ctx: Context<UpdateDurationTier>,
+        enabled: Option<bool>,
+        cap: Option<u64>,
+        conversion_multiplier_bps: Option<u16>,
+    ) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        let duration_tier = &mut ctx.accounts.duration_tier;
+
+        if let Some(val) = enabled {
+            duration_tier.enabled = val;
+            msg!(
+                "duration tier {} enabled updated to: {}",
+                duration_tier.duration,
+                val
+            );
+        }
+        if let Some(val) = cap {
+            // 0 removes the cap
+            duration_tier.cap = if val == 0 { None } else { Some(val) };
+            msg!(
+                "duration tier {} cap updated to: {:?}",
+                duration_tier.duration,
+                duration_tier.cap
+            );
+        }
+        if let Some(val) = conversion_multiplier_bps {
+            // only applies to options issued from now on, existing ones keep theirs
+            require!(
+                (BASE_CONVERSION_MULTIPLIER_BPS..=MAX_CONVERSION_MULTIPLIER_BPS).contains(&val),
+                DurationTierError::InvalidConversionMultiplier
+            );
+            duration_tier.conversion_multiplier_bps = val;
+            msg!(
+                "duration tier {} conversion multiplier updated to: {} bps",
+                duration_tier.duration,
+                val
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RetireDurationTier<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    // closing the tier returns its rent to the authority.
+    // options already issued keep the expiration stored in their OptionData/receipt.
+    #[account(
+        mut,
+        close = authority,
+        seeds = [DurationTier::SEED_PREFIX, &duration_tier.duration.to_le_bytes()],
+        bump = duration_tier.bump,
+    )]
+    pub duration_tier: Account<'info, DurationTier>,
+}
+
+impl<'info> RetireDurationTier<'info> {
+    pub fn handler(ctx: Context<RetireDurationTier>) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        msg!(
+            "retired duration tier: {} seconds",
+            ctx.accounts.duration_tier.duration
+        );
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum DurationTierError {
+    #[msg("option duration must be greater than zero.")]
+    InvalidDuration,
+    #[msg("conversion multiplier must be between 1x (10000 bps) and 5x (50000 bps).")]
+    InvalidConversionMultiplier,
+}
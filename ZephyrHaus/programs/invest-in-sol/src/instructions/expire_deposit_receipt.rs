@@ -0,0 +1,149 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface};
+
+use crate::events::DepositReceiptExpired;
+use crate::instructions::cancel_deposit::CancelDeposit;
+use crate::state::{pt_for_cn, Config, DepositReceipt, DurationTier, Rounding, UserPosition};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct ExpireDepositReceipt<'info> {
+    // anyone can crank an unclaimed receipt once its claim window has passed
+    pub signer: Signer<'info>,
+
+    /// CHECK: only receives the receipt's rent, tied to the receipt by its seeds.
+    #[account(mut)]
+    pub depositor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            DepositReceipt::SEED_PREFIX,
+            depositor.key().as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        bump = deposit_receipt.bump,
+        close = depositor,
+        constraint = deposit_receipt.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub deposit_receipt: Account<'info, DepositReceipt>,
+
+    #[account(
+        mut, // the deposit comes off the epoch deposit limit
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        address = config.pt_mint @ ExpireDepositReceiptError::AddressMismatch
+    )]
+    pub pt_mint: InterfaceAccount<'info, Mint>,
+
+    // protocol's PT ATA (source of the PT backing the unclaimed option)
+    #[account(
+        mut,
+        associated_token::mint = pt_mint,
+        associated_token::authority = config, // config PDA owns the protocol's PT ATA
+        associated_token::token_program = token_program,
+    )]
+    pub protocol_pt_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // depositor's running deposit totals the deposit was counted against
+    #[account(
+        mut,
+        seeds = [UserPosition::SEED_PREFIX, depositor.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    // tier the deposit reserved its option amount in
+    #[account(
+        mut,
+        seeds = [DurationTier::SEED_PREFIX, &deposit_receipt.option_duration.to_le_bytes()],
+        bump = duration_tier.bump,
+    )]
+    pub duration_tier: Account<'info, DurationTier>,
+
+    // programs
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ExpireDepositReceipt<'info> {
+    pub fn handler(ctx: Context<ExpireDepositReceipt>, nonce: u64) -> Result<()> {
+        let receipt = &ctx.accounts.deposit_receipt;
+        require!(
+            !receipt.nft_issued,
+            ExpireDepositReceiptError::OptionAlreadyIssued
+        );
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        require!(
+            !receipt.is_claimable(current_timestamp),
+            ExpireDepositReceiptError::ClaimWindowOpen
+        );
+
+        // the option is forfeited, so the PT minted to back it is burned the same way
+        // sync_expired_options does for expired options. the depositor keeps the CN.
+        let cn_amount = receipt.amount;
+        let pt_amount = pt_for_cn(cn_amount, receipt.conversion_multiplier_bps, Rounding::Up)?;
+        require!(
+            ctx.accounts.protocol_pt_ata.amount >= pt_amount,
+            ExpireDepositReceiptError::InsufficientPtBalance
+        );
+
+        let bump_seed = [ctx.accounts.config.bump];
+        let config_seeds_with_bump = Config::get_seeds_with_bump(&bump_seed);
+        let signer_seeds = &[&config_seeds_with_bump[..]];
+        let cpi_accounts_pt = Burn {
+            mint: ctx.accounts.pt_mint.to_account_info(),
+            from: ctx.accounts.protocol_pt_ata.to_account_info(),
+            authority: ctx.accounts.config.to_account_info(),
+        };
+        let cpi_ctx_pt = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts_pt,
+            signer_seeds,
+        );
+        burn(cpi_ctx_pt, pt_amount)?;
+
+        // the option is never issued, so the capacity reserved for it is given back.
+        // the deposited SOL stays in the treasury backing the depositor's CN
+        let accounts = &mut *ctx.accounts;
+        CancelDeposit::release_caps(
+            &accounts.deposit_receipt,
+            &mut accounts.config,
+            &mut accounts.user_position,
+            &mut accounts.duration_tier,
+        );
+
+        emit_cpi!(DepositReceiptExpired {
+            depositor: ctx.accounts.depositor.key(),
+            nonce,
+            cn_amount,
+            pt_burned: pt_amount,
+            expired_by: ctx.accounts.signer.key(),
+            timestamp: current_timestamp,
+        });
+        msg!(
+            "expired deposit receipt {} - burned {} PT",
+            nonce,
+            pt_amount
+        );
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum ExpireDepositReceiptError {
+    #[msg("account address mismatch.")]
+    AddressMismatch,
+    #[msg("the option for this deposit has already been issued.")]
+    OptionAlreadyIssued,
+    #[msg("the receipt can still be claimed.")]
+    ClaimWindowOpen,
+    #[msg("insufficient PT tokens held by the protocol.")]
+    InsufficientPtBalance,
+}
@@ -1,15 +1,19 @@
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::Token;
-use anchor_spl::token_interface::{mint_to, Mint, MintTo, TokenAccount};
+use anchor_spl::token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface};
 use mpl_token_metadata::{
     instructions::{CreateMasterEditionV3CpiBuilder, CreateMetadataAccountV3CpiBuilder},
     types::{Creator, DataV2},
     ID as MetadataID,
 };
 
-use crate::state::{Config, Treasury};
+use crate::events::Initialized;
+use crate::state::{
+    Config, FeeVault, Roles, Treasury, DEFAULT_CLAIM_WINDOW, DEFAULT_WITHDRAWAL_DELAY,
+    NAV_PRECISION,
+};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(mut)]
@@ -66,6 +70,24 @@ pub struct Initialize<'info> {
     )]
     pub treasury: Account<'info, Treasury>,
 
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [FeeVault::SEED_PREFIX], // use constant seed
+        bump,
+        space = 8 + FeeVault::INIT_SPACE
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [Roles::SEED_PREFIX],
+        bump,
+        space = 8 + Roles::INIT_SPACE
+    )]
+    pub roles: Box<Account<'info, Roles>>,
+
     /// CHECK: This is the token metadata program
     #[account(address = MetadataID)]
     pub token_metadata_program: UncheckedAccount<'info>,
@@ -100,7 +122,7 @@ pub struct Initialize<'info> {
     // --- programs ---
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 impl<'info> Initialize<'info> {
@@ -109,22 +131,49 @@ impl<'info> Initialize<'info> {
         // initialize config PDA
         let config = &mut ctx.accounts.config;
         config.authority = Some(ctx.accounts.initializer.key());
+        config.pending_authority = None; // no handover in progress
         config.cn_mint = ctx.accounts.cn_mint.key();
         config.pt_mint = ctx.accounts.pt_mint.key();
         config.collection_mint = ctx.accounts.collection_mint.key();
         config.fee = None; // default to no fee
+        config.conversion_fee = None; // default to no conversion fee
+        config.fee_recipient = ctx.accounts.initializer.key(); // fees go to the initializer until updated
         config.option_count = 0; // initialize count
         config.total_option_amount = 0; // initialize total option amount
+        config.total_receipt_amount = 0; // no deposit receipts issued yet
         config.deposit_nonce = 0; // initialize deposit nonce
+        config.yield_per_unit = 0; // no option yield distributed yet
+        config.max_total_deposited_sol = None; // deposit caps are set by the authority
+        config.max_wallet_deposit = None;
+        config.max_epoch_deposit = None;
+        config.allowlist_root = None; // deposits are open to anyone
+        config.deposit_epoch = 0;
+        config.epoch_deposited = 0;
+        config.claim_window = DEFAULT_CLAIM_WINDOW; // time to claim a deposit's option
         config.locked = false; // default to unlocked
         config.deposit_locked = true; // default deposit to locked
         config.convert_locked = true; // default convert to locked
+        config.redeem_locked = true; // default redeem to locked
         config.bump = ctx.bumps.config; // use correct bump field name
+        config.version = Config::VERSION;
 
         // initialize treasury PDA
         let treasury = &mut ctx.accounts.treasury;
         treasury.authority = Some(ctx.accounts.initializer.key());
         treasury.treasury_bump = ctx.bumps.treasury; // use correct bump field name 'treasury_bump'
+        treasury.nav = NAV_PRECISION as u64; // 1 lamport per token until anything is issued
+        treasury.reserved_yield = 0; // no option yield waiting to be claimed
+        treasury.converted_cn = 0; // nothing converted into PT yet
+        treasury.backing_sol = 0; // nothing paid in yet
+        treasury.withdrawal_delay = DEFAULT_WITHDRAWAL_DELAY; // safety window before withdrawals
+        treasury.version = Treasury::VERSION;
+
+        // initialize fee vault PDA
+        ctx.accounts.fee_vault.bump = ctx.bumps.fee_vault;
+
+        // initialize roles PDA, no one holds a role until the authority grants it
+        ctx.accounts.roles.members = Vec::new();
+        ctx.accounts.roles.bump = ctx.bumps.roles;
 
         // treasury_vault is initialized via account constraints.
         // anchor should automatically assign ownership to the program
@@ -134,6 +183,7 @@ impl<'info> Initialize<'info> {
         msg!("protocol initialized:");
         msg!("  config PDA: {}", config.key());
         msg!("  treasury PDA: {}", treasury.key());
+        msg!("  fee vault PDA: {}", ctx.accounts.fee_vault.key());
         msg!("  CN Mint: {}", config.cn_mint);
         msg!("  PT Mint: {}", config.pt_mint);
         msg!("  Collection Mint: {}", config.collection_mint);
@@ -142,6 +192,18 @@ impl<'info> Initialize<'info> {
         msg!("  authority: {}", config.authority.unwrap());
 
         Initialize::create_collection(ctx)?;
+
+        let config = &ctx.accounts.config;
+        emit_cpi!(Initialized {
+            authority: ctx.accounts.initializer.key(),
+            config: config.key(),
+            treasury: ctx.accounts.treasury.key(),
+            cn_mint: config.cn_mint,
+            pt_mint: config.pt_mint,
+            collection_mint: config.collection_mint,
+            nav: ctx.accounts.treasury.nav,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
@@ -1,18 +1,19 @@
 use anchor_lang::{prelude::*, solana_program};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::Token,
-    token_interface::{mint_to, Mint, MintTo, TokenAccount},
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 use mpl_token_metadata::{
     accounts::{MasterEdition, Metadata},
-    instructions::{CreateV1CpiBuilder, VerifyCollectionV1CpiBuilder},
-    types::{Collection, Creator, PrintSupply},
     ID as MPL_TOKEN_METADATA_ID,
 };
 
+use crate::events::OptionIssued;
+use crate::instructions::option_nft::OptionNftAccounts;
 use crate::state::{Config, DepositReceipt, OptionData};
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(nonce: u64)]
 pub struct InitializeOption<'info> {
     #[account(mut)]
     pub depositor: Signer<'info>,
@@ -21,18 +22,28 @@ pub struct InitializeOption<'info> {
         mut, // needs mut to increment option_count
         seeds = [Config::SEED_PREFIX],
         bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
     )]
     pub config: Account<'info, Config>,
 
+    // the receipt is consumed by the claim, so it is closed and its rent returned.
+    // its nonce is never handed out again, so the PDA can't be re-created.
     #[account(
-        seeds = [DepositReceipt::SEED_PREFIX, depositor.key().as_ref()],
+        mut,
+        seeds = [
+            DepositReceipt::SEED_PREFIX,
+            depositor.key().as_ref(),
+            &nonce.to_le_bytes(),
+        ],
         bump = deposit_receipt.bump,
+        close = depositor,
+        constraint = deposit_receipt.is_current() @ crate::ErrorCode::AccountVersionOutdated,
     )]
     pub deposit_receipt: Account<'info, DepositReceipt>,
 
     #[account(
         init,
-        seeds = [b"option_mint", depositor.key().as_ref()],
+        seeds = [b"option_mint", depositor.key().as_ref(), &nonce.to_le_bytes()],
         bump,
         payer = depositor,
         mint::decimals = 0, // NFTs have 0 decimals
@@ -98,7 +109,7 @@ pub struct InitializeOption<'info> {
 
     // programs
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     /// CHECK: address checked
     #[account(address = MPL_TOKEN_METADATA_ID)]
@@ -112,26 +123,25 @@ pub struct InitializeOption<'info> {
 impl<'info> InitializeOption<'info> {
     pub fn process_initialize_option(mut ctx: Context<InitializeOption>) -> Result<()> {
         // call the existing functions to handle the rest of the initialization
-        Self::verify_receipt(&ctx)?;
-        Self::mint_option_to_depositor(&ctx)?;
-        Self::create_option_metadata_account(&ctx)?;
-        Self::verify_mint_with_collection(&ctx)?;
-        Self::set_option_data(&mut ctx)?;
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        Self::verify_receipt(&ctx, current_timestamp)?;
+        Self::issue_option_nft(&ctx)?;
+        Self::set_option_data(&mut ctx, current_timestamp)?;
         Self::increment_config_option_count(&mut ctx)?;
         Self::increment_total_option_amount(&mut ctx)?;
-        Self::update_deposit_receipt(&mut ctx)?;
+        Self::emit_option_issued(&ctx)?;
 
         msg!(
             "option nft initialized and added to collection. mint: {}, amount: {}, expiration: {}",
             ctx.accounts.option_mint.key(),
             ctx.accounts.deposit_receipt.amount,
-            ctx.accounts.deposit_receipt.expiration
+            ctx.accounts.option_data.expiration
         );
 
         Ok(())
     }
 
-    pub fn verify_receipt(ctx: &Context<InitializeOption>) -> Result<()> {
+    pub fn verify_receipt(ctx: &Context<InitializeOption>, current_timestamp: i64) -> Result<()> {
         // 1. check if the deposit receipt is valid
         let deposit_receipt = &ctx.accounts.deposit_receipt;
 
@@ -140,129 +150,74 @@ impl<'info> InitializeOption<'info> {
             return Err(ErrorCode::DepositReceiptIssued.into());
         }
 
-        // check if the deposit receipt is expired
-        let current_time = Clock::get()?.unix_timestamp;
-        if current_time > deposit_receipt.expiration {
+        // check the receipt is still within its claim window
+        if !deposit_receipt.is_claimable(current_timestamp) {
             return Err(ErrorCode::DepositReceiptExpired.into());
         }
 
         Ok(())
     }
 
-    pub fn mint_option_to_depositor(ctx: &Context<InitializeOption>) -> Result<()> {
-        let config_bump = ctx.accounts.config.bump;
-        let bump_seed = [config_bump];
-        let config_seeds = Config::get_seeds_with_bump(&bump_seed);
-
-        // 1. mint the option NFT to the depositor's ATA
-        mint_to(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                MintTo {
-                    mint: ctx.accounts.option_mint.to_account_info(),
-                    to: ctx.accounts.depositor_option_ata.to_account_info(),
-                    authority: ctx.accounts.config.to_account_info(),
-                },
-                &[&config_seeds[..]],
-            ),
-            1, // mint 1 NFT
-        )
-    }
-
-    pub fn create_option_metadata_account(ctx: &Context<InitializeOption>) -> Result<()> {
-        // don't think these are needed here since we're storing in option data account
-        // and reading from off chain service for metadata via uri field
-        // let amount = ctx.accounts.deposit_receipt.amount;
-        // let expiration = ctx.accounts.deposit_receipt.expiration;
-
-        // derive the OptionData PDA address for the URI
-        let (option_data_pda_key, _) = Pubkey::find_program_address(
-            &[
-                OptionData::SEED_PREFIX,
-                ctx.accounts.option_mint.key().as_ref(),
-            ],
-            ctx.program_id,
-        );
-
-        // format the metadata URI with the OptionData PDA address
-        // the off chain service will parse account data into properly formatted metadata for
-        // marketplaces / other uses
-        let uri = format!(
-            "https://metadata.zephyr.haus/{}",
-            option_data_pda_key.to_string()
-        );
-
-        let config_key = ctx.accounts.config.key();
-        let config_bump = ctx.accounts.config.bump;
-        let bump_seed = [config_bump];
-        let config_seeds = Config::get_seeds_with_bump(&bump_seed);
-
-        // 2. create the metadata account
-        CreateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
-            .metadata(&ctx.accounts.option_metadata_account.to_account_info())
-            .mint(&ctx.accounts.option_mint.to_account_info(), false)
-            .authority(&ctx.accounts.config.to_account_info())
-            .payer(&ctx.accounts.depositor.to_account_info())
-            .update_authority(&ctx.accounts.config.to_account_info(), true)
-            .system_program(&ctx.accounts.system_program.to_account_info())
-            .master_edition(Some(&ctx.accounts.option_master_edition.to_account_info()))
-            .spl_token_program(Some(&ctx.accounts.token_program.to_account_info()))
-            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
-            .name("zOption".into())
-            .symbol("zOption".into())
-            .uri(uri.clone())
-            .seller_fee_basis_points(0)
-            .creators(vec![Creator {
-                address: config_key,
-                verified: true,
-                share: 100,
-            }])
-            .collection(Collection {
-                key: ctx.accounts.main_collection_mint.key(),
-                verified: false,
-            })
-            .print_supply(PrintSupply::Zero)
-            .invoke_signed(&[&config_seeds[..]])?;
-
-        Ok(())
-    }
-
-    pub fn verify_mint_with_collection(ctx: &Context<InitializeOption>) -> Result<()> {
-        let config_bump = ctx.accounts.config.bump;
-        let bump_seed = [config_bump];
-        let config_seeds = Config::get_seeds_with_bump(&bump_seed);
-
-        // 3. set and verify collection item
-        msg!("setting and verifying collection item");
-        VerifyCollectionV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
-            .metadata(&ctx.accounts.option_metadata_account.to_account_info())
-            .authority(&ctx.accounts.config.to_account_info())
-            .collection_mint(&ctx.accounts.main_collection_mint.to_account_info())
-            .collection_master_edition(Some(
-                &ctx.accounts
-                    .main_collection_master_edition
-                    .to_account_info(),
-            ))
-            .collection_metadata(Some(
-                &ctx.accounts.main_collection_metadata.to_account_info(),
-            ))
-            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
-            .system_program(&ctx.accounts.system_program.to_account_info())
-            .invoke_signed(&[&config_seeds[..]])?;
-
-        Ok(())
+    pub fn issue_option_nft(ctx: &Context<InitializeOption>) -> Result<()> {
+        // mint the NFT, create its metadata and verify it with the collection
+        let depositor = ctx.accounts.depositor.to_account_info();
+        let config = ctx.accounts.config.to_account_info();
+        let option_mint = ctx.accounts.option_mint.to_account_info();
+        let depositor_option_ata = ctx.accounts.depositor_option_ata.to_account_info();
+        let option_metadata = ctx.accounts.option_metadata_account.to_account_info();
+        let option_master_edition = ctx.accounts.option_master_edition.to_account_info();
+        let collection_mint = ctx.accounts.main_collection_mint.to_account_info();
+        let collection_metadata = ctx.accounts.main_collection_metadata.to_account_info();
+        let collection_master_edition = ctx
+            .accounts
+            .main_collection_master_edition
+            .to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let sysvar_instructions = ctx.accounts.sysvar_instructions.to_account_info();
+
+        OptionNftAccounts {
+            payer: &depositor,
+            owner: &depositor,
+            config: &config,
+            option_mint: &option_mint,
+            owner_option_ata: &depositor_option_ata,
+            option_metadata: &option_metadata,
+            option_master_edition: &option_master_edition,
+            collection_mint: &collection_mint,
+            collection_metadata: &collection_metadata,
+            collection_master_edition: &collection_master_edition,
+            token_program: &token_program,
+            token_metadata_program: &token_metadata_program,
+            system_program: &system_program,
+            sysvar_instructions: &sysvar_instructions,
+        }
+        .issue(ctx.accounts.config.bump, ctx.program_id)
     }
 
-    pub fn set_option_data(ctx: &mut Context<InitializeOption>) -> Result<()> {
+    pub fn set_option_data(ctx: &mut Context<InitializeOption>, current_timestamp: i64) -> Result<()> {
         let amount = ctx.accounts.deposit_receipt.amount;
-        let expiration = ctx.accounts.deposit_receipt.expiration;
+        // the option's life starts when it is issued, not when the deposit was made
+        let expiration = ctx
+            .accounts
+            .deposit_receipt
+            .expiration_from(current_timestamp)
+            .ok_or(ErrorCode::Overflow)?;
 
         // 4. initialize the option data PDA
         *ctx.accounts.option_data = OptionData {
             mint: ctx.accounts.option_mint.key(),
             amount,
             expiration,
+            expired: false,
+            conversion_multiplier_bps: ctx.accounts.deposit_receipt.conversion_multiplier_bps,
+            yield_checkpoint: ctx.accounts.config.yield_per_unit, // only earns yield distributed from now on
+            accrued_yield: 0,
+            unlock_at: None,
+            rent_payer: ctx.accounts.depositor.key(),
             bump: ctx.bumps.option_data,
+            version: OptionData::VERSION,
         };
 
         Ok(())
@@ -288,6 +243,10 @@ impl<'info> InitializeOption<'info> {
             .total_option_amount
             .checked_add(amount)
             .ok_or(ErrorCode::Overflow)?;
+        // the receipt's CN is set aside for the option now. receipts from before the
+        // total was kept aren't in it, hence the saturating sub
+        let config = &mut ctx.accounts.config;
+        config.total_receipt_amount = config.total_receipt_amount.saturating_sub(amount);
 
         msg!(
             "incremented total_option_amount to {}",
@@ -296,11 +255,18 @@ impl<'info> InitializeOption<'info> {
         Ok(())
     }
 
-    pub fn update_deposit_receipt(ctx: &mut Context<InitializeOption>) -> Result<()> {
-        // update the deposit receipt to indicate that the NFT has been issued
-        ctx.accounts.deposit_receipt.nft_issued = true;
-        // mark as uninitialized so the receipt account can be reused
-        ctx.accounts.deposit_receipt.initialized = false;
+    pub fn emit_option_issued(ctx: &Context<InitializeOption>) -> Result<()> {
+        let option_data = &ctx.accounts.option_data;
+        emit_cpi!(OptionIssued {
+            owner: ctx.accounts.depositor.key(),
+            option_mint: ctx.accounts.option_mint.key(),
+            option_data: option_data.key(),
+            nonce: ctx.accounts.deposit_receipt.nonce,
+            amount: option_data.amount,
+            expiration: option_data.expiration,
+            conversion_multiplier_bps: option_data.conversion_multiplier_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 }
@@ -313,6 +279,6 @@ pub enum ErrorCode {
     AddressMismatch,
     #[msg("already issued deposit receipt")]
     DepositReceiptIssued,
-    #[msg("deposit receipt expired")]
+    #[msg("deposit receipt claim window has passed")]
     DepositReceiptExpired,
 }
@@ -16,6 +16,7 @@ pub struct LendTreasurySol<'info> {
         mut,
         seeds = [b"treasury"],
         bump = treasury.bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
     )]
     pub treasury: Account<'info, Treasury>,
 
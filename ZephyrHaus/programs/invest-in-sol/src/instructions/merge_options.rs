@@ -0,0 +1,282 @@
+use anchor_lang::{prelude::*, solana_program, solana_program::program_option::COption};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use mpl_token_metadata::{
+    accounts::{MasterEdition, Metadata},
+    instructions::BurnV1CpiBuilder,
+    ID as MPL_TOKEN_METADATA_ID,
+};
+
+use crate::events::OptionsMerged;
+use crate::instructions::option_nft::OptionMetadataAccounts;
+use crate::state::{Config, OptionData};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MergeOptions<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        mut, // needs mut to decrement option_count
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    // --- option kept, receives the merged amount ---
+    #[account(
+        associated_token::mint = option_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+        constraint = holder_option_ata.amount == 1 @ MergeOptionsError::NotOptionHolder,
+        constraint = !holder_option_ata.is_frozen() @ MergeOptionsError::OptionFrozen,
+        // without the config as delegate the NFT could no longer be frozen
+        constraint = holder_option_ata.delegate == COption::Some(config.key())
+            @ crate::ErrorCode::OptionNotDelegated,
+    )]
+    pub holder_option_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mint::token_program = token_program)]
+    pub option_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [OptionData::SEED_PREFIX, option_mint.key().as_ref()],
+        bump = option_data.bump,
+        constraint = option_data.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub option_data: Box<Account<'info, OptionData>>,
+
+    /// CHECK: checked by Metaplex CPI. PDA derived from option_mint.
+    #[account(
+        mut,
+        address = Metadata::find_pda(&option_mint.key()).0 @ MergeOptionsError::AddressMismatch,
+    )]
+    pub option_metadata: UncheckedAccount<'info>,
+    /// CHECK: checked by Metaplex CPI. PDA derived from option_mint.
+    #[account(
+        address = MasterEdition::find_pda(&option_mint.key()).0 @ MergeOptionsError::AddressMismatch,
+    )]
+    pub option_master_edition: UncheckedAccount<'info>,
+
+    // --- option burned into the kept one ---
+    #[account(
+        mut,
+        associated_token::mint = merged_option_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+        constraint = holder_merged_option_ata.amount == 1 @ MergeOptionsError::NotOptionHolder,
+        constraint = !holder_merged_option_ata.is_frozen() @ MergeOptionsError::OptionFrozen,
+        constraint = holder_merged_option_ata.delegate == COption::Some(config.key())
+            @ crate::ErrorCode::OptionNotDelegated,
+    )]
+    pub holder_merged_option_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut, // needs to be mutable for Metaplex BurnV1 CPI
+        mint::token_program = token_program,
+        constraint = merged_option_mint.key() != option_mint.key() @ MergeOptionsError::SameOption,
+    )]
+    pub merged_option_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // the NFT is burned, so its data is closed with it
+    #[account(
+        mut,
+        seeds = [OptionData::SEED_PREFIX, merged_option_mint.key().as_ref()],
+        bump = merged_option_data.bump,
+        close = merged_option_rent_payer,
+        constraint = merged_option_data.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub merged_option_data: Box<Account<'info, OptionData>>,
+
+    /// CHECK: receives merged_option_data's rent, must be the wallet that paid it.
+    #[account(
+        mut,
+        address = merged_option_data.rent_payer @ MergeOptionsError::RentPayerMismatch,
+    )]
+    pub merged_option_rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: checked by Metaplex CPI. PDA derived from merged_option_mint.
+    #[account(
+        mut,
+        address = Metadata::find_pda(&merged_option_mint.key()).0 @ MergeOptionsError::AddressMismatch,
+    )]
+    pub merged_option_metadata: UncheckedAccount<'info>,
+    /// CHECK: checked by Metaplex CPI. PDA derived from merged_option_mint.
+    #[account(
+        mut,
+        address = MasterEdition::find_pda(&merged_option_mint.key()).0 @ MergeOptionsError::AddressMismatch,
+    )]
+    pub merged_option_master_edition: UncheckedAccount<'info>,
+    /// CHECK: checked by Metaplex CPI. PDA derived from collection_mint.
+    #[account(
+        mut,
+        address = Metadata::find_pda(&config.collection_mint).0 @ MergeOptionsError::AddressMismatch,
+    )]
+    pub collection_metadata: UncheckedAccount<'info>, // required for BurnV1
+
+    // programs
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// CHECK: address checked
+    #[account(address = MPL_TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Anchor will verify this is the sysvar instruction account
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+impl<'info> MergeOptions<'info> {
+    pub fn handler(mut ctx: Context<MergeOptions>) -> Result<()> {
+        require!(!ctx.accounts.config.locked, MergeOptionsError::ProtocolLocked);
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let option_data = &ctx.accounts.option_data;
+        let merged_option_data = &ctx.accounts.merged_option_data;
+        for data in [option_data, merged_option_data] {
+            require!(
+                !data.expired && !data.is_expired(current_timestamp),
+                MergeOptionsError::OptionExpired
+            );
+            require!(data.amount > 0, MergeOptionsError::OptionExhausted);
+        }
+        // only identical options can be merged, anything else would change their terms
+        require!(
+            option_data.expiration == merged_option_data.expiration,
+            MergeOptionsError::ExpirationMismatch
+        );
+        require!(
+            option_data.conversion_multiplier_bps == merged_option_data.conversion_multiplier_bps,
+            MergeOptionsError::MultiplierMismatch
+        );
+
+        let merged_amount = Self::move_amount(&mut ctx)?;
+        Self::burn_merged_option_nft(&ctx)?;
+        Self::update_option_metadata(&ctx, current_timestamp)?;
+
+        // one NFT less is outstanding, total_option_amount is unchanged
+        let config = &mut ctx.accounts.config;
+        config.option_count = config
+            .option_count
+            .checked_sub(1)
+            .ok_or(MergeOptionsError::Overflow)?;
+
+        emit_cpi!(OptionsMerged {
+            holder: ctx.accounts.holder.key(),
+            option_mint: ctx.accounts.option_mint.key(),
+            merged_option_mint: ctx.accounts.merged_option_mint.key(),
+            merged_amount,
+            total_amount: ctx.accounts.option_data.amount,
+            expiration: ctx.accounts.option_data.expiration,
+            timestamp: current_timestamp,
+        });
+
+        msg!(
+            "merged option {} into {} (amount: {})",
+            ctx.accounts.merged_option_mint.key(),
+            ctx.accounts.option_mint.key(),
+            ctx.accounts.option_data.amount
+        );
+        Ok(())
+    }
+
+    /// moves the merged option's amount and settled yield onto the kept option.
+    /// returns the amount moved.
+    pub fn move_amount(ctx: &mut Context<MergeOptions>) -> Result<u64> {
+        // settle both first so each amount keeps the yield it earned up to now
+        let yield_per_unit = ctx.accounts.config.yield_per_unit;
+        let merged_option_data = &mut ctx.accounts.merged_option_data;
+        merged_option_data.settle_yield(yield_per_unit)?;
+        let merged_amount = merged_option_data.amount;
+        let merged_yield = merged_option_data.accrued_yield;
+        merged_option_data.amount = 0;
+        merged_option_data.accrued_yield = 0;
+
+        let option_data = &mut ctx.accounts.option_data;
+        option_data.settle_yield(yield_per_unit)?;
+        option_data.amount = option_data
+            .amount
+            .checked_add(merged_amount)
+            .ok_or(MergeOptionsError::Overflow)?;
+        option_data.accrued_yield = option_data
+            .accrued_yield
+            .checked_add(merged_yield)
+            .ok_or(MergeOptionsError::Overflow)?;
+        Ok(merged_amount)
+    }
+
+    pub fn burn_merged_option_nft(ctx: &Context<MergeOptions>) -> Result<()> {
+        BurnV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .authority(&ctx.accounts.holder.to_account_info()) // the owner burning the token
+            .collection_metadata(Some(&ctx.accounts.collection_metadata.to_account_info()))
+            .metadata(&ctx.accounts.merged_option_metadata.to_account_info())
+            .edition(Some(&ctx.accounts.merged_option_master_edition.to_account_info()))
+            .mint(&ctx.accounts.merged_option_mint.to_account_info())
+            .token(&ctx.accounts.holder_merged_option_ata.to_account_info())
+            .spl_token_program(&ctx.accounts.token_program.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+            .invoke() // holder signs
+            .map_err(|e| {
+                msg!("error burning nft: {:?}", e);
+                e
+            })?;
+        Ok(())
+    }
+
+    /// rewrites the kept option's metadata with its merged amount, paid by the holder.
+    pub fn update_option_metadata(ctx: &Context<MergeOptions>, current_timestamp: i64) -> Result<()> {
+        let holder = ctx.accounts.holder.to_account_info();
+        let config = ctx.accounts.config.to_account_info();
+        let option_mint = ctx.accounts.option_mint.to_account_info();
+        let option_metadata = ctx.accounts.option_metadata.to_account_info();
+        let option_master_edition = ctx.accounts.option_master_edition.to_account_info();
+        let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let sysvar_instructions = ctx.accounts.sysvar_instructions.to_account_info();
+
+        OptionMetadataAccounts {
+            payer: &holder,
+            config: &config,
+            option_mint: &option_mint,
+            option_metadata: &option_metadata,
+            option_master_edition: &option_master_edition,
+            token_metadata_program: &token_metadata_program,
+            system_program: &system_program,
+            sysvar_instructions: &sysvar_instructions,
+        }
+        .update(
+            &ctx.accounts.option_data.key(),
+            &ctx.accounts.option_data,
+            current_timestamp,
+            ctx.accounts.config.bump,
+        )
+    }
+}
+
+#[error_code]
+pub enum MergeOptionsError {
+    #[msg("account address mismatch.")]
+    AddressMismatch,
+    #[msg("protocol is locked.")]
+    ProtocolLocked,
+    #[msg("signer does not hold the option NFT.")]
+    NotOptionHolder,
+    #[msg("option NFT is frozen.")]
+    OptionFrozen,
+    #[msg("an option cannot be merged into itself.")]
+    SameOption,
+    #[msg("option has expired.")]
+    OptionExpired,
+    #[msg("option has no amount left.")]
+    OptionExhausted,
+    #[msg("options must have the same expiration to be merged.")]
+    ExpirationMismatch,
+    #[msg("options must have the same conversion multiplier to be merged.")]
+    MultiplierMismatch,
+    #[msg("calculation overflow")]
+    Overflow,
+    #[msg("option rent payer does not match the option data.")]
+    RentPayerMismatch,
+}
@@ -0,0 +1,588 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token_interface::Mint;
+
+use crate::events::AccountMigrated;
+use crate::instructions::admin::require_authority;
+use crate::instructions::deposit::Deposit;
+use crate::state::{
+    Config, ConfigV0, DepositReceipt, DepositReceiptV0, FeeVault, OptionData, Roles, Treasury,
+    TreasuryV0,
+};
+
+/// grows `account` to `space` bytes, paid by `payer`. the bytes added are zeroed.
+fn grow<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+) -> Result<()> {
+    if account.data_len() < space {
+        // the payer covers the rent of the added bytes whatever the account holds, so
+        // the treasury's idle SOL isn't spent on its own rent
+        let rent = Rent::get()?;
+        let rent_due = rent
+            .minimum_balance(space)
+            .saturating_sub(rent.minimum_balance(account.data_len()));
+        if rent_due > 0 {
+            let transfer_accounts = system_program::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            };
+            let cpi_ctx = CpiContext::new(system_program.clone(), transfer_accounts);
+            system_program::transfer(cpi_ctx, rent_due)?;
+        }
+        // resize isn't available on every solana-account-info we build against
+        #[allow(deprecated)]
+        account.realloc(space, true)?;
+    }
+    Ok(())
+}
+
+/// grows `account` to `space` bytes and loads it. versions after 0 only append fields,
+/// so the ones added since the account was written read as zero.
+fn realloc_and_load<'info, T: AccountDeserialize + Owner>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+) -> Result<T> {
+    require_keys_eq!(
+        *account.owner,
+        T::owner(),
+        anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram
+    );
+    grow(account, payer, system_program, space)?;
+    T::try_deserialize(&mut &account.try_borrow_data()?[..])
+}
+
+/// decodes an account written before accounts were versioned with its version 0 layout
+/// `V`, or returns None if it isn't that layout's size. fields were added in the middle
+/// of those layouts, so they can't be zero-extended into the current one.
+fn load_v0<V: AnchorDeserialize + Space>(
+    account: &AccountInfo,
+    discriminator: &[u8],
+) -> Result<Option<V>> {
+    require_keys_eq!(
+        *account.owner,
+        crate::ID,
+        anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram
+    );
+    if account.data_len() != discriminator.len() + V::INIT_SPACE {
+        return Ok(None);
+    }
+
+    let data = account.try_borrow_data()?;
+    require!(
+        data.starts_with(discriminator),
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+    );
+    let v0 = V::deserialize(&mut &data[discriminator.len()..])
+        .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+    Ok(Some(v0))
+}
+
+/// loads `account` for migration whichever version wrote it, grown to `T`'s space.
+/// a version 0 account is decoded with its own layout `V` and converted by `from_v0`.
+fn load_for_migration<'info, T, V>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    from_v0: impl FnOnce(V) -> T,
+) -> Result<T>
+where
+    T: AccountDeserialize + Owner + Discriminator + Space,
+    V: AnchorDeserialize + Space,
+{
+    let space = 8 + T::INIT_SPACE;
+    match load_v0::<V>(account, T::DISCRIMINATOR)? {
+        Some(v0) => {
+            grow(account, payer, system_program, space)?;
+            Ok(from_v0(v0))
+        }
+        None => realloc_and_load(account, payer, system_program, space),
+    }
+}
+
+/// writes a migrated account back, discriminator included.
+fn store<T: AccountSerialize>(account: &AccountInfo, data: &T) -> Result<()> {
+    let mut buf = account.try_borrow_mut_data()?;
+    data.try_serialize(&mut &mut buf[..])
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    // pays the rent for the extra space
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: an old config may not deserialize as the current layout, it's loaded after the realloc.
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateConfig<'info> {
+    pub fn handler(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+        // fees go to the authority until it sets a recipient, as after initialize
+        let mut config: Config = load_for_migration(
+            &config_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            |v0: ConfigV0| Config::from_v0(v0, ctx.accounts.authority.key()),
+        )?;
+        require_authority(&config, &ctx.accounts.authority.key())?;
+        require!(
+            config.version < Config::VERSION,
+            MigrateError::AlreadyMigrated
+        );
+
+        let from_version = config.version;
+        config.migrate();
+        store(&config_info, &config)?;
+
+        emit_cpi!(AccountMigrated {
+            account: config_info.key(),
+            from_version,
+            to_version: config.version,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!(
+            "migrated config from version {} to {}",
+            from_version,
+            config.version
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateTreasury<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // the config is migrated first, so it's checked like everywhere else
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: an old treasury may not deserialize as the current layout, it's loaded after the realloc.
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    // a version 0 treasury's converted CN is read off the supplies
+    #[account(address = config.cn_mint @ MigrateError::AddressMismatch)]
+    pub cn_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = config.pt_mint @ MigrateError::AddressMismatch)]
+    pub pt_mint: InterfaceAccount<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateTreasury<'info> {
+    pub fn handler(ctx: Context<MigrateTreasury>) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        // version 0 minted PT one to one with CN and only converting burned CN
+        // without burning PT, so the PT in excess of the CN was converted
+        let converted_cn = ctx
+            .accounts
+            .pt_mint
+            .supply
+            .saturating_sub(ctx.accounts.cn_mint.supply);
+        let treasury_info = ctx.accounts.treasury.to_account_info();
+        let mut treasury: Treasury = load_for_migration(
+            &treasury_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            |v0: TreasuryV0| Treasury::from_v0(v0, converted_cn),
+        )?;
+        require!(
+            treasury.version < Treasury::VERSION,
+            MigrateError::AlreadyMigrated
+        );
+
+        let from_version = treasury.version;
+        treasury.migrate();
+        store(&treasury_info, &treasury)?;
+
+        emit_cpi!(AccountMigrated {
+            account: treasury_info.key(),
+            from_version,
+            to_version: treasury.version,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!(
+            "migrated treasury from version {} to {}",
+            from_version,
+            treasury.version
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateOptionData<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: only used to derive the option data PDA.
+    pub option_mint: UncheckedAccount<'info>,
+
+    /// CHECK: an old option data may not deserialize as the current layout, it's loaded after the realloc.
+    #[account(
+        mut,
+        seeds = [OptionData::SEED_PREFIX, option_mint.key().as_ref()],
+        bump,
+    )]
+    pub option_data: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateOptionData<'info> {
+    pub fn handler(ctx: Context<MigrateOptionData>) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        let option_data_info = ctx.accounts.option_data.to_account_info();
+        let mut option_data: OptionData = load_for_migration(
+            &option_data_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            OptionData::from_v0,
+        )?;
+        require!(
+            option_data.version < OptionData::VERSION,
+            MigrateError::AlreadyMigrated
+        );
+
+        // the rent of options issued before the rent payer was stored used to go to
+        // the authority on close, so it keeps getting it
+        let from_version = option_data.version;
+        option_data.migrate(ctx.accounts.authority.key());
+        store(&option_data_info, &option_data)?;
+
+        emit_cpi!(AccountMigrated {
+            account: option_data_info.key(),
+            from_version,
+            to_version: option_data.version,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!(
+            "migrated option data {} from version {} to {}",
+            option_data_info.key(),
+            from_version,
+            option_data.version
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct MigrateDepositReceipt<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: only used to derive the deposit receipt PDA.
+    pub depositor: UncheckedAccount<'info>,
+
+    /// CHECK: an old receipt may not deserialize as the current layout, it's loaded after the realloc.
+    #[account(
+        mut,
+        seeds = [
+            DepositReceipt::SEED_PREFIX,
+            depositor.key().as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub deposit_receipt: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateDepositReceipt<'info> {
+    pub fn handler(ctx: Context<MigrateDepositReceipt>, nonce: u64) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        let receipt_info = ctx.accounts.deposit_receipt.to_account_info();
+        let mut receipt: DepositReceipt = realloc_and_load(
+            &receipt_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            8 + DepositReceipt::INIT_SPACE,
+        )?;
+        require!(
+            receipt.version < DepositReceipt::VERSION,
+            MigrateError::AlreadyMigrated
+        );
+
+        let from_version = receipt.version;
+        receipt.migrate();
+        store(&receipt_info, &receipt)?;
+
+        emit_cpi!(AccountMigrated {
+            account: receipt_info.key(),
+            from_version,
+            to_version: receipt.version,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!(
+            "migrated deposit receipt {} from version {} to {}",
+            nonce,
+            from_version,
+            receipt.version
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateLegacyDepositReceipt<'info> {
+    // pays the rent of the receipt it's moved to
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut, // needs mut to advance the deposit nonce
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: the wallet the legacy receipt belongs to, gets its rent back.
+    #[account(mut)]
+    pub depositor: UncheckedAccount<'info>,
+
+    /// CHECK: a version 0 receipt doesn't deserialize as the current layout, it's decoded in the handler.
+    #[account(
+        mut,
+        seeds = [DepositReceipt::SEED_PREFIX, depositor.key().as_ref()],
+        bump,
+    )]
+    pub legacy_deposit_receipt: UncheckedAccount<'info>,
+
+    /// CHECK: created in the handler for an unclaimed receipt, keyed by the next deposit nonce.
+    #[account(
+        mut,
+        seeds = [
+            DepositReceipt::SEED_PREFIX,
+            depositor.key().as_ref(),
+            &config.deposit_nonce.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub deposit_receipt: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateLegacyDepositReceipt<'info> {
+    pub fn handler(ctx: Context<MigrateLegacyDepositReceipt>) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        let legacy_info = ctx.accounts.legacy_deposit_receipt.to_account_info();
+        let legacy: DepositReceiptV0 = load_v0(&legacy_info, DepositReceipt::DISCRIMINATOR)?
+            .ok_or(MigrateError::NotLegacyReceipt)?;
+
+        // a claimed receipt has served its purpose, so it's only closed
+        let nonce = if legacy.nft_issued {
+            None
+        } else {
+            let nonce = ctx
+                .accounts
+                .config
+                .take_deposit_nonce()
+                .ok_or(crate::ErrorCode::Overflow)?;
+            let mut receipt = DepositReceipt::from_v0(
+                legacy,
+                nonce,
+                ctx.bumps.deposit_receipt,
+                Clock::get()?.unix_timestamp,
+            );
+            receipt.migrate();
+            Deposit::add_receipt_amount(&mut ctx.accounts.config, receipt.amount)?;
+            Self::create_receipt(&ctx, &receipt)?;
+            Some(nonce)
+        };
+
+        // close the legacy receipt, its rent goes back to the depositor
+        let lamports = legacy_info.lamports();
+        legacy_info.sub_lamports(lamports)?;
+        ctx.accounts.depositor.add_lamports(lamports)?;
+        legacy_info.assign(&system_program::ID);
+        #[allow(deprecated)]
+        legacy_info.realloc(0, false)?;
+
+        emit_cpi!(AccountMigrated {
+            account: legacy_info.key(),
+            from_version: 0,
+            to_version: DepositReceipt::VERSION,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        match nonce {
+            Some(nonce) => msg!(
+                "moved legacy deposit receipt of {} to nonce {}",
+                ctx.accounts.depositor.key(),
+                nonce
+            ),
+            None => msg!(
+                "closed claimed legacy deposit receipt of {}",
+                ctx.accounts.depositor.key()
+            ),
+        }
+        Ok(())
+    }
+
+    /// creates the nonce-keyed receipt, signed for with its PDA seeds, and writes `receipt`.
+    pub fn create_receipt(
+        ctx: &Context<MigrateLegacyDepositReceipt>,
+        receipt: &DepositReceipt,
+    ) -> Result<()> {
+        let space = 8 + DepositReceipt::INIT_SPACE;
+        let depositor_key = ctx.accounts.depositor.key();
+        let nonce_bytes = receipt.nonce.to_le_bytes();
+        let bump_seed = [receipt.bump];
+        let receipt_seeds_with_bump =
+            DepositReceipt::get_seeds_with_bump(depositor_key.as_ref(), &nonce_bytes, &bump_seed);
+        let signer_seeds = &[&receipt_seeds_with_bump[..]];
+
+        let create_accounts = system_program::CreateAccount {
+            from: ctx.accounts.authority.to_account_info(),
+            to: ctx.accounts.deposit_receipt.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            create_accounts,
+            signer_seeds,
+        );
+        system_program::create_account(
+            cpi_ctx,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &crate::ID,
+        )?;
+        store(&ctx.accounts.deposit_receipt.to_account_info(), receipt)
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitFeeVault<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    // plain init: an existing fee vault is left alone
+    #[account(
+        init,
+        payer = authority,
+        seeds = [FeeVault::SEED_PREFIX],
+        bump,
+        space = 8 + FeeVault::INIT_SPACE
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitFeeVault<'info> {
+    pub fn handler(ctx: Context<InitFeeVault>) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        ctx.accounts.fee_vault.bump = ctx.bumps.fee_vault;
+        msg!("fee vault created: {}", ctx.accounts.fee_vault.key());
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitRoles<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    // plain init: an existing roles account is left alone
+    #[account(
+        init,
+        payer = authority,
+        seeds = [Roles::SEED_PREFIX],
+        bump,
+        space = 8 + Roles::INIT_SPACE
+    )]
+    pub roles: Box<Account<'info, Roles>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitRoles<'info> {
+    pub fn handler(ctx: Context<InitRoles>) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        // no one holds a role until the authority grants it
+        ctx.accounts.roles.members = Vec::new();
+        ctx.accounts.roles.bump = ctx.bumps.roles;
+        msg!("roles account created: {}", ctx.accounts.roles.key());
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum MigrateError {
+    #[msg("account is already on the current version.")]
+    AlreadyMigrated,
+    #[msg("account address mismatch.")]
+    AddressMismatch,
+    #[msg("not a version 0 deposit receipt.")]
+    NotLegacyReceipt,
+}
@@ -4,6 +4,27 @@ pub mod convert;
 pub mod admin;
 pub mod initialize_option;
 pub mod close_option_account;
+pub mod redeem;
+pub mod sync_expired_options;
+pub mod deposit_spl;
+pub mod treasury_asset;
+pub mod claim_fees;
+pub mod authority;
+pub mod option_nft;
+pub mod token_transfer;
+pub mod deposit_with_option;
+pub mod duration_tier;
+pub mod option_yield;
+pub mod treasury_withdrawal;
+pub mod refresh_option_metadata;
+pub mod split_option;
+pub mod merge_options;
+pub mod option_freeze;
+pub mod roles;
+pub mod cancel_deposit;
+pub mod close_deposit_receipt;
+pub mod expire_deposit_receipt;
+pub mod migrate;
 // pub mod orca;
 
 pub use deposit::*;
@@ -12,4 +33,23 @@ pub use convert::*;
 pub use admin::*;
 pub use initialize_option::*;
 pub use close_option_account::*;
+pub use redeem::*;
+pub use sync_expired_options::*;
+pub use deposit_spl::*;
+pub use treasury_asset::*;
+pub use claim_fees::*;
+pub use authority::*;
+pub use deposit_with_option::*;
+pub use duration_tier::*;
+pub use option_yield::*;
+pub use treasury_withdrawal::*;
+pub use refresh_option_metadata::*;
+pub use split_option::*;
+pub use merge_options::*;
+pub use option_freeze::*;
+pub use roles::*;
+pub use cancel_deposit::*;
+pub use close_deposit_receipt::*;
+pub use expire_deposit_receipt::*;
+pub use migrate::*;
 // pub use orca::*;
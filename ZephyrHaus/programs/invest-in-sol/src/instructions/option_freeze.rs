@@ -0,0 +1,256 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use mpl_token_metadata::{
+    accounts::MasterEdition,
+    instructions::{FreezeDelegatedAccountCpiBuilder, ThawDelegatedAccountCpiBuilder},
+    ID as MPL_TOKEN_METADATA_ID,
+};
+
+use crate::events::{OptionFrozen, OptionThawed};
+use crate::instructions::admin::{require_role, AdminError};
+use crate::state::{Config, OptionData, Role, Roles};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FreezeOption<'info> {
+    // the authority, a pause guardian or metadata manager, or anyone once the option has expired
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut, // Token Metadata takes the delegate as writable
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(mint::token_program = token_program)]
+    pub option_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [OptionData::SEED_PREFIX, option_mint.key().as_ref()],
+        bump = option_data.bump,
+        constraint = option_data.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub option_data: Account<'info, OptionData>,
+
+    // the token account currently holding the NFT
+    #[account(
+        mut,
+        token::mint = option_mint,
+        token::token_program = token_program,
+        constraint = option_token_account.amount == 1 @ OptionFreezeError::EmptyTokenAccount,
+    )]
+    pub option_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: checked by Metaplex CPI. PDA derived from option_mint, holds the freeze authority.
+    #[account(
+        address = MasterEdition::find_pda(&option_mint.key()).0 @ OptionFreezeError::AddressMismatch,
+    )]
+    pub option_master_edition: UncheckedAccount<'info>,
+
+    // programs
+    pub token_program: Interface<'info, TokenInterface>,
+    /// CHECK: address checked
+    #[account(address = MPL_TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+impl<'info> FreezeOption<'info> {
+    pub fn handler(ctx: Context<FreezeOption>, unlock_at: Option<i64>) -> Result<()> {
+        require!(
+            !ctx.accounts.option_token_account.is_frozen(),
+            OptionFreezeError::AlreadyFrozen
+        );
+        require!(
+            ctx.accounts.option_token_account.delegate == COption::Some(ctx.accounts.config.key()),
+            OptionFreezeError::ConfigNotDelegate
+        );
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let option_data = &ctx.accounts.option_data;
+        let expired = option_data.expired || option_data.is_expired(current_timestamp);
+        if expired {
+            // expired options can't convert any more, so anyone can freeze them for good
+            require!(unlock_at.is_none(), OptionFreezeError::InvalidUnlockTime);
+        } else {
+            require_role(
+                &ctx.accounts.config,
+                &ctx.accounts.roles,
+                &ctx.accounts.signer.key(),
+                &[Role::PauseGuardian, Role::MetadataManager],
+            )?;
+            if let Some(unlock_at) = unlock_at {
+                require!(
+                    unlock_at > current_timestamp,
+                    OptionFreezeError::InvalidUnlockTime
+                );
+            }
+        }
+
+        ctx.accounts.option_data.unlock_at = unlock_at;
+
+        let bump_seed = [ctx.accounts.config.bump];
+        let config_seeds = Config::get_seeds_with_bump(&bump_seed);
+        FreezeDelegatedAccountCpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .delegate(&ctx.accounts.config.to_account_info())
+            .token_account(&ctx.accounts.option_token_account.to_account_info())
+            .edition(&ctx.accounts.option_master_edition.to_account_info())
+            .mint(&ctx.accounts.option_mint.to_account_info())
+            .token_program(&ctx.accounts.token_program.to_account_info())
+            .invoke_signed(&[&config_seeds[..]])?;
+
+        emit_cpi!(OptionFrozen {
+            option_mint: ctx.accounts.option_mint.key(),
+            token_account: ctx.accounts.option_token_account.key(),
+            frozen_by: ctx.accounts.signer.key(),
+            unlock_at,
+            expired,
+            timestamp: current_timestamp,
+        });
+        msg!(
+            "froze option {} (unlock at: {:?})",
+            ctx.accounts.option_mint.key(),
+            unlock_at
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ThawOption<'info> {
+    // the authority or a metadata manager, or anyone once the option's cliff has passed
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut, // Token Metadata takes the delegate as writable
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(mint::token_program = token_program)]
+    pub option_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [OptionData::SEED_PREFIX, option_mint.key().as_ref()],
+        bump = option_data.bump,
+        constraint = option_data.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub option_data: Account<'info, OptionData>,
+
+    #[account(
+        mut,
+        token::mint = option_mint,
+        token::token_program = token_program,
+    )]
+    pub option_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: checked by Metaplex CPI. PDA derived from option_mint, holds the freeze authority.
+    #[account(
+        address = MasterEdition::find_pda(&option_mint.key()).0 @ OptionFreezeError::AddressMismatch,
+    )]
+    pub option_master_edition: UncheckedAccount<'info>,
+
+    // programs
+    pub token_program: Interface<'info, TokenInterface>,
+    /// CHECK: address checked
+    #[account(address = MPL_TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+impl<'info> ThawOption<'info> {
+    pub fn handler(ctx: Context<ThawOption>) -> Result<()> {
+        require!(
+            ctx.accounts.option_token_account.is_frozen(),
+            OptionFreezeError::NotFrozen
+        );
+
+        // expired options stay frozen
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let option_data = &ctx.accounts.option_data;
+        require!(
+            !option_data.expired && !option_data.is_expired(current_timestamp),
+            OptionFreezeError::OptionExpired
+        );
+
+        // anyone can thaw an option frozen until a cliff once it has passed
+        let cliff_passed = option_data
+            .unlock_at
+            .is_some_and(|unlock_at| current_timestamp >= unlock_at);
+        if !cliff_passed {
+            // before then only the authority or a metadata manager can
+            let has_cliff = option_data.unlock_at.is_some();
+            require_role(
+                &ctx.accounts.config,
+                &ctx.accounts.roles,
+                &ctx.accounts.signer.key(),
+                &[Role::MetadataManager],
+            )
+            .map_err(|err| {
+                if has_cliff && err == AdminError::Unauthorized.into() {
+                    OptionFreezeError::CliffNotReached.into()
+                } else {
+                    err
+                }
+            })?;
+        }
+
+        ctx.accounts.option_data.unlock_at = None;
+
+        let bump_seed = [ctx.accounts.config.bump];
+        let config_seeds = Config::get_seeds_with_bump(&bump_seed);
+        ThawDelegatedAccountCpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .delegate(&ctx.accounts.config.to_account_info())
+            .token_account(&ctx.accounts.option_token_account.to_account_info())
+            .edition(&ctx.accounts.option_master_edition.to_account_info())
+            .mint(&ctx.accounts.option_mint.to_account_info())
+            .token_program(&ctx.accounts.token_program.to_account_info())
+            .invoke_signed(&[&config_seeds[..]])?;
+
+        emit_cpi!(OptionThawed {
+            option_mint: ctx.accounts.option_mint.key(),
+            token_account: ctx.accounts.option_token_account.key(),
+            thawed_by: ctx.accounts.signer.key(),
+            timestamp: current_timestamp,
+        });
+        msg!("thawed option {}", ctx.accounts.option_mint.key());
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum OptionFreezeError {
+    #[msg("account address mismatch.")]
+    AddressMismatch,
+    #[msg("token account does not hold the option NFT.")]
+    EmptyTokenAccount,
+    #[msg("option NFT is already frozen.")]
+    AlreadyFrozen,
+    #[msg("option NFT is not frozen.")]
+    NotFrozen,
+    #[msg("config is not the delegate of the option's token account.")]
+    ConfigNotDelegate,
+    #[msg("unlock time must be in the future, and expired options can't have one.")]
+    InvalidUnlockTime,
+    #[msg("option has expired.")]
+    OptionExpired,
+    #[msg("option is frozen until its cliff.")]
+    CliffNotReached,
+}
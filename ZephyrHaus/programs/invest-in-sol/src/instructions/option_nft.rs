@@ -0,0 +1,230 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{approve, mint_to, Approve, MintTo};
+use mpl_token_metadata::{
+    instructions::{CreateV1CpiBuilder, UpdateV1CpiBuilder, VerifyCollectionV1CpiBuilder},
+    types::{Collection, Creator, Data, PrintSupply},
+};
+
+use crate::state::{Config, OptionData, OptionStatus};
+
+/// the metadata service serving zOption JSON, keyed by the OptionData PDA.
+pub const OPTION_METADATA_BASE_URI: &str = "https://metadata.zephyr.haus";
+
+/// NFT name for an option in `status`, e.g. "zOption (expired)".
+pub fn option_metadata_name(status: OptionStatus) -> String {
+    match status {
+        OptionStatus::Active => "zOption".into(),
+        _ => format!("zOption ({})", status.as_str()),
+    }
+}
+
+/// NFT URI for an option. the remaining amount, expiration and status are passed
+/// along so the metadata service can render them as attributes.
+pub fn option_metadata_uri(
+    option_data_key: &Pubkey,
+    option_data: &OptionData,
+    status: OptionStatus,
+) -> String {
+    format!(
+        "{}/{}?amount={}&expiration={}&status={}",
+        OPTION_METADATA_BASE_URI,
+        option_data_key,
+        option_data.amount,
+        option_data.expiration,
+        status.as_str()
+    )
+}
+
+/// accounts needed to mint a zOption NFT into the main collection.
+/// shared by every instruction that issues an option.
+pub struct OptionNftAccounts<'a, 'info> {
+    pub payer: &'a AccountInfo<'info>,
+    /// the owner of `owner_option_ata`, must sign to approve the config as delegate.
+    pub owner: &'a AccountInfo<'info>,
+    pub config: &'a AccountInfo<'info>,
+    pub option_mint: &'a AccountInfo<'info>,
+    pub owner_option_ata: &'a AccountInfo<'info>,
+    pub option_metadata: &'a AccountInfo<'info>,
+    pub option_master_edition: &'a AccountInfo<'info>,
+    pub collection_mint: &'a AccountInfo<'info>,
+    pub collection_metadata: &'a AccountInfo<'info>,
+    pub collection_master_edition: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub sysvar_instructions: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> OptionNftAccounts<'a, 'info> {
+    /// mints the NFT, creates its metadata + master edition and verifies it
+    /// as part of the main collection. the config PDA signs every step.
+    /// the config is also approved as delegate of the owner's ATA so it can freeze the NFT.
+    pub fn issue(&self, config_bump: u8, program_id: &Pubkey) -> Result<()> {
+        self.mint_option_to_owner(config_bump)?;
+        self.create_option_metadata_account(config_bump, program_id)?;
+        self.verify_mint_with_collection(config_bump)?;
+        self.approve_config_delegate()?;
+        Ok(())
+    }
+
+    pub fn mint_option_to_owner(&self, config_bump: u8) -> Result<()> {
+        let bump_seed = [config_bump];
+        let config_seeds = Config::get_seeds_with_bump(&bump_seed);
+
+        // 1. mint the option NFT to the owner's ATA
+        mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                MintTo {
+                    mint: self.option_mint.clone(),
+                    to: self.owner_option_ata.clone(),
+                    authority: self.config.clone(),
+                },
+                &[&config_seeds[..]],
+            ),
+            1, // mint 1 NFT
+        )
+    }
+
+    pub fn create_option_metadata_account(
+        &self,
+        config_bump: u8,
+        program_id: &Pubkey,
+    ) -> Result<()> {
+        // derive the OptionData PDA address for the URI
+        let (option_data_pda_key, _) = Pubkey::find_program_address(
+            &[OptionData::SEED_PREFIX, self.option_mint.key().as_ref()],
+            program_id,
+        );
+
+        // format the metadata URI with the OptionData PDA address
+        // the off chain service will parse account data into properly formatted metadata for
+        // marketplaces / other uses
+        let uri = format!("{}/{}", OPTION_METADATA_BASE_URI, option_data_pda_key);
+
+        let bump_seed = [config_bump];
+        let config_seeds = Config::get_seeds_with_bump(&bump_seed);
+
+        // 2. create the metadata account
+        CreateV1CpiBuilder::new(self.token_metadata_program)
+            .metadata(self.option_metadata)
+            .mint(self.option_mint, false)
+            .authority(self.config)
+            .payer(self.payer)
+            .update_authority(self.config, true)
+            .system_program(self.system_program)
+            .master_edition(Some(self.option_master_edition))
+            .spl_token_program(Some(self.token_program))
+            .sysvar_instructions(self.sysvar_instructions)
+            .name("zOption".into())
+            .symbol("zOption".into())
+            .uri(uri)
+            .seller_fee_basis_points(0)
+            .creators(vec![Creator {
+                address: self.config.key(),
+                verified: true,
+                share: 100,
+            }])
+            .collection(Collection {
+                key: self.collection_mint.key(),
+                verified: false,
+            })
+            .print_supply(PrintSupply::Zero)
+            .invoke_signed(&[&config_seeds[..]])?;
+
+        Ok(())
+    }
+
+    pub fn verify_mint_with_collection(&self, config_bump: u8) -> Result<()> {
+        let bump_seed = [config_bump];
+        let config_seeds = Config::get_seeds_with_bump(&bump_seed);
+
+        // 3. set and verify collection item
+        msg!("setting and verifying collection item");
+        VerifyCollectionV1CpiBuilder::new(self.token_metadata_program)
+            .metadata(self.option_metadata)
+            .authority(self.config)
+            .collection_mint(self.collection_mint)
+            .collection_master_edition(Some(self.collection_master_edition))
+            .collection_metadata(Some(self.collection_metadata))
+            .sysvar_instructions(self.sysvar_instructions)
+            .system_program(self.system_program)
+            .invoke_signed(&[&config_seeds[..]])?;
+
+        Ok(())
+    }
+
+    pub fn approve_config_delegate(&self) -> Result<()> {
+        // 4. the master edition takes over the mint's freeze authority in CreateV1,
+        // so the config freezes through Token Metadata as the ATA's delegate
+        approve(
+            CpiContext::new(
+                self.token_program.clone(),
+                Approve {
+                    to: self.owner_option_ata.clone(),
+                    delegate: self.config.clone(),
+                    authority: self.owner.clone(),
+                },
+            ),
+            1,
+        )
+    }
+}
+
+/// accounts needed to rewrite a zOption NFT's metadata with UpdateV1.
+/// shared by every instruction that changes what an option reports.
+pub struct OptionMetadataAccounts<'a, 'info> {
+    pub payer: &'a AccountInfo<'info>,
+    pub config: &'a AccountInfo<'info>,
+    pub option_mint: &'a AccountInfo<'info>,
+    pub option_metadata: &'a AccountInfo<'info>,
+    pub option_master_edition: &'a AccountInfo<'info>,
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub sysvar_instructions: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> OptionMetadataAccounts<'a, 'info> {
+    /// refreshes the name and URI with the option's remaining amount, expiration
+    /// and status. the config PDA signs as update authority.
+    pub fn update(
+        &self,
+        option_data_key: &Pubkey,
+        option_data: &OptionData,
+        current_timestamp: i64,
+        config_bump: u8,
+    ) -> Result<()> {
+        let status = option_data.status(current_timestamp);
+        let bump_seed = [config_bump];
+        let config_seeds = Config::get_seeds_with_bump(&bump_seed);
+
+        // symbol, royalties and creators stay as they were created
+        UpdateV1CpiBuilder::new(self.token_metadata_program)
+            .authority(self.config)
+            .mint(self.option_mint)
+            .metadata(self.option_metadata)
+            .edition(Some(self.option_master_edition))
+            .payer(self.payer)
+            .system_program(self.system_program)
+            .sysvar_instructions(self.sysvar_instructions)
+            .data(Data {
+                name: option_metadata_name(status),
+                symbol: "zOption".into(),
+                uri: option_metadata_uri(option_data_key, option_data, status),
+                seller_fee_basis_points: 0,
+                creators: Some(vec![Creator {
+                    address: self.config.key(),
+                    verified: true,
+                    share: 100,
+                }]),
+            })
+            .invoke_signed(&[&config_seeds[..]])?;
+
+        msg!(
+            "updated metadata for option {} (status: {})",
+            self.option_mint.key(),
+            status.as_str()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::events::{OptionYieldClaimed, YieldDistributed};
+use crate::instructions::admin::require_role;
+use crate::state::{Config, OptionData, Role, Roles, Treasury};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DistributeYield<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    // the distributed yield stays in the treasury, reserved until claimed
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+impl<'info> DistributeYield<'info> {
+    pub fn handler(ctx: Context<DistributeYield>, amount: u64) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.roles,
+            &ctx.accounts.authority.key(),
+            &[Role::TreasuryManager],
+        )?;
+
+        require!(amount > 0, OptionYieldError::ZeroAmount);
+        require!(
+            ctx.accounts.config.total_option_amount > 0,
+            OptionYieldError::NoOutstandingOptions
+        );
+
+        // only idle SOL that isn't already owed to option holders can be distributed
+        let idle_sol = Treasury::idle_sol(&ctx.accounts.treasury.to_account_info())?;
+        require!(
+            ctx.accounts.treasury.unreserved_sol(idle_sol) >= amount,
+            OptionYieldError::InsufficientTreasuryFunds
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.accrue_yield(amount)?;
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.reserved_yield = treasury
+            .reserved_yield
+            .checked_add(amount)
+            .ok_or(OptionYieldError::Overflow)?;
+
+        emit_cpi!(YieldDistributed {
+            amount,
+            total_option_amount: config.total_option_amount,
+            yield_per_unit: config.yield_per_unit,
+        });
+        msg!(
+            "distributed {} lamports of yield over {} option units",
+            amount,
+            config.total_option_amount
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimOptionYield<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    // holder's ATA for the option NFT, proves they currently hold it
+    #[account(
+        associated_token::mint = option_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+        constraint = holder_option_ata.amount == 1 @ OptionYieldError::NotOptionHolder,
+        // without the config as delegate the NFT could no longer be frozen
+        constraint = holder_option_ata.delegate == COption::Some(config.key())
+            @ crate::ErrorCode::OptionNotDelegated,
+    )]
+    pub holder_option_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    // treasury PDA holds the reserved yield paid out to the holder
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mint::token_program = token_program)]
+    pub option_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [OptionData::SEED_PREFIX, option_mint.key().as_ref()],
+        bump = option_data.bump,
+        constraint = option_data.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub option_data: Account<'info, OptionData>,
+
+    // programs
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClaimOptionYield<'info> {
+    pub fn handler(ctx: Context<ClaimOptionYield>) -> Result<()> {
+        // a frozen option can't claim until it is thawed. expired options are frozen for
+        // good, but the yield they earned while outstanding is still theirs
+        let option_data = &mut ctx.accounts.option_data;
+        let expired = option_data.expired || option_data.is_expired(Clock::get()?.unix_timestamp);
+        require!(
+            expired || !ctx.accounts.holder_option_ata.is_frozen(),
+            OptionYieldError::OptionFrozen
+        );
+        option_data.settle_yield(ctx.accounts.config.yield_per_unit)?;
+        require!(
+            option_data.accrued_yield > 0,
+            OptionYieldError::NoYieldToClaim
+        );
+
+        let amount = pay_accrued_yield(
+            &mut ctx.accounts.treasury,
+            &ctx.accounts.holder.to_account_info(),
+            option_data,
+        )?;
+
+        emit_cpi!(OptionYieldClaimed {
+            recipient: ctx.accounts.holder.key(),
+            option_mint: ctx.accounts.option_mint.key(),
+            amount,
+        });
+        msg!(
+            "claimed {} lamports of yield for option {}",
+            amount,
+            ctx.accounts.option_mint.key()
+        );
+        Ok(())
+    }
+}
+
+/// pays an option's settled yield out of the treasury's reserve to `recipient`
+/// and returns the amount paid. the option must be settled first.
+pub fn pay_accrued_yield<'info>(
+    treasury: &mut Account<'info, Treasury>,
+    recipient: &AccountInfo<'info>,
+    option_data: &mut OptionData,
+) -> Result<u64> {
+    let amount = option_data.accrued_yield;
+    if amount == 0 {
+        return Ok(0);
+    }
+
+    treasury.reserved_yield = treasury
+        .reserved_yield
+        .checked_sub(amount)
+        .ok_or(OptionYieldError::InsufficientReservedYield)?;
+    option_data.accrued_yield = 0;
+
+    // the treasury is program-owned, so lamports are moved directly
+    treasury.sub_lamports(amount)?;
+    recipient.add_lamports(amount)?;
+    Ok(amount)
+}
+
+#[error_code]
+pub enum OptionYieldError {
+    #[msg("yield amount must be greater than zero.")]
+    ZeroAmount,
+    #[msg("there are no outstanding options to distribute yield to.")]
+    NoOutstandingOptions,
+    #[msg("treasury does not hold enough unreserved idle SOL.")]
+    InsufficientTreasuryFunds,
+    #[msg("signer does not hold the option NFT.")]
+    NotOptionHolder,
+    #[msg("option has no yield to claim.")]
+    NoYieldToClaim,
+    #[msg("option yield exceeds the treasury's reserved yield.")]
+    InsufficientReservedYield,
+    #[msg("calculation overflow")]
+    Overflow,
+    #[msg("option NFT is frozen.")]
+    OptionFrozen,
+}
@@ -0,0 +1,301 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface};
+
+use crate::events::Redeemed;
+use crate::state::{Config, OptionData, Rounding, Treasury};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct Redeem<'info> {
+    #[account(mut)]
+    pub redeemer: Signer<'info>,
+
+    // redeemer's CN ATA (source of the CN being burned)
+    #[account(
+        mut,
+        associated_token::mint = cn_mint,
+        associated_token::authority = redeemer,
+        associated_token::token_program = token_program,
+    )]
+    pub redeemer_cn_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut, // needs mut to take a redeemed option's amount off the config totals
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    // treasury PDA holds the SOL paid out to the redeemer
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    // mints (checked against config)
+    #[account(
+        mut,
+        address = config.cn_mint @ RedeemError::AddressMismatch
+    )]
+    pub cn_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = config.pt_mint @ RedeemError::AddressMismatch
+    )]
+    pub pt_mint: InterfaceAccount<'info, Mint>,
+
+    // protocol's PT ATA (source of the PT being burned)
+    #[account(
+        mut,
+        associated_token::mint = pt_mint,
+        associated_token::authority = config, // config PDA owns the protocol's PT ATA
+        associated_token::token_program = token_program,
+    )]
+    pub protocol_pt_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // the redeemer's option NFT and its data, passed to redeem CN together with the option
+    // it came with. the option's amount goes down by the CN redeemed and the PT reserved
+    // for it is burned. they can only be left out for CN whose PT is already gone
+    #[account(
+        token::authority = redeemer,
+        token::token_program = token_program,
+        constraint = !redeemer_option_ata.is_frozen() @ RedeemError::OptionFrozen,
+        // without the config as delegate the NFT could no longer be frozen
+        constraint = redeemer_option_ata.delegate == COption::Some(config.key())
+            @ crate::ErrorCode::OptionNotDelegated,
+    )]
+    pub redeemer_option_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [OptionData::SEED_PREFIX, option_data.mint.as_ref()],
+        bump = option_data.bump,
+        constraint = option_data.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub option_data: Option<Account<'info, OptionData>>,
+
+    // programs
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Redeem<'info> {
+    pub fn assert_protocol_state(ctx: &Context<Redeem>, amount: u64) -> Result<()> {
+        // ensure the protocol is not locked
+        require!(!ctx.accounts.config.locked, RedeemError::ProtocolLocked);
+        require!(
+            !ctx.accounts.config.redeem_locked,
+            RedeemError::RedemptionsLocked
+        );
+
+        require!(amount > 0, RedeemError::ZeroAmount);
+        require!(
+            ctx.accounts.redeemer_cn_ata.amount >= amount,
+            RedeemError::InsufficientCnBalance
+        );
+        Ok(())
+    }
+
+    pub fn calculate_payout(ctx: &mut Context<Redeem>, amount: u64) -> Result<u64> {
+        // calculate nav and determine the SOL owed for the redeemed tokens.
+        // nav and payout are both rounded down so rounding always favours the protocol.
+        let idle_sol = Treasury::idle_sol(&ctx.accounts.treasury.to_account_info())?;
+        let nav = ctx.accounts.treasury.calculate_nav(
+            idle_sol,
+            ctx.accounts.cn_mint.supply,
+            Rounding::Down,
+        )?;
+        let payout = Treasury::lamports_for_tokens(amount, nav)?;
+        require!(payout > 0, RedeemError::RedeemTooSmall);
+
+        // only lamports above the treasury's rent-exempt minimum and not reserved
+        // for option yield can be paid out
+        require!(
+            ctx.accounts.treasury.unreserved_sol(idle_sol) >= payout,
+            RedeemError::InsufficientTreasuryFunds
+        );
+        ctx.accounts.treasury.update_nav(nav)?;
+
+        msg!("calculated NAV: {}", nav);
+        msg!("SOL to pay out: {}", payout);
+        Ok(payout)
+    }
+
+    /// the redeemed CN no longer needs its share of the SOL paid in for the backing units.
+    pub fn release_backing(ctx: &mut Context<Redeem>, amount: u64) -> Result<()> {
+        let cn_supply = ctx.accounts.cn_mint.supply;
+        let released = ctx.accounts.treasury.release_backing(amount, cn_supply)?;
+        msg!("released {} lamports of backing", released);
+        Ok(())
+    }
+
+    /// takes `amount` off the redeemer's option, if one was passed, and returns the PT
+    /// reserved for it that must be burned. CN redeemed without an option burns no PT,
+    /// so only CN no option or receipt is set aside for can be.
+    pub fn release_option(ctx: &mut Context<Redeem>, amount: u64) -> Result<u64> {
+        let (option_ata, option_data) = match (
+            ctx.accounts.redeemer_option_ata.as_ref(),
+            ctx.accounts.option_data.as_mut(),
+        ) {
+            (Some(option_ata), Some(option_data)) => (option_ata, option_data),
+            (None, None) => {
+                // without an option no PT is burned, so the CN must be CN whose PT is
+                // already gone, not CN an outstanding option or receipt still pairs with
+                let unpaired_cn = ctx.accounts.config.unpaired_cn(ctx.accounts.cn_mint.supply);
+                require!(amount <= unpaired_cn, RedeemError::OptionRequired);
+                return Ok(0);
+            }
+            _ => return err!(RedeemError::IncompleteOptionAccounts),
+        };
+        require_keys_eq!(
+            option_ata.mint,
+            option_data.mint,
+            RedeemError::AddressMismatch
+        );
+        require!(option_ata.amount == 1, RedeemError::OptionNotHeld);
+        require!(
+            amount <= option_data.amount,
+            RedeemError::InsufficientOptionAmount
+        );
+
+        // settle the option's yield before its amount changes
+        let config = &mut ctx.accounts.config;
+        option_data.settle_yield(config.yield_per_unit)?;
+        option_data.amount = option_data
+            .amount
+            .checked_sub(amount)
+            .ok_or(RedeemError::Overflow)?;
+        config.total_option_amount = config
+            .total_option_amount
+            .checked_sub(amount)
+            .ok_or(RedeemError::Overflow)?;
+        if option_data.amount == 0 {
+            config.option_count = config.option_count.saturating_sub(1);
+        }
+
+        // the PT reserved for the option is scaled by its conversion multiplier,
+        // rounded down like the PT a conversion would have paid out
+        let pt_amount = option_data.pt_for_cn(amount, Rounding::Down)?;
+        require!(
+            ctx.accounts.protocol_pt_ata.amount >= pt_amount,
+            RedeemError::InsufficientPtBalance
+        );
+        msg!(
+            "released {} from option {} ({} left)",
+            amount,
+            option_data.mint,
+            option_data.amount
+        );
+        Ok(pt_amount)
+    }
+
+    pub fn burn_cn_from_redeemer(ctx: &Context<Redeem>, amount: u64) -> Result<()> {
+        // burn CN tokens from the redeemer's ATA, redeemer signs
+        let cpi_accounts_cn = Burn {
+            mint: ctx.accounts.cn_mint.to_account_info(),
+            from: ctx.accounts.redeemer_cn_ata.to_account_info(),
+            authority: ctx.accounts.redeemer.to_account_info(),
+        };
+        let cpi_ctx_cn = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts_cn,
+        );
+        burn(cpi_ctx_cn, amount)?;
+        msg!("burned {} CN tokens from redeemer", amount);
+
+        Ok(())
+    }
+
+    pub fn burn_pt_from_protocol(ctx: &Context<Redeem>, pt_amount: u64) -> Result<()> {
+        // prepare PDA signer seeds using helper
+        let bump_seed = [ctx.accounts.config.bump];
+        let config_seeds_with_bump = Config::get_seeds_with_bump(&bump_seed);
+        let signer_seeds = &[&config_seeds_with_bump[..]];
+
+        // burn the PT that was reserved for the option from the protocol's PT ATA
+        let cpi_accounts_pt = Burn {
+            mint: ctx.accounts.pt_mint.to_account_info(),
+            from: ctx.accounts.protocol_pt_ata.to_account_info(),
+            authority: ctx.accounts.config.to_account_info(),
+        };
+        let cpi_ctx_pt = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts_pt,
+            signer_seeds,
+        );
+        burn(cpi_ctx_pt, pt_amount)?;
+        msg!("burned {} PT tokens from protocol's ATA", pt_amount);
+
+        Ok(())
+    }
+
+    pub fn withdraw_sol(ctx: &mut Context<Redeem>, payout: u64) -> Result<()> {
+        // the treasury is owned by this program, so lamports are moved directly
+        // instead of through a system program transfer
+        ctx.accounts.treasury.sub_lamports(payout)?;
+        ctx.accounts.redeemer.add_lamports(payout)?;
+        msg!("transferred {} SOL from treasury to redeemer", payout);
+
+        // update treasury state to track total sol deposits.
+        // payouts above a NAV of 1 can exceed what was deposited, so clamp at zero.
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.total_deposited_sol = treasury.total_deposited_sol.saturating_sub(payout);
+
+        Ok(())
+    }
+
+    pub fn emit_redeemed(
+        ctx: &Context<Redeem>,
+        amount: u64,
+        pt_burned: u64,
+        payout: u64,
+    ) -> Result<()> {
+        emit_cpi!(Redeemed {
+            redeemer: ctx.accounts.redeemer.key(),
+            cn_burned: amount,
+            pt_burned,
+            payout,
+            nav: ctx.accounts.treasury.nav,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum RedeemError {
+    #[msg("redeem amount must be greater than zero.")]
+    ZeroAmount,
+    #[msg("account address mismatch.")]
+    AddressMismatch,
+    #[msg("protocol is locked.")]
+    ProtocolLocked,
+    #[msg("redemptions are currently locked.")]
+    RedemptionsLocked,
+    #[msg("calculation overflow")]
+    Overflow,
+    #[msg("insufficient CN token balance.")]
+    InsufficientCnBalance,
+    #[msg("insufficient PT tokens held by the protocol.")]
+    InsufficientPtBalance,
+    #[msg("treasury does not hold enough idle SOL for this redemption.")]
+    InsufficientTreasuryFunds,
+    #[msg("redeem amount is too small to pay out any SOL at the current NAV.")]
+    RedeemTooSmall,
+    #[msg("the option NFT account and its OptionData must be passed together.")]
+    IncompleteOptionAccounts,
+    #[msg("redeemer does not hold the option NFT.")]
+    OptionNotHeld,
+    #[msg("option NFT is frozen.")]
+    OptionFrozen,
+    #[msg("redeem amount exceeds the remaining amount on the option NFT.")]
+    InsufficientOptionAmount,
+    #[msg("this CN is set aside for an option or receipt, redeem it with its option NFT.")]
+    OptionRequired,
+}
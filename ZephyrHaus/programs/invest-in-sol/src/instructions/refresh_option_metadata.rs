@@ -0,0 +1,89 @@
+use anchor_lang::{prelude::*, solana_program};
+use anchor_spl::token_interface::Mint;
+use mpl_token_metadata::{
+    accounts::{MasterEdition, Metadata},
+    ID as MPL_TOKEN_METADATA_ID,
+};
+
+use crate::instructions::option_nft::OptionMetadataAccounts;
+use crate::state::{Config, OptionData};
+
+#[derive(Accounts)]
+pub struct RefreshOptionMetadata<'info> {
+    // anyone can refresh, e.g. a crank once an option expires
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub option_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [OptionData::SEED_PREFIX, option_mint.key().as_ref()],
+        bump = option_data.bump,
+        constraint = option_data.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub option_data: Account<'info, OptionData>,
+
+    /// CHECK: checked by Metaplex CPI. PDA derived from option_mint.
+    #[account(
+        mut,
+        address = Metadata::find_pda(&option_mint.key()).0 @ RefreshOptionMetadataError::AddressMismatch,
+    )]
+    pub option_metadata: UncheckedAccount<'info>,
+    /// CHECK: checked by Metaplex CPI. PDA derived from option_mint.
+    #[account(
+        address = MasterEdition::find_pda(&option_mint.key()).0 @ RefreshOptionMetadataError::AddressMismatch,
+    )]
+    pub option_master_edition: UncheckedAccount<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+    /// CHECK: address checked
+    #[account(address = MPL_TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Anchor will verify this is the sysvar instruction account
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+impl<'info> RefreshOptionMetadata<'info> {
+    pub fn handler(ctx: Context<RefreshOptionMetadata>) -> Result<()> {
+        let payer = ctx.accounts.payer.to_account_info();
+        let config = ctx.accounts.config.to_account_info();
+        let option_mint = ctx.accounts.option_mint.to_account_info();
+        let option_metadata = ctx.accounts.option_metadata.to_account_info();
+        let option_master_edition = ctx.accounts.option_master_edition.to_account_info();
+        let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let sysvar_instructions = ctx.accounts.sysvar_instructions.to_account_info();
+
+        OptionMetadataAccounts {
+            payer: &payer,
+            config: &config,
+            option_mint: &option_mint,
+            option_metadata: &option_metadata,
+            option_master_edition: &option_master_edition,
+            token_metadata_program: &token_metadata_program,
+            system_program: &system_program,
+            sysvar_instructions: &sysvar_instructions,
+        }
+        .update(
+            &ctx.accounts.option_data.key(),
+            &ctx.accounts.option_data,
+            Clock::get()?.unix_timestamp,
+            ctx.accounts.config.bump,
+        )
+    }
+}
+
+#[error_code]
+pub enum RefreshOptionMetadataError {
+    #[msg("account address mismatch.")]
+    AddressMismatch,
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+use crate::events::{RoleGranted, RoleRevoked};
+use crate::instructions::admin::require_authority;
+use crate::state::{Config, Role, Roles};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GrantRole<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+}
+
+impl<'info> GrantRole<'info> {
+    pub fn handler(ctx: Context<GrantRole>, member: Pubkey, role: Role) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+        require!(
+            ctx.accounts.roles.grant(member, role),
+            RolesError::TooManyMembers
+        );
+
+        emit_cpi!(RoleGranted { member, role });
+        msg!("granted {:?} to {}", role, member);
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+}
+
+impl<'info> RevokeRole<'info> {
+    pub fn handler(ctx: Context<RevokeRole>, member: Pubkey, role: Role) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+        require!(
+            ctx.accounts.roles.revoke(&member, role),
+            RolesError::RoleNotGranted
+        );
+
+        emit_cpi!(RoleRevoked { member, role });
+        msg!("revoked {:?} from {}", role, member);
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum RolesError {
+    #[msg("the roles account is full.")]
+    TooManyMembers,
+    #[msg("member does not hold this role.")]
+    RoleNotGranted,
+}
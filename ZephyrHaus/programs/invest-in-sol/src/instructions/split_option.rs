@@ -0,0 +1,323 @@
+use anchor_lang::{prelude::*, solana_program, solana_program::program_option::COption};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use mpl_token_metadata::{
+    accounts::{MasterEdition, Metadata},
+    ID as MPL_TOKEN_METADATA_ID,
+};
+
+use crate::events::{OptionIssued, OptionSplit};
+use crate::instructions::option_nft::{OptionMetadataAccounts, OptionNftAccounts};
+use crate::state::{Config, OptionData};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SplitOption<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    // holder's ATA for the option being split, proves they currently hold it
+    #[account(
+        associated_token::mint = option_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+        constraint = holder_option_ata.amount == 1 @ SplitOptionError::NotOptionHolder,
+        constraint = !holder_option_ata.is_frozen() @ SplitOptionError::OptionFrozen,
+        // without the config as delegate the NFT could no longer be frozen
+        constraint = holder_option_ata.delegate == COption::Some(config.key())
+            @ crate::ErrorCode::OptionNotDelegated,
+    )]
+    pub holder_option_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut, // needs mut to take a nonce and increment option_count
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    // --- option being split ---
+    #[account(mint::token_program = token_program)]
+    pub option_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [OptionData::SEED_PREFIX, option_mint.key().as_ref()],
+        bump = option_data.bump,
+        constraint = option_data.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub option_data: Box<Account<'info, OptionData>>,
+
+    /// CHECK: checked by Metaplex CPI. PDA derived from option_mint.
+    #[account(
+        mut,
+        address = Metadata::find_pda(&option_mint.key()).0 @ SplitOptionError::AddressMismatch,
+    )]
+    pub option_metadata: UncheckedAccount<'info>,
+    /// CHECK: checked by Metaplex CPI. PDA derived from option_mint.
+    #[account(
+        address = MasterEdition::find_pda(&option_mint.key()).0 @ SplitOptionError::AddressMismatch,
+    )]
+    pub option_master_edition: UncheckedAccount<'info>,
+
+    // --- new option NFT accounts ---
+    #[account(
+        init,
+        seeds = [
+            b"option_mint",
+            holder.key().as_ref(),
+            &config.deposit_nonce.to_le_bytes(),
+        ],
+        bump,
+        payer = holder,
+        mint::decimals = 0, // NFTs have 0 decimals
+        mint::authority = config, // PDA is mint authority
+        mint::freeze_authority = config, // PDA is freeze authority
+        token::token_program = token_program, // specify token program
+    )]
+    pub new_option_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed, // initialize ATA if it doesn't exist
+        payer = holder,
+        associated_token::mint = new_option_mint,
+        associated_token::authority = holder, // user owns the ATA
+        token::token_program = token_program, // specify token program
+    )]
+    pub holder_new_option_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: checked via CPI to token metadata program
+    #[account(
+        mut,
+        address = Metadata::find_pda(&new_option_mint.key()).0 @ SplitOptionError::AddressMismatch,
+    )]
+    pub new_option_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: checked in constraints and CPI
+    #[account(
+        mut,
+        address = MasterEdition::find_pda(&new_option_mint.key()).0 @ SplitOptionError::AddressMismatch,
+    )]
+    pub new_option_master_edition: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + OptionData::INIT_SPACE,
+        seeds = [OptionData::SEED_PREFIX, new_option_mint.key().as_ref()],
+        bump
+    )]
+    pub new_option_data: Box<Account<'info, OptionData>>,
+
+    // --- main collection accounts ---
+    /// CHECK: checked against config
+    #[account(
+        mut,
+        address = config.collection_mint @ SplitOptionError::AddressMismatch,
+    )]
+    pub main_collection_mint: UncheckedAccount<'info>,
+    /// CHECK: checked in constraints and CPI
+    #[account(
+        mut, // verification might change collection metadata account (e.g., size)
+        address = Metadata::find_pda(&main_collection_mint.key()).0 @ SplitOptionError::AddressMismatch,
+    )]
+    pub main_collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: checked in constraints and CPI
+    #[account(
+        mut,
+        address = MasterEdition::find_pda(&main_collection_mint.key()).0 @ SplitOptionError::AddressMismatch,
+    )]
+    pub main_collection_master_edition: UncheckedAccount<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// CHECK: address checked
+    #[account(address = MPL_TOKEN_METADATA_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Anchor will verify this is the sysvar instruction account
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SplitOption<'info> {
+    pub fn handler(mut ctx: Context<SplitOption>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.config.locked, SplitOptionError::ProtocolLocked);
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let option_data = &ctx.accounts.option_data;
+        require!(
+            !option_data.expired && !option_data.is_expired(current_timestamp),
+            SplitOptionError::OptionExpired
+        );
+        // both halves must keep a non-zero amount
+        require!(
+            amount > 0 && amount < option_data.amount,
+            SplitOptionError::InvalidSplitAmount
+        );
+
+        Self::move_amount(&mut ctx, amount)?;
+        Self::issue_option_nft(&ctx)?;
+        Self::update_option_metadata(&ctx, current_timestamp)?;
+
+        let nonce = ctx
+            .accounts
+            .config
+            .take_deposit_nonce()
+            .ok_or(SplitOptionError::Overflow)?;
+        // a new NFT is outstanding, total_option_amount is unchanged
+        let config = &mut ctx.accounts.config;
+        config.option_count = config
+            .option_count
+            .checked_add(1)
+            .ok_or(SplitOptionError::Overflow)?;
+
+        let new_option_data = &ctx.accounts.new_option_data;
+        emit_cpi!(OptionIssued {
+            owner: ctx.accounts.holder.key(),
+            option_mint: ctx.accounts.new_option_mint.key(),
+            option_data: new_option_data.key(),
+            nonce,
+            amount: new_option_data.amount,
+            expiration: new_option_data.expiration,
+            conversion_multiplier_bps: new_option_data.conversion_multiplier_bps,
+            timestamp: current_timestamp,
+        });
+        emit_cpi!(OptionSplit {
+            holder: ctx.accounts.holder.key(),
+            option_mint: ctx.accounts.option_mint.key(),
+            new_option_mint: ctx.accounts.new_option_mint.key(),
+            amount,
+            remaining_amount: ctx.accounts.option_data.amount,
+            expiration: new_option_data.expiration,
+            timestamp: current_timestamp,
+        });
+
+        msg!(
+            "split {} of option {} into {} ({} left)",
+            amount,
+            ctx.accounts.option_mint.key(),
+            ctx.accounts.new_option_mint.key(),
+            ctx.accounts.option_data.amount
+        );
+        Ok(())
+    }
+
+    /// takes `amount` off the split option and sets up the new option's data with it.
+    pub fn move_amount(ctx: &mut Context<SplitOption>, amount: u64) -> Result<()> {
+        // settle first so the yield earned by the full amount stays with the split option
+        let yield_per_unit = ctx.accounts.config.yield_per_unit;
+        let option_data = &mut ctx.accounts.option_data;
+        option_data.settle_yield(yield_per_unit)?;
+        option_data.amount = option_data
+            .amount
+            .checked_sub(amount)
+            .ok_or(SplitOptionError::Overflow)?;
+
+        **ctx.accounts.new_option_data = OptionData {
+            mint: ctx.accounts.new_option_mint.key(),
+            amount,
+            expiration: option_data.expiration,
+            expired: false,
+            conversion_multiplier_bps: option_data.conversion_multiplier_bps,
+            yield_checkpoint: yield_per_unit, // already settled up to now
+            accrued_yield: 0,
+            unlock_at: None,
+            rent_payer: ctx.accounts.holder.key(),
+            bump: ctx.bumps.new_option_data,
+            version: OptionData::VERSION,
+        };
+        Ok(())
+    }
+
+    pub fn issue_option_nft(ctx: &Context<SplitOption>) -> Result<()> {
+        // mint the new NFT, create its metadata and verify it with the collection
+        let holder = ctx.accounts.holder.to_account_info();
+        let config = ctx.accounts.config.to_account_info();
+        let new_option_mint = ctx.accounts.new_option_mint.to_account_info();
+        let holder_new_option_ata = ctx.accounts.holder_new_option_ata.to_account_info();
+        let new_option_metadata = ctx.accounts.new_option_metadata.to_account_info();
+        let new_option_master_edition = ctx.accounts.new_option_master_edition.to_account_info();
+        let collection_mint = ctx.accounts.main_collection_mint.to_account_info();
+        let collection_metadata = ctx.accounts.main_collection_metadata.to_account_info();
+        let collection_master_edition = ctx
+            .accounts
+            .main_collection_master_edition
+            .to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let sysvar_instructions = ctx.accounts.sysvar_instructions.to_account_info();
+
+        OptionNftAccounts {
+            payer: &holder,
+            owner: &holder,
+            config: &config,
+            option_mint: &new_option_mint,
+            owner_option_ata: &holder_new_option_ata,
+            option_metadata: &new_option_metadata,
+            option_master_edition: &new_option_master_edition,
+            collection_mint: &collection_mint,
+            collection_metadata: &collection_metadata,
+            collection_master_edition: &collection_master_edition,
+            token_program: &token_program,
+            token_metadata_program: &token_metadata_program,
+            system_program: &system_program,
+            sysvar_instructions: &sysvar_instructions,
+        }
+        .issue(ctx.accounts.config.bump, ctx.program_id)
+    }
+
+    /// rewrites the split option's metadata with its remaining amount, paid by the holder.
+    pub fn update_option_metadata(ctx: &Context<SplitOption>, current_timestamp: i64) -> Result<()> {
+        let holder = ctx.accounts.holder.to_account_info();
+        let config = ctx.accounts.config.to_account_info();
+        let option_mint = ctx.accounts.option_mint.to_account_info();
+        let option_metadata = ctx.accounts.option_metadata.to_account_info();
+        let option_master_edition = ctx.accounts.option_master_edition.to_account_info();
+        let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let sysvar_instructions = ctx.accounts.sysvar_instructions.to_account_info();
+
+        OptionMetadataAccounts {
+            payer: &holder,
+            config: &config,
+            option_mint: &option_mint,
+            option_metadata: &option_metadata,
+            option_master_edition: &option_master_edition,
+            token_metadata_program: &token_metadata_program,
+            system_program: &system_program,
+            sysvar_instructions: &sysvar_instructions,
+        }
+        .update(
+            &ctx.accounts.option_data.key(),
+            &ctx.accounts.option_data,
+            current_timestamp,
+            ctx.accounts.config.bump,
+        )
+    }
+}
+
+#[error_code]
+pub enum SplitOptionError {
+    #[msg("account address mismatch.")]
+    AddressMismatch,
+    #[msg("protocol is locked.")]
+    ProtocolLocked,
+    #[msg("signer does not hold the option NFT.")]
+    NotOptionHolder,
+    #[msg("option NFT is frozen.")]
+    OptionFrozen,
+    #[msg("option has expired.")]
+    OptionExpired,
+    #[msg("split amount must be greater than zero and less than the option's amount.")]
+    InvalidSplitAmount,
+    #[msg("calculation overflow")]
+    Overflow,
+}
@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface};
+
+use crate::events::ExpiredOptionsSynced;
+use crate::instructions::admin::require_authority;
+use crate::state::{Config, OptionData, Rounding};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SyncExpiredOptions<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut, // needs mut to decrement option_count and total_option_amount
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        address = config.pt_mint @ SyncError::AddressMismatch
+    )]
+    pub pt_mint: InterfaceAccount<'info, Mint>,
+
+    // protocol's PT holding ATA (source of the PT being burned)
+    #[account(
+        mut,
+        associated_token::mint = pt_mint,
+        associated_token::authority = config, // owned by config PDA
+        associated_token::token_program = token_program,
+    )]
+    pub protocol_pt_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // programs
+    pub token_program: Interface<'info, TokenInterface>,
+    // remaining accounts: writable OptionData PDAs to sync
+}
+
+impl<'info> SyncExpiredOptions<'info> {
+    pub fn handler(ctx: Context<'_, '_, 'info, 'info, SyncExpiredOptions<'info>>) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let mut amount_to_burn: u64 = 0;
+        let mut synced_count: u64 = 0;
+
+        for option_data_info in ctx.remaining_accounts.iter() {
+            require!(
+                option_data_info.is_writable,
+                SyncError::OptionDataNotWritable
+            );
+            // checks the account is owned by this program and is an OptionData
+            let mut option_data: Account<'info, OptionData> = Account::try_from(option_data_info)?;
+            require!(
+                option_data.is_current(),
+                crate::ErrorCode::AccountVersionOutdated
+            );
+
+            if option_data.expired || !option_data.is_expired(current_timestamp) {
+                msg!(
+                    "option {} not expired or already synced, skipping",
+                    option_data.mint
+                );
+                continue;
+            }
+            // fully converted options were already removed from the config totals
+            if option_data.amount == 0 {
+                msg!("option {} has no amount left, skipping", option_data.mint);
+                continue;
+            }
+
+            let amount = option_data.amount;
+            // the PT backing the option is scaled by its conversion multiplier
+            let pt_backing = option_data.pt_for_cn(amount, Rounding::Down)?;
+            amount_to_burn = amount_to_burn
+                .checked_add(pt_backing)
+                .ok_or(SyncError::Overflow)?;
+
+            Self::decrement_config_totals(&mut ctx.accounts.config, amount)?;
+
+            // lock in the yield earned while outstanding; the holder can still claim it
+            option_data.settle_yield(ctx.accounts.config.yield_per_unit)?;
+
+            // mark the option as spent so its NFT can no longer convert
+            option_data.amount = 0;
+            option_data.expired = true;
+            option_data.exit(ctx.program_id)?;
+
+            synced_count = synced_count.checked_add(1).ok_or(SyncError::Overflow)?;
+            msg!(
+                "synced expired option {} (amount: {})",
+                option_data.mint,
+                amount
+            );
+        }
+
+        if amount_to_burn > 0 {
+            Self::burn_expired_pt(&ctx, amount_to_burn)?;
+        }
+
+        emit_cpi!(ExpiredOptionsSynced {
+            synced_count,
+            pt_burned: amount_to_burn,
+            total_option_amount: ctx.accounts.config.total_option_amount,
+            timestamp: current_timestamp,
+        });
+        msg!(
+            "synced {} expired options, burned {} PT tokens",
+            synced_count,
+            amount_to_burn
+        );
+        Ok(())
+    }
+
+    pub fn decrement_config_totals(config: &mut Account<'info, Config>, amount: u64) -> Result<()> {
+        // decrement the option count in the config account
+        // only if it's greater than 0 to prevent underflow
+        if config.option_count > 0 {
+            config.option_count = config
+                .option_count
+                .checked_sub(1)
+                .ok_or(SyncError::Overflow)?;
+        } else {
+            msg!("warning: config option_count is already 0, not decrementing");
+        }
+
+        require!(
+            config.total_option_amount >= amount,
+            SyncError::InsufficientTotalOptionAmount
+        );
+        config.total_option_amount = config
+            .total_option_amount
+            .checked_sub(amount)
+            .ok_or(SyncError::Overflow)?;
+
+        Ok(())
+    }
+
+    pub fn burn_expired_pt(
+        ctx: &Context<'_, '_, 'info, 'info, SyncExpiredOptions<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        // prepare PDA signer seeds using helper
+        let bump_seed = [ctx.accounts.config.bump];
+        let config_seeds_with_bump = Config::get_seeds_with_bump(&bump_seed);
+        let signer_seeds = &[&config_seeds_with_bump[..]];
+
+        // burn the PT that was backing the expired options
+        let cpi_accounts_pt = Burn {
+            mint: ctx.accounts.pt_mint.to_account_info(),
+            from: ctx.accounts.protocol_pt_ata.to_account_info(),
+            authority: ctx.accounts.config.to_account_info(),
+        };
+        let cpi_ctx_pt = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts_pt,
+            signer_seeds,
+        );
+        burn(cpi_ctx_pt, amount)?;
+        msg!("burned {} PT tokens from protocol's ATA", amount);
+
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum SyncError {
+    #[msg("account address mismatch.")]
+    AddressMismatch,
+    #[msg("calculation overflow")]
+    Overflow,
+    #[msg("OptionData accounts passed to sync must be writable.")]
+    OptionDataNotWritable,
+    #[msg("expired amount exceeds total option amount tracked in config.")]
+    InsufficientTotalOptionAmount,
+}
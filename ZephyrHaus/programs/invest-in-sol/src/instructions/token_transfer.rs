@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    get_mint_extension_data,
+    spl_token_2022::{
+        extension::transfer_fee::TransferFeeConfig,
+        onchain::{invoke_transfer_checked, invoke_transfer_checked_with_fee},
+    },
+    TransferChecked,
+};
+
+use crate::ErrorCode;
+
+/// the transfer fee a Token-2022 mint withholds from a transfer of `amount` in the
+/// current epoch. 0 for SPL Token mints and mints without the transfer fee extension.
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let Ok(fee_config) = get_mint_extension_data::<TransferFeeConfig>(mint) else {
+        return Ok(0);
+    };
+    fee_config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// transfer_checked for SPL Token and Token-2022 mints. returns the transfer fee withheld
+/// from what `to` receives, so callers can account for the amount that actually arrived.
+///
+/// a transfer fee is passed to the token program explicitly (TransferCheckedWithFee), so
+/// the transfer fails rather than withholding more than was accounted for. the extra
+/// accounts of a transfer hook are taken from the context's remaining accounts.
+pub fn transfer_checked<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<u64> {
+    let has_fee_config = get_mint_extension_data::<TransferFeeConfig>(&ctx.accounts.mint).is_ok();
+    if !has_fee_config {
+        invoke_transfer_checked(
+            ctx.program.key,
+            ctx.accounts.from,
+            ctx.accounts.mint,
+            ctx.accounts.to,
+            ctx.accounts.authority,
+            ctx.remaining_accounts.as_slice(),
+            amount,
+            decimals,
+            ctx.signer_seeds,
+        )?;
+        return Ok(0);
+    }
+
+    let fee = transfer_fee(&ctx.accounts.mint, amount)?;
+    invoke_transfer_checked_with_fee(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        ctx.remaining_accounts.as_slice(),
+        amount,
+        decimals,
+        fee,
+        ctx.signer_seeds,
+    )?;
+    Ok(fee)
+}
@@ -0,0 +1,220 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::instructions::admin::require_role;
+use crate::state::{Config, Role, Roles, Treasury, TreasuryAsset};
+
+#[derive(Accounts)]
+pub struct AddTreasuryAsset<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    // the SPL mint being whitelisted
+    #[account(mint::token_program = token_program)]
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [TreasuryAsset::SEED_PREFIX, asset_mint.key().as_ref()],
+        bump,
+        space = 8 + TreasuryAsset::INIT_SPACE,
+    )]
+    pub treasury_asset: Account<'info, TreasuryAsset>,
+
+    // treasury-owned vault holding deposits of this asset
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = asset_mint,
+        associated_token::authority = treasury, // treasury PDA owns the asset vault
+        token::token_program = token_program,
+    )]
+    pub treasury_asset_vault: InterfaceAccount<'info, TokenAccount>,
+
+    // programs
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddTreasuryAsset<'info> {
+    pub fn handler(
+        ctx: Context<AddTreasuryAsset>,
+        price_authority: Pubkey,
+        price_lamports: u64,
+        max_price_age: i64,
+    ) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.roles,
+            &ctx.accounts.authority.key(),
+            &[Role::TreasuryManager],
+        )?;
+        require!(price_lamports > 0, TreasuryAssetError::InvalidPrice);
+        require!(max_price_age > 0, TreasuryAssetError::InvalidMaxPriceAge);
+
+        *ctx.accounts.treasury_asset = TreasuryAsset {
+            mint: ctx.accounts.asset_mint.key(),
+            vault: ctx.accounts.treasury_asset_vault.key(),
+            price_authority,
+            price_lamports,
+            price_updated_at: Clock::get()?.unix_timestamp,
+            max_price_age,
+            total_deposited: 0,
+            enabled: true,
+            bump: ctx.bumps.treasury_asset,
+            decimals: ctx.accounts.asset_mint.decimals,
+            value_lamports: 0,
+        };
+
+        msg!(
+            "whitelisted treasury asset {} (vault: {}, price: {} lamports)",
+            ctx.accounts.asset_mint.key(),
+            ctx.accounts.treasury_asset_vault.key(),
+            price_lamports
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreasuryAsset<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        mut,
+        seeds = [TreasuryAsset::SEED_PREFIX, treasury_asset.mint.as_ref()],
+        bump = treasury_asset.bump,
+    )]
+    pub treasury_asset: Account<'info, TreasuryAsset>,
+}
+
+impl<'info> UpdateTreasuryAsset<'info> {
+    pub fn handler(
+        ctx: Context<UpdateTreasuryAsset>,
+        enabled: Option<bool>,
+        price_authority: Option<Pubkey>,
+        max_price_age: Option<i64>,
+    ) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.roles,
+            &ctx.accounts.authority.key(),
+            &[Role::TreasuryManager],
+        )?;
+
+        let treasury_asset = &mut ctx.accounts.treasury_asset;
+
+        if let Some(val) = enabled {
+            treasury_asset.enabled = val;
+            msg!("asset {} enabled updated to: {}", treasury_asset.mint, val);
+        }
+        if let Some(val) = price_authority {
+            treasury_asset.price_authority = val;
+            msg!(
+                "asset {} price authority updated to: {}",
+                treasury_asset.mint,
+                val
+            );
+        }
+        if let Some(val) = max_price_age {
+            require!(val > 0, TreasuryAssetError::InvalidMaxPriceAge);
+            treasury_asset.max_price_age = val;
+            msg!(
+                "asset {} max price age updated to: {}",
+                treasury_asset.mint,
+                val
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetAssetPrice<'info> {
+    pub price_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [TreasuryAsset::SEED_PREFIX, treasury_asset.mint.as_ref()],
+        bump = treasury_asset.bump,
+        has_one = price_authority @ TreasuryAssetError::PriceAuthorityMismatch,
+    )]
+    pub treasury_asset: Account<'info, TreasuryAsset>,
+
+    // the asset's holdings are re-valued at the new price
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+impl<'info> SetAssetPrice<'info> {
+    pub fn handler(ctx: Context<SetAssetPrice>, price_lamports: u64) -> Result<()> {
+        require!(price_lamports > 0, TreasuryAssetError::InvalidPrice);
+
+        let treasury_asset = &mut ctx.accounts.treasury_asset;
+        treasury_asset.price_lamports = price_lamports;
+        treasury_asset.price_updated_at = Clock::get()?.unix_timestamp;
+        treasury_asset.revalue(&mut ctx.accounts.treasury)?;
+
+        msg!(
+            "asset {} price updated to: {} lamports (holdings valued at {} lamports)",
+            treasury_asset.mint,
+            price_lamports,
+            treasury_asset.value_lamports
+        );
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum TreasuryAssetError {
+    #[msg("asset price must be greater than zero.")]
+    InvalidPrice,
+    #[msg("max price age must be greater than zero.")]
+    InvalidMaxPriceAge,
+    #[msg("signer is not the price authority for this asset.")]
+    PriceAuthorityMismatch,
+}
@@ -0,0 +1,340 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::events::{
+    TreasuryWithdrawalCancelled, TreasuryWithdrawalExecuted, TreasuryWithdrawalQueued,
+    WithdrawalDelayUpdated,
+};
+use crate::instructions::admin::{require_authority, require_role};
+use crate::state::{
+    Config, Role, Roles, Rounding, Treasury, TreasuryWithdrawal, MAX_WITHDRAWAL_DELAY,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct QueueTreasuryWithdrawal<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    // only one withdrawal can be pending, init fails while another is queued
+    #[account(
+        init,
+        payer = authority,
+        seeds = [TreasuryWithdrawal::SEED_PREFIX],
+        bump,
+        space = 8 + TreasuryWithdrawal::INIT_SPACE,
+    )]
+    pub treasury_withdrawal: Account<'info, TreasuryWithdrawal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> QueueTreasuryWithdrawal<'info> {
+    pub fn handler(
+        ctx: Context<QueueTreasuryWithdrawal>,
+        recipient: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.roles,
+            &ctx.accounts.authority.key(),
+            &[Role::TreasuryManager],
+        )?;
+        require!(amount > 0, TreasuryWithdrawalError::ZeroAmount);
+
+        // fail early, the limit is checked again when the withdrawal executes
+        let idle_sol = Treasury::idle_sol(&ctx.accounts.treasury.to_account_info())?;
+        let withdrawable = ctx.accounts.treasury.withdrawable_sol(idle_sol);
+        require!(
+            amount <= withdrawable,
+            TreasuryWithdrawalError::ExceedsWithdrawableSol
+        );
+
+        let queued_at = Clock::get()?.unix_timestamp;
+        let executable_at = queued_at
+            .checked_add(ctx.accounts.treasury.withdrawal_delay)
+            .ok_or(TreasuryWithdrawalError::Overflow)?;
+
+        *ctx.accounts.treasury_withdrawal = TreasuryWithdrawal {
+            recipient,
+            amount,
+            queued_at,
+            executable_at,
+            bump: ctx.bumps.treasury_withdrawal,
+            queued_by: ctx.accounts.authority.key(),
+        };
+
+        emit_cpi!(TreasuryWithdrawalQueued {
+            authority: ctx.accounts.authority.key(),
+            recipient,
+            amount,
+            executable_at,
+            timestamp: queued_at,
+        });
+        msg!(
+            "queued treasury withdrawal of {} lamports to {}, executable at {}",
+            amount,
+            recipient,
+            executable_at
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteTreasuryWithdrawal<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    // treasury PDA holds the SOL being withdrawn
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    // closed once executed, rent goes back to whoever queued it
+    #[account(
+        mut,
+        seeds = [TreasuryWithdrawal::SEED_PREFIX],
+        bump = treasury_withdrawal.bump,
+        close = queued_by,
+    )]
+    pub treasury_withdrawal: Account<'info, TreasuryWithdrawal>,
+
+    /// CHECK: only receives the rent, checked against treasury_withdrawal.queued_by
+    #[account(
+        mut,
+        address = treasury_withdrawal.queued_by @ TreasuryWithdrawalError::QueuedByMismatch,
+    )]
+    pub queued_by: UncheckedAccount<'info>,
+
+    /// CHECK: only receives lamports, checked against treasury_withdrawal.recipient
+    #[account(
+        mut,
+        address = treasury_withdrawal.recipient @ TreasuryWithdrawalError::RecipientMismatch,
+    )]
+    pub recipient: UncheckedAccount<'info>,
+
+    // CN mint (checked against config), its supply values the NAV after the withdrawal
+    #[account(address = config.cn_mint @ TreasuryWithdrawalError::AddressMismatch)]
+    pub cn_mint: InterfaceAccount<'info, Mint>,
+}
+
+impl<'info> ExecuteTreasuryWithdrawal<'info> {
+    pub fn handler(ctx: Context<ExecuteTreasuryWithdrawal>) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.roles,
+            &ctx.accounts.authority.key(),
+            &[Role::TreasuryManager],
+        )?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts
+                .treasury_withdrawal
+                .is_executable(current_timestamp),
+            TreasuryWithdrawalError::WithdrawalTimelocked
+        );
+
+        // the treasury's idle SOL may have dropped since the withdrawal was queued
+        let amount = ctx.accounts.treasury_withdrawal.amount;
+        let idle_sol = Treasury::idle_sol(&ctx.accounts.treasury.to_account_info())?;
+        let withdrawable = ctx.accounts.treasury.withdrawable_sol(idle_sol);
+        require!(
+            amount <= withdrawable,
+            TreasuryWithdrawalError::ExceedsWithdrawableSol
+        );
+
+        // the treasury is program-owned, so lamports are moved directly
+        ctx.accounts.treasury.sub_lamports(amount)?;
+        ctx.accounts.recipient.add_lamports(amount)?;
+
+        // only surplus above the deposits' backing is withdrawn, so total_deposited_sol
+        // is left as it is
+        let treasury = &mut ctx.accounts.treasury;
+        let nav = treasury.calculate_nav(
+            idle_sol
+                .checked_sub(amount)
+                .ok_or(TreasuryWithdrawalError::Overflow)?,
+            ctx.accounts.cn_mint.supply,
+            Rounding::Down,
+        )?;
+        treasury.update_nav(nav)?;
+
+        emit_cpi!(TreasuryWithdrawalExecuted {
+            authority: ctx.accounts.authority.key(),
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            nav,
+            timestamp: current_timestamp,
+        });
+        msg!(
+            "withdrew {} lamports from the treasury to {}",
+            amount,
+            ctx.accounts.recipient.key()
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelTreasuryWithdrawal<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Roles::SEED_PREFIX],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    // closed once cancelled, rent goes back to whoever queued it
+    #[account(
+        mut,
+        seeds = [TreasuryWithdrawal::SEED_PREFIX],
+        bump = treasury_withdrawal.bump,
+        close = queued_by,
+    )]
+    pub treasury_withdrawal: Account<'info, TreasuryWithdrawal>,
+
+    /// CHECK: only receives the rent, checked against treasury_withdrawal.queued_by
+    #[account(
+        mut,
+        address = treasury_withdrawal.queued_by @ TreasuryWithdrawalError::QueuedByMismatch,
+    )]
+    pub queued_by: UncheckedAccount<'info>,
+}
+
+impl<'info> CancelTreasuryWithdrawal<'info> {
+    pub fn handler(ctx: Context<CancelTreasuryWithdrawal>) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.roles,
+            &ctx.accounts.authority.key(),
+            &[Role::TreasuryManager],
+        )?;
+
+        let withdrawal = &ctx.accounts.treasury_withdrawal;
+        emit_cpi!(TreasuryWithdrawalCancelled {
+            authority: ctx.accounts.authority.key(),
+            recipient: withdrawal.recipient,
+            amount: withdrawal.amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!(
+            "cancelled treasury withdrawal of {} lamports to {}",
+            withdrawal.amount,
+            withdrawal.recipient
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateWithdrawalDelay<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX],
+        bump = treasury.treasury_bump,
+        constraint = treasury.is_current() @ crate::ErrorCode::AccountVersionOutdated,
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+impl<'info> UpdateWithdrawalDelay<'info> {
+    pub fn handler(ctx: Context<UpdateWithdrawalDelay>, withdrawal_delay: i64) -> Result<()> {
+        require_authority(&ctx.accounts.config, &ctx.accounts.authority.key())?;
+        require!(
+            (0..=MAX_WITHDRAWAL_DELAY).contains(&withdrawal_delay),
+            TreasuryWithdrawalError::InvalidWithdrawalDelay
+        );
+
+        // only applies to withdrawals queued from now on
+        ctx.accounts.treasury.withdrawal_delay = withdrawal_delay;
+
+        emit_cpi!(WithdrawalDelayUpdated {
+            authority: ctx.accounts.authority.key(),
+            withdrawal_delay,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("withdrawal delay updated to: {} seconds", withdrawal_delay);
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum TreasuryWithdrawalError {
+    #[msg("account address mismatch.")]
+    AddressMismatch,
+    #[msg("withdrawal amount must be greater than zero.")]
+    ZeroAmount,
+    #[msg("withdrawal would take the treasury below the SOL backing outstanding CN and PT.")]
+    ExceedsWithdrawableSol,
+    #[msg("withdrawal delay has not passed yet.")]
+    WithdrawalTimelocked,
+    #[msg("recipient does not match the queued withdrawal.")]
+    RecipientMismatch,
+    #[msg("withdrawal delay must be between 0 and 30 days.")]
+    InvalidWithdrawalDelay,
+    #[msg("calculation overflow")]
+    Overflow,
+    #[msg("account does not match the one that queued the withdrawal.")]
+    QueuedByMismatch,
+}
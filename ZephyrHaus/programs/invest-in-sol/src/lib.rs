@@ -2,6 +2,8 @@
 mod instructions;
 use instructions::*;
 pub mod state;
+use state::{AllowlistProof, Role};
+pub mod events;
 
 use anchor_lang::prelude::*;
 
@@ -19,48 +21,443 @@ pub mod invest_in_sol {
     }
     /// deposits SOL, mints CN tokens to the depositor,
     /// and mints PT tokens to the protocol treasury.
+    /// the protocol fee (config.fee, in bps) is taken from the deposit into the fee vault.
     /// NFT minting is handled separately in the initialize_option instruction.
-    pub fn deposit(mut ctx: Context<Deposit>, amount: u64, option_duration: u32) -> Result<()> {
+    /// `option_duration` selects the duration tier (seconds) the option is issued in.
+    /// while the allowlist is on, `allowlist_proof` must prove the depositor is on it.
+    pub fn deposit(
+        mut ctx: Context<Deposit>,
+        amount: u64,
+        _option_duration: u32,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
         Deposit::assert_protocol_state(&ctx)?;
-        Deposit::deposit_sol(&mut ctx, amount)?;
-        let tokens_to_mint = Deposit::calculate_tokens_to_mint(&ctx, amount)?;
-        Deposit::set_deposit_receipt(&mut ctx, tokens_to_mint, option_duration)?;
+        // nav is calculated before the deposit lands in the treasury
+        let net_amount = Deposit::charge_deposit_fee(&mut ctx, amount)?;
+        Deposit::apply_deposit_caps(&mut ctx, net_amount)?;
+        Deposit::apply_allowlist(&mut ctx, net_amount, allowlist_proof)?;
+        let tokens_to_mint = Deposit::calculate_tokens_to_mint(&mut ctx, net_amount)?;
+        Deposit::deposit_sol(&mut ctx, net_amount)?;
+        Deposit::set_deposit_receipt(&mut ctx, tokens_to_mint, net_amount)?;
         Deposit::mint_cn_to_depositor(&ctx, tokens_to_mint)?;
-        Deposit::mint_pt_to_protocol(&ctx, tokens_to_mint)?;
+        let pt_minted = Deposit::mint_pt_to_protocol(&ctx, tokens_to_mint)?;
+        Deposit::emit_deposited(&ctx, net_amount, pt_minted)?;
+        Ok(())
+    }
+    /// deposits a whitelisted SPL asset (e.g. USDC) into the treasury's vault for that asset,
+    /// valued in SOL at the asset's current price. mints CN to the depositor and PT to the
+    /// protocol the same way as a SOL deposit, and issues a deposit receipt.
+    /// the protocol fee is taken in the deposited asset into the fee vault's ATA.
+    /// gated by the allowlist the same way as deposit.
+    pub fn deposit_spl<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, DepositSpl<'info>>,
+        amount: u64,
+        _option_duration: u32,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
+        DepositSpl::assert_protocol_state(&ctx)?;
+        // nav is calculated before the deposit lands in the treasury
+        let net_amount = DepositSpl::charge_deposit_fee(&ctx, amount)?;
+        let (tokens_to_mint, value_lamports) =
+            DepositSpl::calculate_tokens_to_mint(&mut ctx, net_amount)?;
+        DepositSpl::apply_deposit_caps(&mut ctx, value_lamports)?;
+        DepositSpl::apply_allowlist(&mut ctx, value_lamports, allowlist_proof)?;
+        let received = DepositSpl::deposit_asset(&mut ctx, net_amount, value_lamports)?;
+        DepositSpl::set_deposit_receipt(&mut ctx, tokens_to_mint, value_lamports, received)?;
+        DepositSpl::mint_cn_to_depositor(&ctx, tokens_to_mint)?;
+        let pt_minted = DepositSpl::mint_pt_to_protocol(&ctx, tokens_to_mint)?;
+        DepositSpl::emit_deposited(&ctx, net_amount, value_lamports, pt_minted)?;
+        Ok(())
+    }
+    /// deposits SOL, mints CN/PT and issues the option NFT + OptionData in a single
+    /// instruction, so no deposit receipt is ever left waiting to be claimed.
+    /// the protocol fee is charged the same way as in deposit.
+    /// gated by the allowlist the same way as deposit.
+    pub fn deposit_with_option(
+        mut ctx: Context<DepositWithOption>,
+        amount: u64,
+        _option_duration: u32,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
+        DepositWithOption::assert_protocol_state(&ctx)?;
+        // nav is calculated before the deposit lands in the treasury
+        let net_amount = DepositWithOption::charge_deposit_fee(&mut ctx, amount)?;
+        DepositWithOption::apply_deposit_caps(&mut ctx, net_amount)?;
+        DepositWithOption::apply_allowlist(&mut ctx, net_amount, allowlist_proof)?;
+        let tokens_to_mint = DepositWithOption::calculate_tokens_to_mint(&mut ctx, net_amount)?;
+        DepositWithOption::deposit_sol(&mut ctx, net_amount)?;
+        DepositWithOption::mint_cn_to_depositor(&ctx, tokens_to_mint)?;
+        let pt_minted = DepositWithOption::mint_pt_to_protocol(&ctx, tokens_to_mint)?;
+        DepositWithOption::issue_option_nft(&ctx)?;
+        let nonce = DepositWithOption::set_option_data(&mut ctx, tokens_to_mint)?;
+        DepositWithOption::emit_deposited(&ctx, net_amount, pt_minted, nonce)?;
         Ok(())
     }
     /// initializes the option NFT, metadata, master edition, and OptionData PDA.
     /// this is intended to be called separately before or after deposit.
+    /// the claimed deposit receipt is closed and its rent returned to the depositor.
     /// if the main collection doesn't exist, it will be created.
-    pub fn initialize_option(ctx: Context<InitializeOption>) -> Result<()> {
+    /// `nonce` is the deposit nonce stored on the receipt being claimed.
+    pub fn initialize_option(ctx: Context<InitializeOption>, _nonce: u64) -> Result<()> {
         // Use the new process_initialize_option function that handles collection creation
         InitializeOption::process_initialize_option(ctx)
     }
 
+    /// cancels a deposit whose option was never issued: burns the CN it minted and the
+    /// matching PT, refunds the lower of the deposit's SOL value and its CN at the current
+    /// NAV in what was deposited, and closes the receipt. the deposit comes back off the
+    /// deposit caps. `nonce` is the deposit nonce stored on the receipt.
+    pub fn cancel_deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelDeposit<'info>>,
+        nonce: u64,
+    ) -> Result<()> {
+        CancelDeposit::handler(ctx, nonce)
+    }
+
+    /// closes a deposit receipt whose option was issued before initialize_option closed
+    /// receipts itself, returning its rent to the depositor. callable by anyone.
+    pub fn close_deposit_receipt(ctx: Context<CloseDepositReceipt>, nonce: u64) -> Result<()> {
+        CloseDepositReceipt::handler(ctx, nonce)
+    }
+
+    /// expires a deposit receipt whose claim deadline has passed without the option being
+    /// issued. the option is forfeited: the PT backing it is burned and the receipt closed,
+    /// its rent going back to the depositor, who keeps the CN. callable by anyone.
+    pub fn expire_deposit_receipt(ctx: Context<ExpireDepositReceipt>, nonce: u64) -> Result<()> {
+        ExpireDepositReceipt::handler(ctx, nonce)
+    }
+
     /// burns user's CN tokens and optionally the NFT option, mints PT tokens to the user.
     /// If amount_to_convert_ui equals the full amount in OptionData, burns the NFT and sets amount to 0.
     /// If amount_to_convert_ui is less than the full amount, decrements OptionData.amount and keeps the NFT.
-    pub fn convert(ctx: Context<Convert>, amount_to_convert_ui: u64) -> Result<()> {
+    pub fn convert<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Convert<'info>>,
+        amount_to_convert_ui: u64,
+    ) -> Result<()> {
         Convert::handler(ctx, amount_to_convert_ui)
     }
 
-    /// updates the protocol locks (global, deposit, convert).
-    /// only callable by the config authority.
+    /// rewrites an option NFT's metadata (name and URI) with its remaining amount,
+    /// expiration and status (active/expired/exhausted). permissionless, so a crank
+    /// can refresh options once they expire. partial conversions refresh it themselves.
+    pub fn refresh_option_metadata(ctx: Context<RefreshOptionMetadata>) -> Result<()> {
+        RefreshOptionMetadata::handler(ctx)
+    }
+
+    /// moves `amount` of a held option onto a new option NFT with the same expiration
+    /// and multiplier. the split option keeps its settled yield and the rest of its amount.
+    pub fn split_option(ctx: Context<SplitOption>, amount: u64) -> Result<()> {
+        SplitOption::handler(ctx, amount)
+    }
+
+    /// burns `merged_option_mint` into `option_mint`, moving its amount and settled yield.
+    /// both options must be held by the signer and share an expiration and multiplier.
+    pub fn merge_options(ctx: Context<MergeOptions>) -> Result<()> {
+        MergeOptions::handler(ctx)
+    }
+
+    /// freezes the token account holding an option NFT, through Token Metadata with the
+    /// config PDA as its delegate. the authority, a pause guardian or a metadata manager
+    /// can freeze any option, with `unlock_at` letting anyone thaw it after a cliff.
+    /// anyone can freeze an expired option.
+    pub fn freeze_option(ctx: Context<FreezeOption>, unlock_at: Option<i64>) -> Result<()> {
+        FreezeOption::handler(ctx, unlock_at)
+    }
+
+    /// thaws a frozen option NFT. callable by the authority or a metadata manager, or by
+    /// anyone once the option's `unlock_at` has passed. expired options stay frozen.
+    pub fn thaw_option(ctx: Context<ThawOption>) -> Result<()> {
+        ThawOption::handler(ctx)
+    }
+
+    /// burns the redeemer's CN tokens, then pays the redeemer SOL from the treasury
+    /// at the current NAV. when the redeemer's option is passed its amount goes down
+    /// too and the PT reserved for it, scaled by its multiplier, is burned.
+    /// fails if the treasury does not hold enough idle SOL to cover the payout.
+    pub fn redeem(mut ctx: Context<Redeem>, amount: u64) -> Result<()> {
+        Redeem::assert_protocol_state(&ctx, amount)?;
+        let payout = Redeem::calculate_payout(&mut ctx, amount)?;
+        Redeem::release_backing(&mut ctx, amount)?;
+        let pt_burned = Redeem::release_option(&mut ctx, amount)?;
+        Redeem::burn_cn_from_redeemer(&ctx, amount)?;
+        if pt_burned > 0 {
+            Redeem::burn_pt_from_protocol(&ctx, pt_burned)?;
+        }
+        Redeem::withdraw_sol(&mut ctx, payout)?;
+        Redeem::emit_redeemed(&ctx, amount, pt_burned, payout)?;
+        Ok(())
+    }
+
+    /// updates the protocol locks (global, deposit, convert, redeem).
+    /// callable by the config authority, or a pause guardian setting locks to true.
     pub fn update_locks(
         ctx: Context<UpdateLocks>,
         locked: Option<bool>,
         deposit_locked: Option<bool>,
         convert_locked: Option<bool>,
+        redeem_locked: Option<bool>,
+    ) -> Result<()> {
+        UpdateLocks::handler(ctx, locked, deposit_locked, convert_locked, redeem_locked)
+    }
+
+    /// updates the protocol fees (bps) and the fee recipient.
+    /// a fee of 0 disables it. callable by the config authority or a fee manager.
+    pub fn update_fees(
+        ctx: Context<UpdateFees>,
+        fee: Option<u16>,
+        conversion_fee: Option<u16>,
+        fee_recipient: Option<Pubkey>,
+    ) -> Result<()> {
+        UpdateFees::handler(ctx, fee, conversion_fee, fee_recipient)
+    }
+
+    /// updates the deposit caps (lamports): the total SOL value held from deposits,
+    /// the lifetime deposits of a single wallet and the deposits per epoch.
+    /// a cap of 0 removes it. only callable by the config authority.
+    pub fn update_deposit_caps(
+        ctx: Context<UpdateDepositCaps>,
+        max_total_deposited_sol: Option<u64>,
+        max_wallet_deposit: Option<u64>,
+        max_epoch_deposit: Option<u64>,
+    ) -> Result<()> {
+        UpdateDepositCaps::handler(
+            ctx,
+            max_total_deposited_sol,
+            max_wallet_deposit,
+            max_epoch_deposit,
+        )
+    }
+
+    /// sets how long (seconds, at most 90 days) depositors have to claim the option for
+    /// a deposit receipt. only applies to receipts issued afterwards.
+    /// only callable by the config authority.
+    pub fn update_claim_window(ctx: Context<UpdateClaimWindow>, claim_window: i64) -> Result<()> {
+        UpdateClaimWindow::handler(ctx, claim_window)
+    }
+
+    /// sets the merkle root of the deposit allowlist. while set, deposits need a proof
+    /// against it; None opens deposits to anyone again. only callable by the config authority.
+    pub fn set_allowlist_root(
+        ctx: Context<SetAllowlistRoot>,
+        allowlist_root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        SetAllowlistRoot::handler(ctx, allowlist_root)
+    }
+
+    /// grants `role` to `member` in the roles account. only callable by the config authority.
+    pub fn grant_role(ctx: Context<GrantRole>, member: Pubkey, role: Role) -> Result<()> {
+        GrantRole::handler(ctx, member, role)
+    }
+
+    /// revokes `role` from `member`. only callable by the config authority.
+    pub fn revoke_role(ctx: Context<RevokeRole>, member: Pubkey, role: Role) -> Result<()> {
+        RevokeRole::handler(ctx, member, role)
+    }
+
+    /// sends the SOL collected in the fee vault to the configured fee recipient.
+    /// callable by the config authority or a fee manager.
+    pub fn claim_fees(ctx: Context<ClaimFees>) -> Result<()> {
+        ClaimFees::handler(ctx)
+    }
+
+    /// sends the fees collected in an SPL asset to the fee recipient's ATA.
+    /// callable by the config authority or a fee manager.
+    pub fn claim_asset_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimAssetFees<'info>>,
+    ) -> Result<()> {
+        ClaimAssetFees::handler(ctx)
+    }
+
+    /// proposes a new authority for the config and treasury, or cancels a pending proposal with None.
+    /// the transfer only takes effect once the proposed authority calls accept_authority.
+    /// only callable by the config authority.
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        new_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        ProposeAuthority::handler(ctx, new_authority)
+    }
+
+    /// completes an authority transfer. must be signed by the pending authority.
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        AcceptAuthority::handler(ctx)
+    }
+
+    /// permanently removes the config and treasury authority.
+    /// only callable by the config authority.
+    pub fn renounce_authority(ctx: Context<RenounceAuthority>) -> Result<()> {
+        RenounceAuthority::handler(ctx)
+    }
+
+    /// burns the PT backing expired options and removes them from the config totals.
+    /// OptionData accounts to sync are passed as writable remaining accounts;
+    /// unexpired or already synced options are skipped.
+    /// only callable by the config authority.
+    pub fn sync_expired_options<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SyncExpiredOptions<'info>>,
+    ) -> Result<()> {
+        SyncExpiredOptions::handler(ctx)
+    }
+
+    /// whitelists an SPL asset for deposits and creates the treasury's vault for it.
+    /// `price_lamports` is the SOL value of one whole token; `price_authority` may refresh it.
+    /// callable by the config authority or a treasury manager.
+    pub fn add_treasury_asset(
+        ctx: Context<AddTreasuryAsset>,
+        price_authority: Pubkey,
+        price_lamports: u64,
+        max_price_age: i64,
+    ) -> Result<()> {
+        AddTreasuryAsset::handler(ctx, price_authority, price_lamports, max_price_age)
+    }
+
+    /// enables/disables a treasury asset and updates its price authority or max price age.
+    /// callable by the config authority or a treasury manager.
+    pub fn update_treasury_asset(
+        ctx: Context<UpdateTreasuryAsset>,
+        enabled: Option<bool>,
+        price_authority: Option<Pubkey>,
+        max_price_age: Option<i64>,
+    ) -> Result<()> {
+        UpdateTreasuryAsset::handler(ctx, enabled, price_authority, max_price_age)
+    }
+
+    /// sets the SOL price of a treasury asset (lamports per whole token) and re-values
+    /// the treasury's holdings of it. only callable by the asset's price authority.
+    pub fn set_asset_price(ctx: Context<SetAssetPrice>, price_lamports: u64) -> Result<()> {
+        SetAssetPrice::handler(ctx, price_lamports)
+    }
+
+    /// adds an option duration tier (seconds) that deposits can pick.
+    /// `cap` limits the total option amount issued in the tier; None or 0 is uncapped.
+    /// `conversion_multiplier_bps` is the PT paid per CN converted (10_000 = 1x).
+    /// only callable by the config authority.
+    pub fn add_duration_tier(
+        ctx: Context<AddDurationTier>,
+        duration: u32,
+        cap: Option<u64>,
+        conversion_multiplier_bps: u16,
+    ) -> Result<()> {
+        AddDurationTier::handler(ctx, duration, cap, conversion_multiplier_bps)
+    }
+
+    /// enables/disables a duration tier or changes its cap (0 removes the cap)
+    /// or conversion multiplier. only callable by the config authority.
+    pub fn update_duration_tier(
+        ctx: Context<UpdateDurationTier>,
+        enabled: Option<bool>,
+        cap: Option<u64>,
+        conversion_multiplier_bps: Option<u16>,
+    ) -> Result<()> {
+        UpdateDurationTier::handler(ctx, enabled, cap, conversion_multiplier_bps)
+    }
+
+    /// closes a duration tier so it can no longer be picked. options already issued
+    /// in it keep their stored expiration. only callable by the config authority.
+    pub fn retire_duration_tier(ctx: Context<RetireDurationTier>) -> Result<()> {
+        RetireDurationTier::handler(ctx)
+    }
+
+    /// spreads `amount` lamports of idle treasury SOL over all outstanding options as yield.
+    /// the yield is reserved in the treasury (and left out of the NAV) until claimed.
+    /// callable by the config authority or a treasury manager.
+    pub fn distribute_yield(ctx: Context<DistributeYield>, amount: u64) -> Result<()> {
+        DistributeYield::handler(ctx, amount)
+    }
+
+    /// pays the current holder of an option NFT the yield the option earned while outstanding.
+    pub fn claim_option_yield(ctx: Context<ClaimOptionYield>) -> Result<()> {
+        ClaimOptionYield::handler(ctx)
+    }
+
+    /// queues a withdrawal of `amount` lamports from the treasury to `recipient`.
+    /// it can only be executed once treasury.withdrawal_delay has passed, and never
+    /// takes the treasury below the SOL backing outstanding CN.
+    /// callable by the config authority or a treasury manager.
+    pub fn queue_treasury_withdrawal(
+        ctx: Context<QueueTreasuryWithdrawal>,
+        recipient: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        QueueTreasuryWithdrawal::handler(ctx, recipient, amount)
+    }
+
+    /// pays out the queued treasury withdrawal once its delay has passed.
+    /// callable by the config authority or a treasury manager.
+    pub fn execute_treasury_withdrawal(ctx: Context<ExecuteTreasuryWithdrawal>) -> Result<()> {
+        ExecuteTreasuryWithdrawal::handler(ctx)
+    }
+
+    /// drops the queued treasury withdrawal.
+    /// callable by the config authority or a treasury manager.
+    pub fn cancel_treasury_withdrawal(ctx: Context<CancelTreasuryWithdrawal>) -> Result<()> {
+        CancelTreasuryWithdrawal::handler(ctx)
+    }
+
+    /// sets the delay (seconds) between queueing and executing a treasury withdrawal.
+    /// only applies to withdrawals queued afterwards. only callable by the config authority.
+    pub fn update_withdrawal_delay(
+        ctx: Context<UpdateWithdrawalDelay>,
+        withdrawal_delay: i64,
     ) -> Result<()> {
-        UpdateLocks::handler(ctx, locked, deposit_locked, convert_locked)
+        UpdateWithdrawalDelay::handler(ctx, withdrawal_delay)
     }
 
-    /// closes a fully converted OptionData account and returns the rent to the config authority.
-    /// can only be called when option_data.amount = 0 (fully converted).
-    /// only the Config PDA can authorize this closure.
+    /// closes a fully spent OptionData account and returns the rent to the wallet that paid it.
+    /// can only be called when option_data.amount = 0 (fully converted or synced as expired).
+    /// callable by anyone.
     pub fn close_option_account(ctx: Context<CloseOptionAccount>) -> Result<()> {
         close_option_account::handler(ctx)
     }
+
+    /// reallocs the config to the current layout, fills defaults for the fields added
+    /// since it was written and bumps its version. a version 0 config is decoded with its
+    /// own layout, since fields were added in the middle of it. the authority pays for the
+    /// extra space.
+    /// the other migrations need the config migrated first.
+    /// only callable by the config authority.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        MigrateConfig::handler(ctx)
+    }
+
+    /// migrates the treasury like migrate_config. a version 0 treasury's converted CN is
+    /// read off the CN and PT supplies. only callable by the config authority.
+    pub fn migrate_treasury(ctx: Context<MigrateTreasury>) -> Result<()> {
+        MigrateTreasury::handler(ctx)
+    }
+
+    /// migrates an option's OptionData like migrate_config. options issued before their
+    /// rent payer was stored return their rent to the authority, as they did before.
+    /// only callable by the config authority.
+    pub fn migrate_option_data(ctx: Context<MigrateOptionData>) -> Result<()> {
+        MigrateOptionData::handler(ctx)
+    }
+
+    /// migrates a deposit receipt like migrate_config. `nonce` is the deposit nonce stored
+    /// on the receipt. only callable by the config authority.
+    pub fn migrate_deposit_receipt(ctx: Context<MigrateDepositReceipt>, nonce: u64) -> Result<()> {
+        MigrateDepositReceipt::handler(ctx, nonce)
+    }
+
+    /// moves a version 0 deposit receipt, kept at one per depositor before receipts were
+    /// keyed by the deposit nonce, to the next nonce so the current instructions can
+    /// address it. a claimed one is only closed. the legacy receipt's rent goes back to
+    /// the depositor. only callable by the config authority.
+    pub fn migrate_legacy_deposit_receipt(ctx: Context<MigrateLegacyDepositReceipt>) -> Result<()> {
+        MigrateLegacyDepositReceipt::handler(ctx)
+    }
+
+    /// creates the fee vault for a deployment initialized before it existed.
+    /// only callable by the config authority.
+    pub fn init_fee_vault(ctx: Context<InitFeeVault>) -> Result<()> {
+        InitFeeVault::handler(ctx)
+    }
+
+    /// creates the roles account for a deployment initialized before it existed.
+    /// only callable by the config authority.
+    pub fn init_roles(ctx: Context<InitRoles>) -> Result<()> {
+        InitRoles::handler(ctx)
+    }
 }
 
 #[error_code]
@@ -73,6 +470,17 @@ pub enum ErrorCode {
     OptionNotFullyConverted,
     #[msg("config authority not set")]
     AuthorityNotSet,
-    #[msg("receiver must be the config authority")]
-    ReceiverAuthorityMismatch,
+    #[msg("receiver must be the option data's rent payer")]
+    ReceiverRentPayerMismatch,
+    #[msg("treasury NAV is zero")]
+    ZeroNav,
+    #[msg("option has unclaimed yield")]
+    UnclaimedOptionYield,
+    /// the `is_current()` constraints run after Anchor deserializes the account, so an
+    /// account missing fields the current layout added fails first with
+    /// AccountDidNotDeserialize. this is only raised for one that loads but is older.
+    #[msg("account is on an old version, migrate it first")]
+    AccountVersionOutdated,
+    #[msg("config must be the delegate of the option NFT's token account")]
+    OptionNotDelegated,
 }
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+/// a depositor's proof of inclusion in the config's allowlist merkle tree.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AllowlistProof {
+    /// The SOL value (lamports) the depositor may deposit while the root is active.
+    /// 0 means the address is allowed without a limit.
+    pub allocation: u64,
+    /// Sibling hashes from the leaf up to the root.
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// leaf for `depositor` with `allocation`: sha256(depositor || allocation as u64 LE).
+pub fn allowlist_leaf(depositor: &Pubkey, allocation: u64) -> [u8; 32] {
+    hashv(&[depositor.as_ref(), &allocation.to_le_bytes()]).to_bytes()
+}
+
+/// walks `proof` up from `leaf` and checks it ends at `root`.
+/// pairs are hashed in sorted order, so proofs don't need to encode left/right.
+pub fn verify_allowlist_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == *root
+}
@@ -1,42 +1,216 @@
 use anchor_lang::prelude::*;
 
+use crate::state::{mul_div, Rounding, DEFAULT_CLAIM_WINDOW};
+use crate::ErrorCode;
+
+/// fixed-point scale for the option yield accumulator (lamports per option unit * YIELD_PRECISION).
+pub const YIELD_PRECISION: u128 = 1_000_000_000_000;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Config {
     /// The authority that can update the config.
     pub authority: Option<Pubkey>,
+    /// The authority proposed to take over, until it accepts.
+    pub pending_authority: Option<Pubkey>,
     /// The address of the Convertible Note we'll be issuing.
     pub cn_mint: Pubkey,
     /// The address of the Protocol Token we'll be issuing.
     pub pt_mint: Pubkey,
     /// The address of the options NFT collection.
     pub collection_mint: Pubkey,
-    /// The optional fee for using the protocol.
+    /// The optional fee for using the protocol, in basis points.
+    /// Charged on deposits and paid into the fee vault.
     pub fee: Option<u16>,
+    /// The optional fee charged on conversions, in basis points of the converted value.
+    pub conversion_fee: Option<u16>,
+    /// The account that receives fees claimed from the fee vault.
+    pub fee_recipient: Pubkey,
     /// Counter for naming/tracking options (optional).
     pub option_count: u64,
     /// Total amount of tokens in all outstanding options.
     pub total_option_amount: u64,
-    /// Counter for unique deposit receipts.
+    /// Counter for unique deposit receipts and option mints.
+    /// Incremented on every deposit so a user can hold any number of them.
     pub deposit_nonce: u64,
+    /// Yield distributed per unit of outstanding option amount, scaled by YIELD_PRECISION.
+    /// Only ever grows; each OptionData checkpoints it to work out its share.
+    pub yield_per_unit: u128,
+    /// The optional cap on the SOL value held from deposits (treasury.total_deposited_sol
+    /// plus SPL holdings), in lamports.
+    pub max_total_deposited_sol: Option<u64>,
+    /// The optional lifetime cap on the SOL value a single wallet can deposit, in lamports.
+    pub max_wallet_deposit: Option<u64>,
+    /// The optional cap on the SOL value deposited per epoch, in lamports.
+    pub max_epoch_deposit: Option<u64>,
+    /// The merkle root of the deposit allowlist. While set, only addresses with a
+    /// proof against it can deposit; None leaves deposits open to anyone.
+    pub allowlist_root: Option<[u8; 32]>,
+    /// The epoch `epoch_deposited` is counted for.
+    pub deposit_epoch: u64,
+    /// The SOL value deposited during `deposit_epoch`, in lamports.
+    pub epoch_deposited: u64,
+    /// How long (seconds) a depositor has to claim the option for a deposit receipt.
+    pub claim_window: i64,
     /// Used to lock the protocol in totality.
     pub locked: bool, // Global lock for all user-facing instructions
     /// Lock specifically for the deposit instruction.
     pub deposit_locked: bool,
     /// Lock specifically for the convert instruction.
     pub convert_locked: bool,
+    /// Lock specifically for the redeem instruction.
+    pub redeem_locked: bool,
     /// The bump used to generate this Config account.
     pub bump: u8, // Renamed from config_bump
+    /// The layout version this account was written with. New fields go after it.
+    pub version: u8,
+    /// Total amount of CN on deposit receipts whose option hasn't been claimed yet.
+    pub total_receipt_amount: u64,
+}
+
+/// the config layout from before accounts were versioned (version 0). fields were added
+/// in the middle of it since, so `migrate_config` decodes it with this layout.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct ConfigV0 {
+    pub authority: Option<Pubkey>,
+    pub cn_mint: Pubkey,
+    pub pt_mint: Pubkey,
+    pub collection_mint: Pubkey,
+    pub fee: Option<u16>,
+    pub option_count: u64,
+    pub total_option_amount: u64,
+    pub deposit_nonce: u64,
+    pub locked: bool,
+    pub deposit_locked: bool,
+    pub convert_locked: bool,
+    pub bump: u8,
 }
 
 impl Config {
     pub const SEED_PREFIX: &'static [u8] = b"config";
+    /// bumped whenever a field is added, see `migrate`. version 2 added
+    /// `total_receipt_amount`.
+    pub const VERSION: u8 = 2;
 
     pub fn get_seeds<'a>() -> [&'a [u8]; 1] {
         [Self::SEED_PREFIX]
     }
 
-    pub fn get_seeds_with_bump<'a>(bump: &'a [u8]) -> [&'a [u8]; 2] {
+    pub fn get_seeds_with_bump(bump: &[u8]) -> [&[u8]; 2] {
         [Self::SEED_PREFIX, bump]
     }
+
+    pub fn is_current(&self) -> bool {
+        self.version == Self::VERSION
+    }
+
+    /// the current layout of a version 0 config. the fields added since get the values
+    /// `initialize` gives them, with `fee_recipient` standing in for the initializer.
+    /// it stays on version 0 until `migrate` stamps it.
+    pub fn from_v0(v0: ConfigV0, fee_recipient: Pubkey) -> Self {
+        Self {
+            authority: v0.authority,
+            pending_authority: None,
+            cn_mint: v0.cn_mint,
+            pt_mint: v0.pt_mint,
+            collection_mint: v0.collection_mint,
+            fee: v0.fee,
+            conversion_fee: None,
+            fee_recipient,
+            option_count: v0.option_count,
+            total_option_amount: v0.total_option_amount,
+            deposit_nonce: v0.deposit_nonce,
+            yield_per_unit: 0,
+            max_total_deposited_sol: None,
+            max_wallet_deposit: None,
+            max_epoch_deposit: None,
+            allowlist_root: None,
+            deposit_epoch: 0,
+            epoch_deposited: 0,
+            claim_window: DEFAULT_CLAIM_WINDOW,
+            locked: v0.locked,
+            deposit_locked: v0.deposit_locked,
+            convert_locked: v0.convert_locked,
+            redeem_locked: true, // redemptions didn't exist, they start locked
+            bump: v0.bump,
+            version: 0,
+            total_receipt_amount: 0,
+        }
+    }
+
+    /// brings a versioned account up to date. versions after 0 only append fields, so
+    /// the ones added since it was written were zero-extended and get their defaults here.
+    pub fn migrate(&mut self) {
+        // configs from before the claim window would let receipts expire right away
+        if self.claim_window == 0 {
+            self.claim_window = DEFAULT_CLAIM_WINDOW;
+        }
+        self.version = Self::VERSION;
+    }
+
+    /// whether `key` is the config authority. false once the authority is renounced.
+    pub fn is_authority(&self, key: &Pubkey) -> bool {
+        self.authority.as_ref() == Some(key)
+    }
+
+    /// CN that neither an outstanding option nor an unclaimed receipt is set aside for:
+    /// its PT was burned when the option or receipt expired, so it can be redeemed on
+    /// its own. `cn_supply` is the outstanding CN supply.
+    pub fn unpaired_cn(&self, cn_supply: u64) -> u64 {
+        cn_supply
+            .saturating_sub(self.total_option_amount)
+            .saturating_sub(self.total_receipt_amount)
+    }
+
+    /// returns the current deposit nonce and advances the counter.
+    pub fn take_deposit_nonce(&mut self) -> Option<u64> {
+        let nonce = self.deposit_nonce;
+        self.deposit_nonce = nonce.checked_add(1)?;
+        Some(nonce)
+    }
+
+    /// whether `value` more can be deposited without the treasury going over
+    /// max_total_deposited_sol. `total_deposits` is what the treasury holds from deposits.
+    pub fn has_deposit_capacity(&self, total_deposits: u64, value: u64) -> bool {
+        match self.max_total_deposited_sol {
+            Some(cap) => total_deposits
+                .checked_add(value)
+                .is_some_and(|total| total <= cap),
+            None => true,
+        }
+    }
+
+    /// whether `epoch_deposited` stays within max_epoch_deposit.
+    pub fn has_epoch_capacity(&self, epoch_deposited: u64) -> bool {
+        match self.max_epoch_deposit {
+            Some(cap) => epoch_deposited <= cap,
+            None => true,
+        }
+    }
+
+    /// the amount deposited in `epoch` once `value` is added.
+    /// the count starts over at the first deposit of a new epoch.
+    pub fn epoch_deposited_after(&self, epoch: u64, value: u64) -> Option<u64> {
+        if epoch == self.deposit_epoch {
+            self.epoch_deposited.checked_add(value)
+        } else {
+            Some(value)
+        }
+    }
+
+    /// spreads `amount` lamports of yield across the outstanding option amount.
+    /// rounded down, so the accumulator never promises more than was distributed.
+    pub fn accrue_yield(&mut self, amount: u64) -> Result<()> {
+        let increment = mul_div(
+            amount as u128,
+            YIELD_PRECISION,
+            self.total_option_amount as u128,
+            Rounding::Down,
+        )?;
+        self.yield_per_unit = self
+            .yield_per_unit
+            .checked_add(increment)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
 }
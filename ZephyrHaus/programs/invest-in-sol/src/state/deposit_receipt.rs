@@ -1,32 +1,129 @@
 use anchor_lang::prelude::*;
 
+use crate::state::BASE_CONVERSION_MULTIPLIER_BPS;
+
+/// how long a depositor has to claim a receipt's option until the authority changes it (7 days).
+pub const DEFAULT_CLAIM_WINDOW: i64 = 7 * 24 * 60 * 60;
+/// the longest claim window the authority can configure (90 days).
+pub const MAX_CLAIM_WINDOW: i64 = 90 * 24 * 60 * 60;
+
 #[account]
 #[derive(InitSpace)]
 pub struct DepositReceipt {
     /// Whether this receipt has been initialized.
-    /// Used to ensure we don't have multiple deposits for the same user before they claim the NFT.
     pub initialized: bool,
-    /// Whether this receipt has been used to claim the Option NFT.
+    /// Whether this receipt has been used to claim the Option NFT. Claimed receipts are
+    /// closed now, so this is only set on ones claimed before that (see `close_deposit_receipt`).
     pub nft_issued: bool,
     /// The amount of SOL deposited by the user.
     pub amount: u64,
-    /// The expiration for the Option NFT in seconds since the Unix epoch.
-    pub expiration: i64,
+    /// The SOL value of the deposit (after fees), in lamports. The most `cancel_deposit` refunds.
+    pub value_lamports: u64,
+    /// The option duration in seconds, picked at deposit time. The option expires
+    /// this long after it is issued.
+    pub option_duration: u32,
+    /// The last moment (unix timestamp) the option can be claimed with `initialize_option`.
+    /// After it anyone can expire the receipt with `expire_deposit_receipt`.
+    pub claim_deadline: i64,
+    /// PT per CN converted (bps), taken from the duration tier at deposit time.
+    pub conversion_multiplier_bps: u16,
+    /// The deposit nonce (config.deposit_nonce at deposit time) keying this receipt and its option mint.
+    pub nonce: u64,
     /// The bump used to generate this DepositReceipt account.
     pub bump: u8,
+    /// The layout version this account was written with. New fields go after it.
+    pub version: u8,
+    /// The SPL asset deposited, or None for a SOL deposit. `cancel_deposit` refunds in kind.
+    pub asset_mint: Option<Pubkey>,
+    /// The amount of the deposited asset that reached the treasury, in its base units
+    /// (lamports for SOL).
+    pub asset_amount: u64,
+    /// The epoch the deposit was counted against the per-epoch deposit limit in.
+    pub deposit_epoch: u64,
+    /// The allowlist root the deposit was counted against, None while the allowlist was off.
+    pub allowlist_root: Option<[u8; 32]>,
+}
+
+/// the receipt layout from before accounts were versioned (version 0), one per depositor
+/// at `[SEED_PREFIX, depositor]`. `migrate_legacy_deposit_receipt` moves it to a nonce.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct DepositReceiptV0 {
+    pub initialized: bool,
+    pub nft_issued: bool,
+    pub amount: u64,
+    /// the option's expiration, also the last moment it could be claimed.
+    pub expiration: i64,
+    pub bump: u8,
 }
 
 impl DepositReceipt {
     pub const SEED_PREFIX: &'static [u8] = b"deposit_receipt";
+    /// bumped whenever a field is added, see `migrate`. version 2 added the
+    /// deposited asset, the deposit epoch and the allowlist root.
+    pub const VERSION: u8 = 2;
+
+    pub fn is_current(&self) -> bool {
+        self.version == Self::VERSION
+    }
 
-    pub fn get_seeds<'a>(depositor_keypair_bytes: &'a [u8]) -> [&'a [u8]; 2] {
-        [Self::SEED_PREFIX, depositor_keypair_bytes]
+    /// the current layout of an unclaimed version 0 receipt, moved to `nonce`. version 0
+    /// only took SOL at one lamport per CN and minted PT one to one. it could be claimed
+    /// until the option's expiration, so that stays the claim deadline, and the option
+    /// keeps as much of its duration as is left at `current_timestamp`.
+    pub fn from_v0(v0: DepositReceiptV0, nonce: u64, bump: u8, current_timestamp: i64) -> Self {
+        let option_duration = v0
+            .expiration
+            .saturating_sub(current_timestamp)
+            .clamp(0, u32::MAX as i64) as u32;
+        Self {
+            initialized: v0.initialized,
+            nft_issued: v0.nft_issued,
+            amount: v0.amount,
+            value_lamports: v0.amount,
+            option_duration,
+            claim_deadline: v0.expiration,
+            conversion_multiplier_bps: BASE_CONVERSION_MULTIPLIER_BPS,
+            nonce,
+            bump,
+            version: 0,
+            asset_mint: None,
+            asset_amount: v0.amount,
+            deposit_epoch: 0,
+            allowlist_root: None,
+        }
+    }
+
+    /// receipts from before version 2 only took SOL, so the asset deposited is the
+    /// deposit's value. the deposit epoch and allowlist root they were counted against
+    /// weren't stored, so those stay unset and `cancel_deposit` leaves those counters alone.
+    pub fn migrate(&mut self) {
+        if self.asset_amount == 0 {
+            self.asset_amount = self.value_lamports;
+        }
+        self.version = Self::VERSION;
+    }
+
+    /// the expiration of an option issued from this receipt at `current_timestamp`.
+    pub fn expiration_from(&self, current_timestamp: i64) -> Option<i64> {
+        current_timestamp.checked_add(self.option_duration as i64)
+    }
+
+    pub fn is_claimable(&self, current_timestamp: i64) -> bool {
+        current_timestamp <= self.claim_deadline
+    }
+
+    pub fn get_seeds<'a>(
+        depositor_keypair_bytes: &'a [u8],
+        nonce_bytes: &'a [u8],
+    ) -> [&'a [u8]; 3] {
+        [Self::SEED_PREFIX, depositor_keypair_bytes, nonce_bytes]
     }
 
     pub fn get_seeds_with_bump<'a>(
         depositor_keypair_bytes: &'a [u8],
+        nonce_bytes: &'a [u8],
         bump: &'a [u8],
-    ) -> [&'a [u8]; 3] {
-        [Self::SEED_PREFIX, depositor_keypair_bytes, bump]
+    ) -> [&'a [u8]; 4] {
+        [Self::SEED_PREFIX, depositor_keypair_bytes, nonce_bytes, bump]
     }
 }
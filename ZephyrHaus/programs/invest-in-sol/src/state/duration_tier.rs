@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{mul_div, Rounding, BPS_DENOMINATOR};
+use crate::ErrorCode;
+
+/// conversion multiplier of 1x: one PT per CN converted.
+pub const BASE_CONVERSION_MULTIPLIER_BPS: u16 = 10_000;
+/// the highest conversion multiplier a tier can offer (5x).
+pub const MAX_CONVERSION_MULTIPLIER_BPS: u16 = 50_000;
+
+/// an option duration the protocol offers on deposits, managed by the authority.
+#[account]
+#[derive(InitSpace)]
+pub struct DurationTier {
+    /// The option duration in seconds.
+    pub duration: u32,
+    /// Whether new deposits may pick this tier.
+    pub enabled: bool,
+    /// The optional cap on the total option amount issued in this tier.
+    pub cap: Option<u64>,
+    /// The total option amount issued in this tier so far.
+    pub total_issued: u64,
+    /// PT paid out per CN converted, in basis points (10_000 = 1x).
+    pub conversion_multiplier_bps: u16,
+    /// The bump used to generate this DurationTier account.
+    pub bump: u8,
+}
+
+impl DurationTier {
+    pub const SEED_PREFIX: &'static [u8] = b"duration_tier";
+
+    pub fn get_seeds_with_bump<'a>(duration_bytes: &'a [u8], bump: &'a [u8]) -> [&'a [u8]; 3] {
+        [Self::SEED_PREFIX, duration_bytes, bump]
+    }
+
+    /// whether `amount` more can be issued without going over the cap.
+    pub fn has_capacity(&self, amount: u64) -> bool {
+        match self.cap {
+            Some(cap) => self
+                .total_issued
+                .checked_add(amount)
+                .is_some_and(|total| total <= cap),
+            None => true,
+        }
+    }
+
+    /// expiration for an option issued at `current_timestamp` in this tier.
+    pub fn expiration_from(&self, current_timestamp: i64) -> Option<i64> {
+        current_timestamp.checked_add(self.duration as i64)
+    }
+}
+
+/// PT owed for `cn_amount` CN at `multiplier_bps`.
+/// PT backing an option is minted rounded up and paid out rounded down, so the
+/// protocol always holds enough PT for every conversion.
+pub fn pt_for_cn(cn_amount: u64, multiplier_bps: u16, rounding: Rounding) -> Result<u64> {
+    let pt = mul_div(
+        cn_amount as u128,
+        multiplier_bps as u128,
+        BPS_DENOMINATOR as u128,
+        rounding,
+    )?;
+    u64::try_from(pt).map_err(|_| ErrorCode::Overflow.into())
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+/// basis points denominator used for all protocol fees.
+pub const BPS_DENOMINATOR: u64 = 10_000;
+/// the highest fee the authority can configure (10%).
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+/// a program-owned PDA that collects protocol fees.
+/// SOL fees are held as lamports on this account, SPL asset fees
+/// in ATAs owned by it.
+#[account]
+#[derive(InitSpace)]
+pub struct FeeVault {
+    /// Total SOL fees collected, in lamports.
+    pub total_collected_sol: u64,
+    /// Total SOL fees claimed by the fee recipient, in lamports.
+    pub total_claimed_sol: u64,
+    /// The bump used to generate this FeeVault account.
+    pub bump: u8,
+}
+
+impl FeeVault {
+    pub const SEED_PREFIX: &'static [u8] = b"fee_vault";
+
+    pub fn get_seeds_with_bump(bump: &[u8]) -> [&[u8]; 2] {
+        [Self::SEED_PREFIX, bump]
+    }
+
+    /// fee owed on `amount` at `fee_bps`, rounded up so it always favours the protocol.
+    /// no fee is charged when `fee_bps` is unset.
+    pub fn calculate_fee(amount: u64, fee_bps: Option<u16>) -> Result<u64> {
+        let Some(fee_bps) = fee_bps else {
+            return Ok(0);
+        };
+        let product = (amount as u128)
+            .checked_mul(fee_bps as u128)
+            .ok_or(ErrorCode::Overflow)?;
+        let fee = product.div_ceil(BPS_DENOMINATOR as u128);
+        u64::try_from(fee).map_err(|_| ErrorCode::Overflow.into())
+    }
+
+    /// lamports held by the fee vault above its rent-exempt minimum.
+    pub fn claimable_sol(fee_vault_info: &AccountInfo) -> Result<u64> {
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(fee_vault_info.data_len());
+        Ok(fee_vault_info
+            .lamports()
+            .saturating_sub(rent_exempt_minimum))
+    }
+}
@@ -1,9 +1,23 @@
+pub mod allowlist;
 pub mod config;
 pub mod deposit_receipt;
+pub mod duration_tier;
+pub mod fee_vault;
 pub mod option_data;
+pub mod roles;
 pub mod treasury;
+pub mod treasury_asset;
+pub mod treasury_withdrawal;
+pub mod user_position;
 
+pub use allowlist::*;
 pub use config::*;
 pub use deposit_receipt::*;
+pub use duration_tier::*;
+pub use fee_vault::*;
 pub use option_data::*;
+pub use roles::*;
 pub use treasury::*;
+pub use treasury_asset::*;
+pub use treasury_withdrawal::*;
+pub use user_position::*;
@@ -1,11 +1,54 @@
 use anchor_lang::prelude::*;
 
+use crate::state::{
+    mul_div, pt_for_cn, Rounding, BASE_CONVERSION_MULTIPLIER_BPS, YIELD_PRECISION,
+};
+use crate::ErrorCode;
+
+/// the state an option NFT's metadata reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionStatus {
+    /// can still be converted
+    Active,
+    /// past its expiration, can no longer be converted
+    Expired,
+    /// fully converted
+    Exhausted,
+}
+
+impl OptionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OptionStatus::Active => "active",
+            OptionStatus::Expired => "expired",
+            OptionStatus::Exhausted => "exhausted",
+        }
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct OptionData {
     pub mint: Pubkey, // the mint address of the option NFT
     pub amount: u64, // the amount of underlying deposited / CN tokens minted
     pub expiration: i64, // unix timestamp of expiration
+    pub expired: bool, // set once the expired option has been synced and its PT burned
+    pub conversion_multiplier_bps: u16, // PT per CN converted, copied from the duration tier at issue
+    pub yield_checkpoint: u128, // config.yield_per_unit when this option's yield was last settled
+    pub accrued_yield: u64, // lamports of yield settled to this option but not claimed yet
+    pub unlock_at: Option<i64>, // while frozen until a cliff, when anyone can thaw the NFT
+    pub rent_payer: Pubkey, // paid this account's rent, gets it back when the account is closed
+    pub bump: u8,
+    pub version: u8, // layout version this account was written with, new fields go after it
+}
+
+// the option data layout from before accounts were versioned (version 0). fields were
+// added in the middle of it since, so `migrate_option_data` decodes it with this layout
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct OptionDataV0 {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub expiration: i64,
     pub bump: u8,
 }
 
@@ -13,6 +56,8 @@ impl OptionData {
     // define seeds for the PDA
     // using the option mint seems appropriate for uniqueness
     pub const SEED_PREFIX: &'static [u8] = b"option_data";
+    // bumped whenever a field is added, see `migrate`
+    pub const VERSION: u8 = 1;
 
      pub fn get_seeds_with_bump<'a>(mint: &'a Pubkey, bump: &'a [u8]) -> [&'a [u8]; 3] {
         [
@@ -23,8 +68,72 @@ impl OptionData {
     }
 
 
+    pub fn is_current(&self) -> bool {
+        self.version == Self::VERSION
+    }
+
+    // the current layout of a version 0 option. version 0 minted PT one to one, and the
+    // option has counted towards config.total_option_amount all along, so it's owed its
+    // share of all yield distributed. it stays on version 0 until `migrate` stamps it
+    pub fn from_v0(v0: OptionDataV0) -> Self {
+        Self {
+            mint: v0.mint,
+            amount: v0.amount,
+            expiration: v0.expiration,
+            expired: false,
+            conversion_multiplier_bps: BASE_CONVERSION_MULTIPLIER_BPS,
+            yield_checkpoint: 0,
+            accrued_yield: 0,
+            unlock_at: None,
+            rent_payer: Pubkey::default(),
+            bump: v0.bump,
+            version: 0,
+        }
+    }
+
+    // options issued before the rent payer was stored have it zeroed, their rent
+    // goes to `default_rent_payer` instead
+    pub fn migrate(&mut self, default_rent_payer: Pubkey) {
+        if self.rent_payer == Pubkey::default() {
+            self.rent_payer = default_rent_payer;
+        }
+        self.version = Self::VERSION;
+    }
+
     // method to check expiration of the option
     pub fn is_expired(&self, current_timestamp: i64) -> bool {
         current_timestamp >= self.expiration
     }
-}
\ No newline at end of file
+
+    // an option synced as expired has no amount left either, but reports expired
+    pub fn status(&self, current_timestamp: i64) -> OptionStatus {
+        if self.amount == 0 && !self.expired {
+            OptionStatus::Exhausted
+        } else if self.expired || self.is_expired(current_timestamp) {
+            OptionStatus::Expired
+        } else {
+            OptionStatus::Active
+        }
+    }
+
+    // PT owed for converting `cn_amount` of this option
+    pub fn pt_for_cn(&self, cn_amount: u64, rounding: Rounding) -> Result<u64> {
+        pt_for_cn(cn_amount, self.conversion_multiplier_bps, rounding)
+    }
+
+    // moves the yield earned since the last checkpoint into accrued_yield.
+    // must run before `amount` changes so the old amount earns up to now
+    pub fn settle_yield(&mut self, yield_per_unit: u128) -> Result<()> {
+        let delta = yield_per_unit
+            .checked_sub(self.yield_checkpoint)
+            .ok_or(ErrorCode::Overflow)?;
+        let earned = mul_div(self.amount as u128, delta, YIELD_PRECISION, Rounding::Down)?;
+        let earned = u64::try_from(earned).map_err(|_| ErrorCode::Overflow)?;
+        self.accrued_yield = self
+            .accrued_yield
+            .checked_add(earned)
+            .ok_or(ErrorCode::Overflow)?;
+        self.yield_checkpoint = yield_per_unit;
+        Ok(())
+    }
+}
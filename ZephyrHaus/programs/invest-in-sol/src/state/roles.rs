@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+/// the most members the roles account can hold.
+pub const MAX_ROLE_MEMBERS: usize = 16;
+
+/// capabilities the config authority can hand out, so one key doesn't control everything.
+/// the authority itself can always do everything a role can.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum Role {
+    /// can set locks to true and freeze option NFTs, but never unlock or thaw
+    PauseGuardian,
+    /// can update and claim fees
+    FeeManager,
+    /// can manage treasury assets, distribute yield and queue treasury withdrawals
+    TreasuryManager,
+    /// can freeze and thaw option NFTs
+    MetadataManager,
+}
+
+impl Role {
+    pub fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct RoleMember {
+    pub key: Pubkey,
+    /// bitmask of `Role::mask` values held by `key`.
+    pub roles: u8,
+}
+
+/// maps pubkeys to the roles they hold. a singleton PDA created in `initialize`.
+#[account]
+#[derive(InitSpace)]
+pub struct Roles {
+    #[max_len(MAX_ROLE_MEMBERS)]
+    pub members: Vec<RoleMember>,
+    /// The bump used to generate this Roles account.
+    pub bump: u8,
+}
+
+impl Roles {
+    pub const SEED_PREFIX: &'static [u8] = b"roles";
+
+    pub fn has_role(&self, key: &Pubkey, role: Role) -> bool {
+        self.members
+            .iter()
+            .any(|member| member.key == *key && member.roles & role.mask() != 0)
+    }
+
+    /// adds `role` to `key`. returns false if the account is full.
+    pub fn grant(&mut self, key: Pubkey, role: Role) -> bool {
+        if let Some(member) = self.members.iter_mut().find(|member| member.key == key) {
+            member.roles |= role.mask();
+            return true;
+        }
+        if self.members.len() >= MAX_ROLE_MEMBERS {
+            return false;
+        }
+        self.members.push(RoleMember {
+            key,
+            roles: role.mask(),
+        });
+        true
+    }
+
+    /// removes `role` from `key`, dropping members left without roles.
+    /// returns false if `key` didn't hold it.
+    pub fn revoke(&mut self, key: &Pubkey, role: Role) -> bool {
+        if !self.has_role(key, role) {
+            return false;
+        }
+        for member in self.members.iter_mut().filter(|member| member.key == *key) {
+            member.roles &= !role.mask();
+        }
+        self.members.retain(|member| member.roles != 0);
+        true
+    }
+}
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+
+use crate::state::DEFAULT_WITHDRAWAL_DELAY;
+use crate::ErrorCode;
 //    - a treasury PDA
 //        - tracks deposited sol
 //    - a treasury vault
@@ -6,6 +9,17 @@ use anchor_lang::prelude::*;
 //            - PT token ata
 //            - ability to hold sol
 
+/// fixed-point scale for the NAV (lamports per token * NAV_PRECISION).
+pub const NAV_PRECISION: u128 = 1_000_000_000;
+
+/// which way a fixed-point result is rounded.
+/// always pick the direction that favours the protocol.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Treasury {
@@ -16,23 +30,188 @@ pub struct Treasury {
     /// the total amount of SOL deposited into the treasury vault.
     #[max_len(8)] // u64 size
     pub total_deposited_sol: u64,
+    /// SOL value of the SPL assets held by the treasury, at the price they were deposited.
+    /// per-asset balances are tracked on each TreasuryAsset account.
+    pub asset_holdings_sol: u64,
+    /// the last NAV used by the protocol, in lamports per token scaled by NAV_PRECISION.
+    pub nav: u64,
+    /// unix timestamp of the last NAV update.
+    pub nav_updated_at: i64,
+    /// idle SOL distributed to option holders as yield but not claimed yet.
+    /// it still sits in the treasury but is excluded from the NAV.
+    pub reserved_yield: u64,
+    /// seconds a queued withdrawal must wait before it can be executed.
+    pub withdrawal_delay: i64,
+    /// the layout version this account was written with. new fields go after it.
+    pub version: u8,
+    /// CN converted into PT so far. the SOL behind it stays in the treasury, so it
+    /// keeps counting as backing units next to the CN supply.
+    pub converted_cn: u64,
+    /// SOL paid in for the outstanding backing units: deposits, less the share taken
+    /// out by redemptions and cancellations. withdrawals can't take the treasury below it.
+    pub backing_sol: u64,
 }
+
+/// the treasury layout from before accounts were versioned (version 0). fields were
+/// added in the middle of it since, so `migrate_treasury` decodes it with this layout.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct TreasuryV0 {
+    pub authority: Option<Pubkey>,
+    pub treasury_bump: u8,
+    pub total_deposited_sol: u64,
+}
+
 impl Treasury {
     pub const SEED_PREFIX: &'static [u8] = b"treasury";
+    /// bumped whenever a field is added, see `migrate`.
+    pub const VERSION: u8 = 1;
+
+    pub fn is_current(&self) -> bool {
+        self.version == Self::VERSION
+    }
+
+    /// the current layout of a version 0 treasury, which only ever took SOL deposits.
+    /// all of it backs the CN and PT issued for it, and `converted_cn` is the CN converted
+    /// so far, which version 0 minted PT for one to one. it stays on version 0 until
+    /// `migrate` stamps it.
+    pub fn from_v0(v0: TreasuryV0, converted_cn: u64) -> Self {
+        Self {
+            authority: v0.authority,
+            treasury_bump: v0.treasury_bump,
+            total_deposited_sol: v0.total_deposited_sol,
+            asset_holdings_sol: 0,
+            nav: NAV_PRECISION as u64,
+            nav_updated_at: 0,
+            reserved_yield: 0,
+            withdrawal_delay: DEFAULT_WITHDRAWAL_DELAY,
+            version: 0,
+            converted_cn,
+            backing_sol: v0.total_deposited_sol,
+        }
+    }
+
+    /// no treasury field added after version 0 needs a default yet, so this only
+    /// stamps the version.
+    pub fn migrate(&mut self) {
+        self.version = Self::VERSION;
+    }
 
     pub fn get_seeds<'a>() -> [&'a [u8]; 1] {
         [Self::SEED_PREFIX]
     }
 
-    pub fn get_seeds_with_bump<'a>(bump: &'a [u8]) -> [&'a [u8]; 2] {
+    pub fn get_seeds_with_bump(bump: &[u8]) -> [&[u8]; 2] {
         [Self::SEED_PREFIX, bump]
     }
 
-    /// calculates the net asset value (nav) of the treasury.
-    /// todo: implement actual nav calculation based on treasury assets (sol, lps, etc.).
-    pub fn calculate_nav(&self) -> Result<u64> {
-        // placeholder: currently returns 1 sol per 1 cn token outstanding (implicitly)
-        // this needs to be updated to reflect the actual value of assets held.
-        Ok(1) // represents a 1:1 value for now
+    /// lamports held by the treasury account above its rent-exempt minimum.
+    pub fn idle_sol(treasury_info: &AccountInfo) -> Result<u64> {
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury_info.data_len());
+        Ok(treasury_info.lamports().saturating_sub(rent_exempt_minimum))
+    }
+
+    /// idle SOL that is not reserved for option yield.
+    pub fn unreserved_sol(&self, idle_sol: u64) -> u64 {
+        idle_sol.saturating_sub(self.reserved_yield)
+    }
+
+    /// total value of the treasury's assets in lamports:
+    /// unreserved idle SOL plus SPL asset holdings.
+    pub fn total_assets(&self, idle_sol: u64) -> Result<u64> {
+        self.unreserved_sol(idle_sol)
+            .checked_add(self.asset_holdings_sol)
+            .ok_or(ErrorCode::Overflow.into())
+    }
+
+    /// the SOL value held from deposits: SOL deposits plus SPL holdings, in lamports.
+    pub fn total_deposits(&self) -> Option<u64> {
+        self.total_deposited_sol
+            .checked_add(self.asset_holdings_sol)
+    }
+
+    /// the units the treasury's assets are shared between: the outstanding CN plus
+    /// the CN converted into PT. PT is scaled by the conversion multiplier, so its
+    /// supply can't be used here without diluting CN.
+    pub fn backing_units(&self, cn_supply: u64) -> Result<u64> {
+        cn_supply
+            .checked_add(self.converted_cn)
+            .ok_or(ErrorCode::Overflow.into())
+    }
+
+    /// calculates the net asset value (nav) of the treasury, in lamports per
+    /// backing unit scaled by NAV_PRECISION. `cn_supply` is the outstanding CN supply.
+    /// before anything is issued the nav is 1 lamport per token.
+    pub fn calculate_nav(&self, idle_sol: u64, cn_supply: u64, rounding: Rounding) -> Result<u64> {
+        let units = self.backing_units(cn_supply)?;
+        if units == 0 {
+            return Ok(NAV_PRECISION as u64);
+        }
+
+        let total_assets = self.total_assets(idle_sol)? as u128;
+        let nav = mul_div(total_assets, NAV_PRECISION, units as u128, rounding)?;
+        require!(nav > 0, ErrorCode::ZeroNav);
+        u64::try_from(nav).map_err(|_| ErrorCode::Overflow.into())
+    }
+
+    /// SOL the authority may withdraw: the idle SOL not reserved for option yield above
+    /// the SOL paid in for the backing units (CN and converted CN alike, whatever their
+    /// multiplier). SPL holdings are valued at pushed prices, so they never count.
+    pub fn withdrawable_sol(&self, idle_sol: u64) -> u64 {
+        self.unreserved_sol(idle_sol).saturating_sub(self.backing_sol)
+    }
+
+    /// records `lamports` paid in for newly issued backing units.
+    pub fn add_backing(&mut self, lamports: u64) -> Result<()> {
+        self.backing_sol = self
+            .backing_sol
+            .checked_add(lamports)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// releases the backing of `units` leaving the treasury, their pro-rata share of
+    /// `backing_sol` rounded down so the rest stays fully reserved.
+    /// `cn_supply` is the CN supply before they are burned.
+    pub fn release_backing(&mut self, units: u64, cn_supply: u64) -> Result<u64> {
+        let total_units = self.backing_units(cn_supply)?;
+        let released = mul_div(
+            self.backing_sol as u128,
+            units as u128,
+            total_units as u128,
+            Rounding::Down,
+        )?;
+        let released = u64::try_from(released).map_err(|_| ErrorCode::Overflow)?;
+        self.backing_sol = self.backing_sol.saturating_sub(released);
+        Ok(released)
+    }
+
+    /// records the nav used by the current instruction.
+    pub fn update_nav(&mut self, nav: u64) -> Result<()> {
+        self.nav = nav;
+        self.nav_updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// tokens to mint for `lamports` at `nav`, rounded down.
+    pub fn tokens_for_lamports(lamports: u64, nav: u64) -> Result<u64> {
+        let tokens = mul_div(lamports as u128, NAV_PRECISION, nav as u128, Rounding::Down)?;
+        u64::try_from(tokens).map_err(|_| ErrorCode::Overflow.into())
+    }
+
+    /// lamports owed for `tokens` at `nav`, rounded down.
+    pub fn lamports_for_tokens(tokens: u64, nav: u64) -> Result<u64> {
+        let lamports = mul_div(tokens as u128, nav as u128, NAV_PRECISION, Rounding::Down)?;
+        u64::try_from(lamports).map_err(|_| ErrorCode::Overflow.into())
+    }
+}
+
+/// computes `a * b / denominator` in u128 with the requested rounding.
+pub(crate) fn mul_div(a: u128, b: u128, denominator: u128, rounding: Rounding) -> Result<u128> {
+    require!(denominator > 0, ErrorCode::ZeroNav);
+    let product = a.checked_mul(b).ok_or(ErrorCode::Overflow)?;
+    let quotient = product / denominator;
+    if rounding == Rounding::Up && product % denominator != 0 {
+        return quotient.checked_add(1).ok_or(ErrorCode::Overflow.into());
     }
+    Ok(quotient)
 }
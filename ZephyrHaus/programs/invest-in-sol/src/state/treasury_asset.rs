@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Treasury;
+use crate::ErrorCode;
+
+/// a whitelisted SPL asset the treasury accepts deposits in.
+#[account]
+#[derive(InitSpace)]
+pub struct TreasuryAsset {
+    /// The SPL mint accepted for deposits.
+    pub mint: Pubkey,
+    /// The treasury-owned token account holding deposits of this asset.
+    pub vault: Pubkey,
+    /// The account allowed to push prices for this asset.
+    pub price_authority: Pubkey,
+    /// Price of one whole token (10^decimals base units) in lamports.
+    pub price_lamports: u64,
+    /// Unix timestamp of the last price update.
+    pub price_updated_at: i64,
+    /// Maximum age of the price in seconds before deposits are rejected.
+    pub max_price_age: i64,
+    /// The total amount of this asset deposited into the vault, in base units.
+    pub total_deposited: u64,
+    /// Whether deposits of this asset are currently accepted.
+    pub enabled: bool,
+    /// The bump used to generate this TreasuryAsset account.
+    pub bump: u8,
+    /// The asset mint's decimals, used to value `total_deposited`.
+    pub decimals: u8,
+    /// The SOL value of `total_deposited` last booked into `treasury.asset_holdings_sol`.
+    pub value_lamports: u64,
+}
+
+impl TreasuryAsset {
+    pub const SEED_PREFIX: &'static [u8] = b"treasury_asset";
+
+    pub fn get_seeds_with_bump<'a>(mint: &'a Pubkey, bump: &'a [u8]) -> [&'a [u8]; 3] {
+        [Self::SEED_PREFIX, mint.as_ref(), bump]
+    }
+
+    /// whether the price is recent enough to value a deposit.
+    pub fn is_price_fresh(&self, current_timestamp: i64) -> bool {
+        current_timestamp.saturating_sub(self.price_updated_at) <= self.max_price_age
+    }
+
+    /// values `amount` base units of this asset in lamports, rounded down.
+    pub fn value_in_lamports(&self, amount: u64) -> Result<u64> {
+        let one_token = 10u128
+            .checked_pow(self.decimals as u32)
+            .ok_or(ErrorCode::Overflow)?;
+        let value = (amount as u128)
+            .checked_mul(self.price_lamports as u128)
+            .ok_or(ErrorCode::Overflow)?
+            / one_token;
+        u64::try_from(value).map_err(|_| ErrorCode::Overflow.into())
+    }
+
+    /// re-values `total_deposited` at the current price and moves the treasury's
+    /// `asset_holdings_sol` by the difference from the last booked value.
+    /// call it whenever the price or the deposited amount changes.
+    pub fn revalue(&mut self, treasury: &mut Treasury) -> Result<()> {
+        let value_lamports = self.value_in_lamports(self.total_deposited)?;
+        treasury.asset_holdings_sol = treasury
+            .asset_holdings_sol
+            .checked_sub(self.value_lamports)
+            .and_then(|holdings| holdings.checked_add(value_lamports))
+            .ok_or(ErrorCode::Overflow)?;
+        self.value_lamports = value_lamports;
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+/// delay applied to treasury withdrawals until the authority changes it (2 days).
+pub const DEFAULT_WITHDRAWAL_DELAY: i64 = 2 * 24 * 60 * 60;
+/// the longest withdrawal delay the authority can configure (30 days).
+pub const MAX_WITHDRAWAL_DELAY: i64 = 30 * 24 * 60 * 60;
+
+/// a treasury withdrawal queued by the authority.
+/// only one can be pending at a time; it is closed when executed or cancelled.
+#[account]
+#[derive(InitSpace)]
+pub struct TreasuryWithdrawal {
+    /// The account receiving the withdrawn SOL.
+    pub recipient: Pubkey,
+    /// The amount of SOL to withdraw, in lamports.
+    pub amount: u64,
+    /// Unix timestamp the withdrawal was queued at.
+    pub queued_at: i64,
+    /// Unix timestamp from which the withdrawal can be executed.
+    pub executable_at: i64,
+    /// The bump used to generate this TreasuryWithdrawal account.
+    pub bump: u8,
+    /// The signer that queued the withdrawal and paid its rent, which gets it back
+    /// when the withdrawal is executed or cancelled.
+    pub queued_by: Pubkey,
+}
+
+impl TreasuryWithdrawal {
+    pub const SEED_PREFIX: &'static [u8] = b"treasury_withdrawal";
+
+    /// whether the timelock has passed.
+    pub fn is_executable(&self, current_timestamp: i64) -> bool {
+        current_timestamp >= self.executable_at
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+/// per-depositor deposit totals, used to enforce the per-wallet deposit cap.
+#[account]
+#[derive(InitSpace)]
+pub struct UserPosition {
+    /// The depositor this position belongs to.
+    pub owner: Pubkey,
+    /// The SOL value of everything the owner has deposited (after fees), in lamports.
+    /// Lifetime total, it is not reduced by conversions or redemptions, only by `cancel_deposit`.
+    pub total_deposited: u64,
+    /// The allowlist root `allowlist_used` is counted against.
+    pub allowlist_root: [u8; 32],
+    /// The SOL value deposited against the owner's allocation under `allowlist_root`, in lamports.
+    pub allowlist_used: u64,
+    /// The bump used to generate this UserPosition account.
+    pub bump: u8,
+}
+
+impl UserPosition {
+    pub const SEED_PREFIX: &'static [u8] = b"user_position";
+
+    /// the allocation used under `root` once `value` is added.
+    /// the count starts over when the authority sets a new root.
+    pub fn allowlist_used_after(&self, root: &[u8; 32], value: u64) -> Option<u64> {
+        if *root == self.allowlist_root {
+            self.allowlist_used.checked_add(value)
+        } else {
+            Some(value)
+        }
+    }
+
+    /// whether `value` more can be deposited without going over `cap`.
+    pub fn has_capacity(&self, cap: Option<u64>, value: u64) -> bool {
+        match cap {
+            Some(cap) => self
+                .total_deposited
+                .checked_add(value)
+                .is_some_and(|total| total <= cap),
+            None => true,
+        }
+    }
+}
@@ -13,8 +13,11 @@ import {
   TOKEN_METADATA_PROGRAM_ID,
   localSendAndConfirmTransaction,
   updateLocks,
+  syncExpiredOptions,
   deposit,
   initializeOption,
+  findFeeVaultPda,
+  findEventAuthorityPda,
   debugEnableLogs,
 } from "./utils";
 import {
@@ -309,7 +312,8 @@ describe("admin instructions (with hardcoded mints)", () => {
     const initializeOptionRes = await initializeOption(
       program,
       provider,
-      testUser
+      testUser,
+      depositResult.nonce
     );
 
     // lock globally
@@ -340,10 +344,13 @@ describe("admin instructions (with hardcoded mints)", () => {
           converterPtAta,
           config: configPda,
           protocolPtAta: depositInfo.protocolPtAta,
+          treasury: treasuryPda,
+          feeVault: findFeeVaultPda(program),
           cnMint,
           ptMint,
           nftMint: initializeOptionRes.optionMint,
           optionData: initializeOptionRes.optionData,
+          optionRentPayer: testUser.publicKey,
           nftMetadata: initializeOptionRes.optionMetadataAccount,
           nftMasterEdition: initializeOptionRes.optionMasterEdition,
           collectionMetadata: collectionMetadataPda,
@@ -353,6 +360,8 @@ describe("admin instructions (with hardcoded mints)", () => {
           metadataProgram: TOKEN_METADATA_PROGRAM_ID,
           sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY, // use token_2022_program_id
+          eventAuthority: findEventAuthorityPda(program),
+          program: program.programId,
         })
         .signers([testUser])
         .transaction();
@@ -389,4 +398,88 @@ describe("admin instructions (with hardcoded mints)", () => {
       console.log("global lock convert prevention test finished.");
     }
   });
+
+  // --- tests for syncing expired options ---
+
+  it("skips unexpired options when syncing", async () => {
+    console.log("testing sync skips unexpired options...");
+    const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    const syncUser = Keypair.generate();
+    await requestAirdrop(provider, syncUser.publicKey, 1 * LAMPORTS_PER_SOL);
+
+    const depositorCnAta = await anchor.utils.token.associatedAddress({
+      mint: cnMint,
+      owner: syncUser.publicKey,
+    });
+    const protocolPtAta = await anchor.utils.token.associatedAddress({
+      mint: ptMint,
+      owner: configPda,
+    });
+    const { nonce } = await deposit(
+      program,
+      provider,
+      syncUser,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    const { optionData } = await initializeOption(
+      program,
+      provider,
+      syncUser,
+      nonce
+    );
+
+    const configBefore = await program.account.config.fetch(configPda);
+    await syncExpiredOptions(program, provider, initializer.payer, ptMint, [
+      optionData,
+    ]);
+
+    // the option was just issued, so nothing should change
+    const configAfter = await program.account.config.fetch(configPda);
+    assert.strictEqual(
+      configAfter.totalOptionAmount.toString(),
+      configBefore.totalOptionAmount.toString(),
+      "total option amount should not change for unexpired options"
+    );
+    assert.strictEqual(
+      configAfter.optionCount.toString(),
+      configBefore.optionCount.toString(),
+      "option count should not change for unexpired options"
+    );
+    const optionDataAccount = await program.account.optionData.fetch(
+      optionData
+    );
+    assert.isFalse(optionDataAccount.expired, "option should not be expired");
+    assert.strictEqual(
+      optionDataAccount.amount.toString(),
+      depositAmount.toString(),
+      "option amount should not change"
+    );
+  });
+
+  it("fails if non-authority tries to sync expired options", async () => {
+    console.log("testing non-authority sync failure...");
+    const nonAuthority = Keypair.generate();
+    await requestAirdrop(
+      provider,
+      nonAuthority.publicKey,
+      1 * LAMPORTS_PER_SOL
+    );
+
+    try {
+      await syncExpiredOptions(program, provider, nonAuthority, ptMint, []);
+      assert.fail("transaction should have failed due to incorrect authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (non-authority sync)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (non-authority sync)"
+      );
+    }
+  });
 });
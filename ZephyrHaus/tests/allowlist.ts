@@ -0,0 +1,181 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddress } from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  THREE_MONTHS_SECONDS,
+  AllowlistProof,
+  initializeProtocol,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  buildAllowlist,
+  setAllowlistRoot,
+  deposit,
+  findUserPositionPda,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("allowlist", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const allowed = Keypair.generate(); // allowlisted with an allocation
+  const unlimited = Keypair.generate(); // allowlisted without a limit
+  const outsider = Keypair.generate(); // not on the allowlist
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+  // room for one deposit but not two
+  const allocation = depositAmount.muln(3).divn(2);
+
+  let configPda: PublicKey;
+  let allowedProof: AllowlistProof;
+  let unlimitedProof: AllowlistProof;
+
+  const depositAs = async (
+    depositor: Keypair,
+    allowlistProof: AllowlistProof | null
+  ) =>
+    deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      await getAssociatedTokenAddress(ptMint, configPda, true),
+      await getAssociatedTokenAddress(cnMint, depositor.publicKey, true),
+      THREE_MONTHS_SECONDS,
+      allowlistProof
+    );
+
+  // deposits as `depositor` and expects it to fail with `code`
+  const expectDepositError = async (
+    depositor: Keypair,
+    allowlistProof: AllowlistProof | null,
+    code: string
+  ) => {
+    try {
+      await depositAs(depositor, allowlistProof);
+      assert.fail(`deposit should have failed with ${code}`);
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, `should be an AnchorError (${code})`);
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        code,
+        `error code mismatch (${code})`
+      );
+    }
+  };
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, allowed.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, unlimited.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, outsider.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null
+    );
+
+    const { root, proofs } = buildAllowlist([
+      { depositor: allowed.publicKey, allocation },
+      { depositor: unlimited.publicKey, allocation: new anchor.BN(0) },
+      { depositor: Keypair.generate().publicKey, allocation },
+    ]);
+    [allowedProof, unlimitedProof] = proofs;
+    await setAllowlistRoot(program, provider, initializer.payer, root);
+  });
+
+  after(async () => {
+    // later suites share the validator, so deposits are opened again
+    await setAllowlistRoot(program, provider, initializer.payer, null);
+  });
+
+  it("fails to set the root from a non-authority", async () => {
+    try {
+      await setAllowlistRoot(program, provider, outsider, null);
+      assert.fail("set allowlist root should have failed for non-authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unauthorized)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (unauthorized)"
+      );
+    }
+  });
+
+  it("fails to deposit without a proof", async () => {
+    await expectDepositError(outsider, null, "NotAllowlisted");
+  });
+
+  it("fails to deposit with another address's proof", async () => {
+    await expectDepositError(outsider, allowedProof, "NotAllowlisted");
+  });
+
+  it("deposits with a valid proof and tracks the allocation", async () => {
+    await depositAs(allowed, allowedProof);
+
+    const config = await program.account.config.fetch(configPda);
+    const position = await program.account.userPosition.fetch(
+      findUserPositionPda(program, allowed.publicKey)
+    );
+    assert.deepEqual(
+      position.allowlistRoot,
+      config.allowlistRoot,
+      "allocation should be counted under the current root"
+    );
+    assert.strictEqual(
+      position.allowlistUsed.toString(),
+      position.totalDeposited.toString(),
+      "allocation used should match the deposit"
+    );
+  });
+
+  it("fails to deposit over the allocation", async () => {
+    await expectDepositError(
+      allowed,
+      allowedProof,
+      "AllowlistAllocationExceeded"
+    );
+  });
+
+  it("deposits without a limit for an allocation of 0", async () => {
+    await depositAs(unlimited, unlimitedProof);
+    await depositAs(unlimited, unlimitedProof);
+  });
+
+  it("opens deposits to anyone once the root is cleared", async () => {
+    await setAllowlistRoot(program, provider, initializer.payer, null);
+
+    await depositAs(outsider, null);
+  });
+});
@@ -0,0 +1,178 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  proposeAuthority,
+  acceptAuthority,
+  renounceAuthority,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("authority transfer (with hardcoded mints)", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const newAuthority = Keypair.generate();
+  const stranger = Keypair.generate();
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, newAuthority.publicKey, LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, stranger.publicKey, LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+  });
+
+  it("fails to propose an authority from a non-authority", async () => {
+    try {
+      await proposeAuthority(program, provider, stranger, stranger.publicKey);
+      assert.fail("propose should have failed for non-authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unauthorized)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (unauthorized)"
+      );
+    }
+  });
+
+  it("proposes a new authority without handing over control", async () => {
+    await proposeAuthority(
+      program,
+      provider,
+      initializer.payer,
+      newAuthority.publicKey
+    );
+
+    const configAccount = await program.account.config.fetch(configPda);
+    assert.ok(
+      configAccount.pendingAuthority.equals(newAuthority.publicKey),
+      "pending authority mismatch"
+    );
+    assert.ok(
+      configAccount.authority.equals(initializer.publicKey),
+      "authority should not change until accepted"
+    );
+  });
+
+  it("fails to accept from a key that was not proposed", async () => {
+    try {
+      await acceptAuthority(program, provider, stranger);
+      assert.fail("accept should have failed for a key that was not proposed");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (pending mismatch)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "PendingAuthorityMismatch",
+        "error code mismatch (pending mismatch)"
+      );
+    }
+  });
+
+  it("transfers config and treasury authority on accept", async () => {
+    await acceptAuthority(program, provider, newAuthority);
+
+    const configAccount = await program.account.config.fetch(configPda);
+    assert.ok(
+      configAccount.authority.equals(newAuthority.publicKey),
+      "config authority mismatch"
+    );
+    assert.isNull(
+      configAccount.pendingAuthority,
+      "pending authority should be cleared"
+    );
+    const treasuryAccount = await program.account.treasury.fetch(treasuryPda);
+    assert.ok(
+      treasuryAccount.authority.equals(newAuthority.publicKey),
+      "treasury authority mismatch"
+    );
+  });
+
+  it("only the new authority can update locks", async () => {
+    try {
+      await updateLocks(
+        program,
+        provider,
+        initializer.payer,
+        configPda,
+        null,
+        null,
+        null
+      );
+      assert.fail("update locks should have failed for the old authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unauthorized)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (unauthorized)"
+      );
+    }
+
+    await updateLocks(
+      program,
+      provider,
+      newAuthority,
+      configPda,
+      null,
+      null,
+      null
+    );
+  });
+
+  it("fails to renounce from a non-authority", async () => {
+    try {
+      await renounceAuthority(program, provider, initializer.payer);
+      assert.fail("renounce should have failed for the old authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unauthorized)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (unauthorized)"
+      );
+    }
+  });
+
+  after(async () => {
+    // hand authority back so other suites are unaffected
+    await proposeAuthority(
+      program,
+      provider,
+      newAuthority,
+      initializer.publicKey
+    );
+    await acceptAuthority(program, provider, initializer.payer);
+  });
+});
@@ -0,0 +1,373 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  createMint,
+  getAccount,
+  getAssociatedTokenAddress,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  expectError,
+  requestAirdrop,
+  updateLocks,
+  deposit,
+  depositSpl,
+  addTreasuryAsset,
+  initializeOption,
+  cancelDeposit,
+  findDurationTierPda,
+  findUserPositionPda,
+  THREE_MONTHS_SECONDS,
+  fetchCpiEvents,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("cancel deposit", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate(); // deposits and cancels
+  const priceAuthority = Keypair.generate(); // prices the mock USDC asset
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.5 * LAMPORTS_PER_SOL);
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+  let protocolPtAta: PublicKey;
+  let depositorCnAta: PublicKey;
+
+  // what a deposit is counted against, read back after it is cancelled
+  const depositCounters = async () => {
+    const config = await program.account.config.fetch(configPda);
+    const position = await program.account.userPosition.fetch(
+      findUserPositionPda(program, depositor.publicKey)
+    );
+    const tier = await program.account.durationTier.fetch(
+      findDurationTierPda(program, THREE_MONTHS_SECONDS)
+    );
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    return {
+      epochDeposited: config.epochDeposited.toString(),
+      totalDeposited: position.totalDeposited.toString(),
+      allowlistUsed: position.allowlistUsed.toString(),
+      totalIssued: tier.totalIssued.toString(),
+      backingSol: treasury.backingSol.toString(),
+    };
+  };
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+    protocolPtAta = await getAssociatedTokenAddress(ptMint, configPda, true);
+    depositorCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      depositor.publicKey,
+      true
+    );
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null,
+      false // set redeems unlocked
+    );
+  });
+
+  it("burns the deposit's CN and PT and refunds its SOL", async () => {
+    const { nonce, depositReceiptPda } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    const cnBefore = (await getAccount(provider.connection, depositorCnAta))
+      .amount;
+    const ptBefore = (await getAccount(provider.connection, protocolPtAta))
+      .amount;
+
+    const signature = await cancelDeposit(program, provider, depositor, nonce);
+
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "depositCancelled");
+    assert.ok(event, "DepositCancelled should be emitted");
+    assert.strictEqual(
+      event.data.cnBurned.toString(),
+      receipt.amount.toString(),
+      "should burn the CN the deposit minted"
+    );
+    assert.isTrue(
+      event.data.refund.lte(receipt.valueLamports),
+      "refund should never exceed the deposited value"
+    );
+    assert.isTrue(event.data.refund.gtn(0), "refund should be paid");
+
+    const cnAfter = (await getAccount(provider.connection, depositorCnAta))
+      .amount;
+    const ptAfter = (await getAccount(provider.connection, protocolPtAta))
+      .amount;
+    assert.strictEqual(
+      (cnBefore - cnAfter).toString(),
+      receipt.amount.toString(),
+      "depositor CN balance mismatch"
+    );
+    assert.strictEqual(
+      (ptBefore - ptAfter).toString(),
+      event.data.ptBurned.toString(),
+      "protocol PT balance mismatch"
+    );
+    assert.isNull(
+      await provider.connection.getAccountInfo(depositReceiptPda),
+      "receipt should be closed"
+    );
+  });
+
+  it("takes a cancelled deposit back off the deposit caps", async () => {
+    // the first deposit creates the user position the counters live on
+    const { nonce: firstNonce } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    await cancelDeposit(program, provider, depositor, firstNonce);
+    const before = await depositCounters();
+
+    const { nonce } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    const during = await depositCounters();
+    assert.notStrictEqual(
+      during.totalDeposited,
+      before.totalDeposited,
+      "deposit should be counted"
+    );
+
+    await cancelDeposit(program, provider, depositor, nonce);
+
+    assert.deepEqual(
+      await depositCounters(),
+      before,
+      "cancel should undo every counter the deposit moved"
+    );
+  });
+
+  it("refunds an SPL deposit in the deposited asset", async () => {
+    await requestAirdrop(provider, priceAuthority.publicKey, LAMPORTS_PER_SOL);
+    const usdcMint = await createMint(
+      provider.connection,
+      initializer.payer,
+      initializer.publicKey,
+      null,
+      6
+    );
+    const depositorUsdcAta = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        initializer.payer,
+        usdcMint,
+        depositor.publicKey
+      )
+    ).address;
+    await mintTo(
+      provider.connection,
+      initializer.payer,
+      usdcMint,
+      depositorUsdcAta,
+      initializer.publicKey,
+      100 * 10 ** 6
+    );
+    const { treasuryAssetPda, treasuryAssetVault } = await addTreasuryAsset(
+      program,
+      provider,
+      initializer.payer,
+      usdcMint,
+      priceAuthority.publicKey,
+      new anchor.BN(0.005 * LAMPORTS_PER_SOL), // 1 USDC = 0.005 SOL
+      new anchor.BN(60 * 60)
+    );
+
+    const usdcBefore = (await getAccount(provider.connection, depositorUsdcAta))
+      .amount;
+    const { nonce, depositReceiptPda } = await depositSpl(
+      program,
+      provider,
+      depositor,
+      usdcMint,
+      cnMint,
+      ptMint,
+      new anchor.BN(10 * 10 ** 6) // 10 USDC
+    );
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    assert.ok(receipt.assetMint.equals(usdcMint), "receipt asset mismatch");
+    const treasuryBefore = await program.account.treasury.fetch(treasuryPda);
+    const assetBefore = await program.account.treasuryAsset.fetch(
+      treasuryAssetPda
+    );
+    const vaultBefore = (
+      await getAccount(provider.connection, treasuryAssetVault)
+    ).amount;
+
+    const signature = await cancelDeposit(program, provider, depositor, nonce);
+
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "depositCancelled");
+    assert.ok(event, "DepositCancelled should be emitted");
+    assert.ok(event.data.mint.equals(usdcMint), "refund should be in USDC");
+    const refunded: anchor.BN = event.data.amountRefunded;
+    assert.isTrue(
+      refunded.lte(receipt.assetAmount) &&
+        refunded.addn(10).gte(receipt.assetAmount),
+      "refund should be the USDC that reached the vault, up to rounding"
+    );
+
+    const vaultAfter = (
+      await getAccount(provider.connection, treasuryAssetVault)
+    ).amount;
+    const usdcAfter = (await getAccount(provider.connection, depositorUsdcAta))
+      .amount;
+    assert.strictEqual(
+      (vaultBefore - vaultAfter).toString(),
+      refunded.toString(),
+      "vault balance mismatch"
+    );
+    assert.strictEqual(
+      (usdcAfter - usdcBefore).toString(),
+      refunded.sub(new anchor.BN(10 * 10 ** 6)).toString(),
+      "depositor should get the refund back on what they deposited"
+    );
+
+    const treasuryAfter = await program.account.treasury.fetch(treasuryPda);
+    const assetAfter = await program.account.treasuryAsset.fetch(
+      treasuryAssetPda
+    );
+    assert.strictEqual(
+      assetBefore.totalDeposited.sub(assetAfter.totalDeposited).toString(),
+      refunded.toString(),
+      "treasury asset balance should go down by the refund"
+    );
+    assert.strictEqual(
+      treasuryBefore.assetHoldingsSol
+        .sub(treasuryAfter.assetHoldingsSol)
+        .toString(),
+      assetBefore.valueLamports.sub(assetAfter.valueLamports).toString(),
+      "holdings should be re-valued"
+    );
+    assert.strictEqual(
+      treasuryAfter.totalDepositedSol.toString(),
+      treasuryBefore.totalDepositedSol.toString(),
+      "no SOL should be refunded"
+    );
+    assert.strictEqual(
+      treasuryBefore.backingSol.sub(treasuryAfter.backingSol).toString(),
+      receipt.valueLamports.toString(),
+      "the deposit's value should stop backing CN"
+    );
+  });
+
+  it("fails once the option is issued and the receipt closed", async () => {
+    const { nonce } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    await initializeOption(program, provider, depositor, nonce);
+
+    await expectError(
+      cancelDeposit(program, provider, depositor, nonce),
+      "AccountNotInitialized"
+    );
+  });
+
+  it("fails while redemptions are locked", async () => {
+    const { nonce } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      null,
+      null,
+      null,
+      true // lock redeems
+    );
+
+    try {
+      await expectError(
+        cancelDeposit(program, provider, depositor, nonce),
+        "RedemptionsLocked"
+      );
+    } finally {
+      await updateLocks(
+        program,
+        provider,
+        initializer.payer,
+        configPda,
+        null,
+        null,
+        null,
+        false
+      );
+    }
+  });
+});
@@ -0,0 +1,304 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { getAccount, getAssociatedTokenAddress } from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  expectError,
+  requestAirdrop,
+  updateLocks,
+  updateClaimWindow,
+  addDurationTier,
+  updateDurationTier,
+  findDurationTierPda,
+  deposit,
+  initializeOption,
+  expireDepositReceipt,
+  fetchCpiEvents,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+const SHORT_CLAIM_WINDOW_SECONDS = 2; // passes while the suite runs
+const DEFAULT_CLAIM_WINDOW_SECONDS = 7 * 24 * 60 * 60;
+const CAPPED_DURATION_SECONDS = 45 * 24 * 60 * 60; // filled by one deposit
+
+describe("claim window", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate(); // deposits without claiming
+  const cranker = Keypair.generate(); // expires receipts, no role needed
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+  let configPda: PublicKey;
+  let protocolPtAta: PublicKey;
+  let depositorCnAta: PublicKey;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, cranker.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    protocolPtAta = await getAssociatedTokenAddress(ptMint, configPda, true);
+    depositorCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      depositor.publicKey,
+      true
+    );
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null
+    );
+  });
+
+  after(async () => {
+    await updateClaimWindow(
+      program,
+      provider,
+      initializer.payer,
+      DEFAULT_CLAIM_WINDOW_SECONDS
+    );
+  });
+
+  it("fails to update the claim window from a non-authority", async () => {
+    await expectError(
+      updateClaimWindow(program, provider, cranker, SHORT_CLAIM_WINDOW_SECONDS),
+      "Unauthorized"
+    );
+  });
+
+  it("fails to set a claim window of zero", async () => {
+    await expectError(
+      updateClaimWindow(program, provider, initializer.payer, 0),
+      "InvalidClaimWindow"
+    );
+  });
+
+  it("sets the receipt's claim deadline from the claim window", async () => {
+    const signature = await updateClaimWindow(
+      program,
+      provider,
+      initializer.payer,
+      SHORT_CLAIM_WINDOW_SECONDS
+    );
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "claimWindowUpdated");
+    assert.ok(event, "ClaimWindowUpdated should be emitted");
+    assert.strictEqual(
+      event.data.claimWindow.toNumber(),
+      SHORT_CLAIM_WINDOW_SECONDS,
+      "event claim window mismatch"
+    );
+
+    const { depositReceiptPda } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    const now = Math.floor(Date.now() / 1000);
+    assert.approximately(
+      receipt.claimDeadline.toNumber(),
+      now + SHORT_CLAIM_WINDOW_SECONDS,
+      120,
+      "claim deadline should be one claim window out"
+    );
+  });
+
+  it("fails to expire a receipt that can still be claimed", async () => {
+    await updateClaimWindow(
+      program,
+      provider,
+      initializer.payer,
+      DEFAULT_CLAIM_WINDOW_SECONDS
+    );
+    const { nonce } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+
+    await expectError(
+      expireDepositReceipt(
+        program,
+        provider,
+        cranker,
+        depositor.publicKey,
+        nonce
+      ),
+      "ClaimWindowOpen"
+    );
+  });
+
+  it("rejects a late claim and lets anyone expire the receipt", async () => {
+    await updateClaimWindow(
+      program,
+      provider,
+      initializer.payer,
+      SHORT_CLAIM_WINDOW_SECONDS
+    );
+    const { nonce, depositReceiptPda } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    await new Promise((resolve) =>
+      setTimeout(resolve, (SHORT_CLAIM_WINDOW_SECONDS + 2) * 1000)
+    );
+
+    await expectError(
+      initializeOption(program, provider, depositor, nonce),
+      "DepositReceiptExpired"
+    );
+
+    const cnBefore = (await getAccount(provider.connection, depositorCnAta))
+      .amount;
+    const ptBefore = (await getAccount(provider.connection, protocolPtAta))
+      .amount;
+
+    const signature = await expireDepositReceipt(
+      program,
+      provider,
+      cranker,
+      depositor.publicKey,
+      nonce
+    );
+
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "depositReceiptExpired");
+    assert.ok(event, "DepositReceiptExpired should be emitted");
+    assert.strictEqual(
+      event.data.cnAmount.toString(),
+      receipt.amount.toString(),
+      "event CN amount mismatch"
+    );
+    assert.isTrue(
+      event.data.expiredBy.equals(cranker.publicKey),
+      "event expired by mismatch"
+    );
+
+    const cnAfter = (await getAccount(provider.connection, depositorCnAta))
+      .amount;
+    const ptAfter = (await getAccount(provider.connection, protocolPtAta))
+      .amount;
+    assert.strictEqual(
+      cnAfter.toString(),
+      cnBefore.toString(),
+      "depositor should keep the CN"
+    );
+    assert.strictEqual(
+      (ptBefore - ptAfter).toString(),
+      event.data.ptBurned.toString(),
+      "protocol PT balance mismatch"
+    );
+    assert.isNull(
+      await provider.connection.getAccountInfo(depositReceiptPda),
+      "receipt should be closed"
+    );
+  });
+
+  it("gives the tier capacity back when a receipt expires", async () => {
+    const tierPda = findDurationTierPda(program, CAPPED_DURATION_SECONDS);
+    if ((await provider.connection.getAccountInfo(tierPda)) === null) {
+      await addDurationTier(
+        program,
+        provider,
+        initializer.payer,
+        CAPPED_DURATION_SECONDS,
+        null
+      );
+    }
+    const depositCapped = () =>
+      deposit(
+        program,
+        provider,
+        depositor,
+        cnMint,
+        ptMint,
+        depositAmount,
+        protocolPtAta,
+        depositorCnAta,
+        CAPPED_DURATION_SECONDS
+      );
+
+    // the claim window is still the short one, so this receipt expires too
+    const { nonce, depositReceiptPda } = await depositCapped();
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    const filled = await program.account.durationTier.fetch(tierPda);
+    await updateDurationTier(
+      program,
+      provider,
+      initializer.payer,
+      CAPPED_DURATION_SECONDS,
+      null,
+      filled.totalIssued
+    );
+    await expectError(depositCapped(), "DurationTierCapExceeded");
+
+    await new Promise((resolve) =>
+      setTimeout(resolve, (SHORT_CLAIM_WINDOW_SECONDS + 2) * 1000)
+    );
+    await expireDepositReceipt(
+      program,
+      provider,
+      cranker,
+      depositor.publicKey,
+      nonce
+    );
+
+    const released = await program.account.durationTier.fetch(tierPda);
+    assert.strictEqual(
+      released.totalIssued.toString(),
+      filled.totalIssued.sub(receipt.amount).toString(),
+      "expired receipt should come off the tier's total issued"
+    );
+    await depositCapped();
+  });
+});
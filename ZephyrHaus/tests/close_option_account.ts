@@ -0,0 +1,167 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  expectError,
+  requestAirdrop,
+  updateLocks,
+  addDurationTier,
+  findDurationTierPda,
+  depositWithOption,
+  syncExpiredOptions,
+  closeOptionAccount,
+  fetchCpiEvents,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+const SHORT_DURATION_SECONDS = 2; // expires while the suite runs
+
+describe("close option account", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const holder = Keypair.generate(); // pays for the option, gets the rent back
+  const cranker = Keypair.generate(); // closes accounts it didn't pay for
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+  let configPda: PublicKey;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, holder.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, cranker.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      false // set converts unlocked
+    );
+
+    const shortTier = await provider.connection.getAccountInfo(
+      findDurationTierPda(program, SHORT_DURATION_SECONDS)
+    );
+    if (shortTier === null) {
+      await addDurationTier(
+        program,
+        provider,
+        initializer.payer,
+        SHORT_DURATION_SECONDS,
+        null
+      );
+    }
+  });
+
+  it("records the depositor as the option data's rent payer", async () => {
+    const option = await depositWithOption(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+    const optionData = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.isTrue(
+      optionData.rentPayer.equals(holder.publicKey),
+      "rent payer should be the depositor"
+    );
+
+    await expectError(
+      closeOptionAccount(
+        program,
+        provider,
+        cranker,
+        option.optionMint,
+        holder.publicKey
+      ),
+      "OptionNotFullyConverted"
+    );
+  });
+
+  it("lets anyone close a spent option for its rent payer", async () => {
+    const option = await depositWithOption(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      depositAmount,
+      SHORT_DURATION_SECONDS
+    );
+    await new Promise((resolve) =>
+      setTimeout(resolve, (SHORT_DURATION_SECONDS + 1) * 1000)
+    );
+    await syncExpiredOptions(program, provider, initializer.payer, ptMint, [
+      option.optionData,
+    ]);
+
+    await expectError(
+      closeOptionAccount(
+        program,
+        provider,
+        cranker,
+        option.optionMint,
+        cranker.publicKey
+      ),
+      "ReceiverRentPayerMismatch"
+    );
+
+    const rent = (await provider.connection.getAccountInfo(option.optionData))
+      .lamports;
+    const holderBefore = await provider.connection.getBalance(holder.publicKey);
+
+    const signature = await closeOptionAccount(
+      program,
+      provider,
+      cranker,
+      option.optionMint,
+      holder.publicKey
+    );
+
+    assert.isNull(
+      await provider.connection.getAccountInfo(option.optionData),
+      "option data should be closed"
+    );
+    const holderAfter = await provider.connection.getBalance(holder.publicKey);
+    assert.strictEqual(
+      holderAfter - holderBefore,
+      rent,
+      "rent should go back to the payer"
+    );
+
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "optionAccountClosed");
+    assert.ok(event, "OptionAccountClosed should be emitted");
+    assert.isTrue(
+      event.data.receiver.equals(holder.publicKey),
+      "event receiver mismatch"
+    );
+  });
+});
@@ -25,6 +25,8 @@ import {
   initializeOption,
   updateLocks,
   localSendAndConfirmTransaction,
+  findFeeVaultPda,
+  findEventAuthorityPda,
   debugEnableLogs,
 } from "./utils";
 
@@ -110,7 +112,7 @@ describe("convert instruction (with hardcoded mints)", () => {
       mint: cnMint,
       owner: converter.publicKey,
     });
-    await deposit(
+    const { nonce } = await deposit(
       program,
       provider,
       converter,
@@ -129,7 +131,7 @@ describe("convert instruction (with hardcoded mints)", () => {
       depositorOptionAta,
       optionMasterEdition,
       optionMetadataAccount,
-    } = await initializeOption(program, provider, converter);
+    } = await initializeOption(program, provider, converter, nonce);
     nftMint = optionMint;
     optionDataPda = optionData;
     nftMetadataPda = optionMetadataAccount;
@@ -182,10 +184,13 @@ describe("convert instruction (with hardcoded mints)", () => {
         converterOptionAta: converterOptionAta,
         config: configPda,
         protocolPtAta,
+        treasury: treasuryPda,
+        feeVault: findFeeVaultPda(program),
         cnMint,
         ptMint,
         nftMint: nftMint,
         optionData: optionDataPda,
+        optionRentPayer: converter.publicKey,
         nftMetadata: nftMetadataPda,
         nftMasterEdition: nftMasterEditionPda,
         collectionMetadata: collectionMetadataPda,
@@ -195,6 +200,8 @@ describe("convert instruction (with hardcoded mints)", () => {
         metadataProgram: TOKEN_METADATA_PROGRAM_ID,
         sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        eventAuthority: findEventAuthorityPda(program),
+        program: program.programId,
       })
       .signers([converter]) // only the converter needs to sign
       .transaction();
@@ -300,10 +307,13 @@ describe("convert instruction (with hardcoded mints)", () => {
         converterOptionAta: converterOptionAta,
         config: configPda,
         protocolPtAta,
+        treasury: treasuryPda,
+        feeVault: findFeeVaultPda(program),
         cnMint,
         ptMint,
         nftMint: nftMint,
         optionData: optionDataPda,
+        optionRentPayer: converter.publicKey,
         nftMetadata: nftMetadataPda,
         nftMasterEdition: nftMasterEditionPda,
         collectionMetadata: collectionMetadataPda,
@@ -313,6 +323,8 @@ describe("convert instruction (with hardcoded mints)", () => {
         metadataProgram: TOKEN_METADATA_PROGRAM_ID,
         sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        eventAuthority: findEventAuthorityPda(program),
+        program: program.programId,
       })
       .signers([converter]) // only the converter needs to sign
       .transaction();
@@ -352,10 +364,11 @@ describe("convert instruction (with hardcoded mints)", () => {
       "converter CN balance mismatch"
     );
 
-    // TODO: 3. OptionData decremented
+    // 3. OptionData closed, its rent returned to the depositor
     const optionDataInfo = await provider.connection.getAccountInfo(
       optionDataPda
     );
+    assert.isNull(optionDataInfo, "option data should be closed");
 
     // 4. TODO Check if NFT metadata decremented
 
@@ -519,6 +532,8 @@ describe("convert instruction (with hardcoded mints)", () => {
   // --- lock tests ---
   // note: these tests might need to perform deposit + initialize_option within each 'it' block
   // to ensure a valid option exists before attempting the locked conversion.
+  // the Token-2022 lock cases run live in token_2022.ts, which initializes the
+  // protocol with Token-2022 mints.
 
   // it("fails conversion when protocol is globally locked", async () => {
   //   console.log("testing global lock for conversion...");
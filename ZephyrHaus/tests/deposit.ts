@@ -13,6 +13,7 @@ import {
   updateLocks,
   deposit,
   initializeOption,
+  closeDepositReceipt,
   debugEnableLogs,
 } from "./utils";
 
@@ -98,7 +99,7 @@ describe("deposit instruction (with hardcoded mints)", () => {
 
     console.log("attempting deposit...");
     // execute deposit
-    await deposit(
+    const { nonce } = await deposit(
       program,
       provider,
       depositor,
@@ -165,10 +166,117 @@ describe("deposit instruction (with hardcoded mints)", () => {
     console.log("deposit state changes verified.");
 
     // 6. Initialize Option
-    await initializeOption(program, provider, depositor);
+    await initializeOption(program, provider, depositor, nonce);
     console.log("Option initialized successfully.");
   });
 
+  it("keys each deposit by nonce so a user can hold several", async () => {
+    const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+    // two deposits before claiming either option
+    const first = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    const second = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    assert.strictEqual(
+      second.nonce.toString(),
+      first.nonce.addn(1).toString(),
+      "deposit nonce should advance by one"
+    );
+    assert.ok(
+      !first.depositReceiptPda.equals(second.depositReceiptPda),
+      "each deposit should get its own receipt"
+    );
+
+    const firstReceipt = await program.account.depositReceipt.fetch(
+      first.depositReceiptPda
+    );
+    assert.strictEqual(
+      firstReceipt.nonce.toString(),
+      first.nonce.toString(),
+      "receipt nonce mismatch"
+    );
+
+    // each receipt mints its own option NFT
+    const firstOption = await initializeOption(
+      program,
+      provider,
+      depositor,
+      first.nonce
+    );
+    const secondOption = await initializeOption(
+      program,
+      provider,
+      depositor,
+      second.nonce
+    );
+    assert.ok(
+      !firstOption.optionMint.equals(secondOption.optionMint),
+      "each deposit should get its own option mint"
+    );
+    assert.ok(
+      !firstOption.optionData.equals(secondOption.optionData),
+      "each deposit should get its own option data"
+    );
+  });
+
+  it("closes the receipt once its option is issued", async () => {
+    const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    const { nonce, depositReceiptPda } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+
+    // a receipt that can still be claimed can't be closed
+    try {
+      await closeDepositReceipt(
+        program,
+        provider,
+        initializer.payer,
+        depositor.publicKey,
+        nonce
+      );
+      assert.fail("close should have failed for an unclaimed receipt");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unclaimed receipt)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "OptionNotIssued",
+        "error code mismatch (unclaimed receipt)"
+      );
+    }
+
+    await initializeOption(program, provider, depositor, nonce);
+
+    assert.isNull(
+      await provider.connection.getAccountInfo(depositReceiptPda),
+      "claimed receipt should be closed"
+    );
+  });
+
   // --- lock tests (using hardcoded mints and utils) ---
 
   it("fails deposit when protocol is globally locked", async () => {
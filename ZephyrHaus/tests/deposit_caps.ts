@@ -0,0 +1,218 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddress } from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  updateDepositCaps,
+  deposit,
+  fetchCpiEvents,
+  findUserPositionPda,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("deposit caps", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate(); // create a new depositor for tests
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+  const noCap = new anchor.BN(0); // a cap of 0 removes it
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+  let userPositionPda: PublicKey;
+
+  // deposits `depositAmount` and expects it to fail with `code`
+  const expectDepositError = async (code: string) => {
+    try {
+      await deposit(
+        program,
+        provider,
+        depositor,
+        cnMint,
+        ptMint,
+        depositAmount,
+        await getAssociatedTokenAddress(ptMint, configPda, true),
+        await getAssociatedTokenAddress(cnMint, depositor.publicKey, true)
+      );
+      assert.fail(`deposit should have failed with ${code}`);
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, `should be an AnchorError (${code})`);
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        code,
+        `error code mismatch (${code})`
+      );
+    }
+  };
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+    userPositionPda = findUserPositionPda(program, depositor.publicKey);
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null
+    );
+  });
+
+  afterEach(async () => {
+    // later suites share the validator, so every cap is removed again
+    await updateDepositCaps(
+      program,
+      provider,
+      initializer.payer,
+      noCap,
+      noCap,
+      noCap
+    );
+  });
+
+  it("tracks the depositor's total in their user position", async () => {
+    const { signature } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      await getAssociatedTokenAddress(ptMint, configPda, true),
+      await getAssociatedTokenAddress(cnMint, depositor.publicKey, true)
+    );
+
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "deposited");
+    assert.ok(event, "Deposited should be emitted");
+
+    const position = await program.account.userPosition.fetch(userPositionPda);
+    assert.isTrue(
+      position.owner.equals(depositor.publicKey),
+      "position owner mismatch"
+    );
+    assert.strictEqual(
+      position.totalDeposited.toString(),
+      event.data.valueLamports.toString(),
+      "position should count the deposit after fees"
+    );
+  });
+
+  it("fails to update the caps from a non-authority", async () => {
+    try {
+      await updateDepositCaps(
+        program,
+        provider,
+        depositor,
+        null,
+        depositAmount,
+        null
+      );
+      assert.fail("update caps should have failed for non-authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unauthorized)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (unauthorized)"
+      );
+    }
+  });
+
+  it("fails to deposit over the total deposit cap", async () => {
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    await updateDepositCaps(
+      program,
+      provider,
+      initializer.payer,
+      treasury.totalDepositedSol.add(treasury.assetHoldingsSol),
+      null,
+      null
+    );
+
+    await expectDepositError("TotalDepositCapExceeded");
+  });
+
+  it("fails to deposit over the per-wallet cap", async () => {
+    const position = await program.account.userPosition.fetch(userPositionPda);
+    await updateDepositCaps(
+      program,
+      provider,
+      initializer.payer,
+      null,
+      position.totalDeposited.addn(1),
+      null
+    );
+
+    await expectDepositError("WalletDepositCapExceeded");
+  });
+
+  it("fails to deposit over the per-epoch limit", async () => {
+    await updateDepositCaps(
+      program,
+      provider,
+      initializer.payer,
+      null,
+      null,
+      new anchor.BN(1)
+    );
+
+    await expectDepositError("EpochDepositLimitExceeded");
+  });
+
+  it("deposits again once the caps are removed", async () => {
+    const positionBefore = await program.account.userPosition.fetch(
+      userPositionPda
+    );
+
+    await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      await getAssociatedTokenAddress(ptMint, configPda, true),
+      await getAssociatedTokenAddress(cnMint, depositor.publicKey, true)
+    );
+
+    const positionAfter = await program.account.userPosition.fetch(
+      userPositionPda
+    );
+    assert.isTrue(
+      positionAfter.totalDeposited.gt(positionBefore.totalDeposited),
+      "position total should grow"
+    );
+  });
+});
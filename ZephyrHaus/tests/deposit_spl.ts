@@ -0,0 +1,460 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  sendAndConfirmTransaction,
+} from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddress,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  addTreasuryAsset,
+  updateTreasuryAsset,
+  setAssetPrice,
+  depositSpl,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("deposit_spl instruction (with a mock USDC mint)", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate(); // create a new depositor for tests
+  const secondDepositor = Keypair.generate();
+  const priceAuthority = Keypair.generate();
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const usdcDecimals = 6;
+  const priceLamports = new anchor.BN(0.005 * LAMPORTS_PER_SOL); // 1 USDC = 0.005 SOL
+  const maxPriceAge = new anchor.BN(60 * 60); // 1 hour
+  const depositAmount = new anchor.BN(10 * 10 ** usdcDecimals); // 10 USDC
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+  let usdcMint: PublicKey;
+  let treasuryAssetPda: PublicKey;
+  let treasuryAssetVault: PublicKey;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(
+      provider,
+      secondDepositor.publicKey,
+      2 * LAMPORTS_PER_SOL
+    );
+    await requestAirdrop(provider, priceAuthority.publicKey, LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null,
+      null
+    );
+
+    // mock USDC mint, funded to both depositors
+    usdcMint = await createMint(
+      provider.connection,
+      initializer.payer,
+      initializer.publicKey,
+      null,
+      usdcDecimals
+    );
+    for (const user of [depositor, secondDepositor]) {
+      const ata = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        initializer.payer,
+        usdcMint,
+        user.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        initializer.payer,
+        usdcMint,
+        ata.address,
+        initializer.publicKey,
+        100 * 10 ** usdcDecimals
+      );
+    }
+
+    const assetResult = await addTreasuryAsset(
+      program,
+      provider,
+      initializer.payer,
+      usdcMint,
+      priceAuthority.publicKey,
+      priceLamports,
+      maxPriceAge
+    );
+    treasuryAssetPda = assetResult.treasuryAssetPda;
+    treasuryAssetVault = assetResult.treasuryAssetVault;
+  });
+
+  it("whitelists the asset with its price", async () => {
+    const treasuryAsset = await program.account.treasuryAsset.fetch(
+      treasuryAssetPda
+    );
+    assert.ok(treasuryAsset.mint.equals(usdcMint), "asset mint mismatch");
+    assert.ok(treasuryAsset.vault.equals(treasuryAssetVault), "vault mismatch");
+    assert.ok(
+      treasuryAsset.priceAuthority.equals(priceAuthority.publicKey),
+      "price authority mismatch"
+    );
+    assert.strictEqual(
+      treasuryAsset.priceLamports.toString(),
+      priceLamports.toString(),
+      "price mismatch"
+    );
+    assert.isTrue(treasuryAsset.enabled, "asset should be enabled");
+  });
+
+  it("deposits USDC and mints CN valued in SOL at NAV", async () => {
+    const depositorCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      depositor.publicKey,
+      true
+    );
+    const initialTreasury = await program.account.treasury.fetch(treasuryPda);
+
+    await depositSpl(
+      program,
+      provider,
+      depositor,
+      usdcMint,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+
+    // 10 USDC at 0.005 SOL each
+    const expectedValue = depositAmount
+      .mul(priceLamports)
+      .div(new anchor.BN(10 ** usdcDecimals));
+
+    const vault = await getAccount(provider.connection, treasuryAssetVault);
+    assert.strictEqual(
+      vault.amount.toString(),
+      depositAmount.toString(),
+      "vault balance mismatch"
+    );
+
+    const treasuryAsset = await program.account.treasuryAsset.fetch(
+      treasuryAssetPda
+    );
+    assert.strictEqual(
+      treasuryAsset.totalDeposited.toString(),
+      depositAmount.toString(),
+      "asset total deposited mismatch"
+    );
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    assert.strictEqual(
+      treasury.assetHoldingsSol.toString(),
+      initialTreasury.assetHoldingsSol.add(expectedValue).toString(),
+      "treasury asset holdings mismatch"
+    );
+
+    // tokens = value * NAV_PRECISION / nav, rounded down
+    const expectedTokens = expectedValue
+      .mul(new anchor.BN(1_000_000_000))
+      .div(treasury.nav);
+    const cnAccount = await getAccount(provider.connection, depositorCnAta);
+    assert.strictEqual(
+      cnAccount.amount.toString(),
+      expectedTokens.toString(),
+      "depositor CN balance mismatch"
+    );
+  });
+
+  it("fails to set the price from a non price authority", async () => {
+    try {
+      await setAssetPrice(
+        program,
+        provider,
+        secondDepositor,
+        usdcMint,
+        priceLamports
+      );
+      assert.fail("set price should have failed for non price authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (price authority)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "PriceAuthorityMismatch",
+        "error code mismatch (price authority)"
+      );
+    }
+  });
+
+  it("updates the price from the price authority", async () => {
+    const newPrice = new anchor.BN(0.006 * LAMPORTS_PER_SOL);
+    const initialAsset = await program.account.treasuryAsset.fetch(
+      treasuryAssetPda
+    );
+    const initialTreasury = await program.account.treasury.fetch(treasuryPda);
+
+    await setAssetPrice(program, provider, priceAuthority, usdcMint, newPrice);
+
+    const treasuryAsset = await program.account.treasuryAsset.fetch(
+      treasuryAssetPda
+    );
+    assert.strictEqual(
+      treasuryAsset.priceLamports.toString(),
+      newPrice.toString(),
+      "price mismatch after update"
+    );
+
+    // the held USDC is re-valued at the new price
+    const expectedValue = treasuryAsset.totalDeposited
+      .mul(newPrice)
+      .div(new anchor.BN(10 ** usdcDecimals));
+    assert.strictEqual(
+      treasuryAsset.valueLamports.toString(),
+      expectedValue.toString(),
+      "asset value should follow the price"
+    );
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    assert.strictEqual(
+      treasury.assetHoldingsSol.toString(),
+      initialTreasury.assetHoldingsSol
+        .sub(initialAsset.valueLamports)
+        .add(expectedValue)
+        .toString(),
+      "treasury asset holdings should be re-valued"
+    );
+  });
+
+  it("fails to deposit a disabled asset", async () => {
+    await updateTreasuryAsset(
+      program,
+      provider,
+      initializer.payer,
+      usdcMint,
+      false
+    );
+
+    try {
+      await depositSpl(
+        program,
+        provider,
+        secondDepositor,
+        usdcMint,
+        cnMint,
+        ptMint,
+        depositAmount
+      );
+      assert.fail("deposit should have failed for a disabled asset");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (disabled asset)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "AssetDepositsDisabled",
+        "error code mismatch (disabled asset)"
+      );
+    }
+  });
+
+  it("fails to add a treasury asset from a non-authority", async () => {
+    const otherMint = await createMint(
+      provider.connection,
+      initializer.payer,
+      initializer.publicKey,
+      null,
+      usdcDecimals
+    );
+
+    try {
+      await addTreasuryAsset(
+        program,
+        provider,
+        secondDepositor,
+        otherMint,
+        priceAuthority.publicKey,
+        priceLamports,
+        maxPriceAge
+      );
+      assert.fail("add treasury asset should have failed for non-authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unauthorized)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (unauthorized)"
+      );
+    }
+  });
+
+  it("deposits a Token-2022 asset net of its transfer fee", async () => {
+    // mock asset whose transfers withhold 1%
+    const feeBps = 100;
+    const feeMintKeypair = Keypair.generate();
+    const feeMint = feeMintKeypair.publicKey;
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const mintRent =
+      await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+    const createMintTx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: initializer.publicKey,
+        newAccountPubkey: feeMint,
+        space: mintLen,
+        lamports: mintRent,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeTransferFeeConfigInstruction(
+        feeMint,
+        initializer.publicKey,
+        initializer.publicKey,
+        feeBps,
+        BigInt(10 ** usdcDecimals), // max fee of 1 token
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createInitializeMintInstruction(
+        feeMint,
+        usdcDecimals,
+        initializer.publicKey,
+        null,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+    await sendAndConfirmTransaction(provider.connection, createMintTx, [
+      initializer.payer,
+      feeMintKeypair,
+    ]);
+    const depositorFeeAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      initializer.payer,
+      feeMint,
+      secondDepositor.publicKey,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      initializer.payer,
+      feeMint,
+      depositorFeeAta.address,
+      initializer.publicKey,
+      100 * 10 ** usdcDecimals,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const feeAsset = await addTreasuryAsset(
+      program,
+      provider,
+      initializer.payer,
+      feeMint,
+      priceAuthority.publicKey,
+      priceLamports,
+      maxPriceAge,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const initialTreasury = await program.account.treasury.fetch(treasuryPda);
+
+    await depositSpl(
+      program,
+      provider,
+      secondDepositor,
+      feeMint,
+      cnMint,
+      ptMint,
+      depositAmount,
+      undefined,
+      null,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    // only what reaches the vault is credited and valued
+    const transferFee = depositAmount
+      .mul(new anchor.BN(feeBps))
+      .add(new anchor.BN(9_999))
+      .div(new anchor.BN(10_000)); // Token-2022 rounds the fee up
+    const received = depositAmount.sub(transferFee);
+    const vault = await getAccount(
+      provider.connection,
+      feeAsset.treasuryAssetVault,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    assert.strictEqual(
+      vault.amount.toString(),
+      received.toString(),
+      "vault should receive the deposit less the transfer fee"
+    );
+
+    const treasuryAsset = await program.account.treasuryAsset.fetch(
+      feeAsset.treasuryAssetPda
+    );
+    assert.strictEqual(
+      treasuryAsset.totalDeposited.toString(),
+      vault.amount.toString(),
+      "asset total deposited should match the vault"
+    );
+
+    const expectedValue = received
+      .mul(priceLamports)
+      .div(new anchor.BN(10 ** usdcDecimals));
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    assert.strictEqual(
+      treasury.assetHoldingsSol.toString(),
+      initialTreasury.assetHoldingsSol.add(expectedValue).toString(),
+      "treasury asset holdings should value the amount received"
+    );
+  });
+
+  after(async () => {
+    await updateTreasuryAsset(
+      program,
+      provider,
+      initializer.payer,
+      usdcMint,
+      true
+    );
+  });
+});
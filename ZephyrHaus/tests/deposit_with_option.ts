@@ -0,0 +1,228 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { getAccount, getAssociatedTokenAddress } from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  depositWithOption,
+  findDepositReceiptPda,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("deposit_with_option instruction (with hardcoded mints)", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate(); // create a new depositor for tests
+  const lockedDepositor = Keypair.generate();
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.5 * LAMPORTS_PER_SOL); // 0.5 SOL
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(
+      provider,
+      lockedDepositor.publicKey,
+      2 * LAMPORTS_PER_SOL
+    );
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null,
+      null
+    );
+  });
+
+  it("deposits and issues the option NFT in one instruction", async () => {
+    const initialConfig = await program.account.config.fetch(configPda);
+    const initialTreasuryBalance = await provider.connection.getBalance(
+      treasuryPda
+    );
+
+    const result = await depositWithOption(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+
+    const finalTreasuryBalance = await provider.connection.getBalance(
+      treasuryPda
+    );
+    assert.strictEqual(
+      finalTreasuryBalance,
+      initialTreasuryBalance + depositAmount.toNumber(),
+      "treasury balance mismatch"
+    );
+
+    const depositorCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      depositor.publicKey,
+      true
+    );
+    const cnAccount = await getAccount(provider.connection, depositorCnAta);
+    assert.isTrue(cnAccount.amount > BigInt(0), "depositor should receive CN");
+
+    const optionAccount = await getAccount(
+      provider.connection,
+      result.depositorOptionAta
+    );
+    assert.strictEqual(
+      optionAccount.amount.toString(),
+      "1",
+      "depositor should hold the option NFT"
+    );
+
+    const optionData = await program.account.optionData.fetch(
+      result.optionData
+    );
+    assert.ok(
+      optionData.mint.equals(result.optionMint),
+      "option mint mismatch"
+    );
+    assert.strictEqual(
+      optionData.amount.toString(),
+      cnAccount.amount.toString(),
+      "option amount should match the CN minted"
+    );
+    assert.isFalse(optionData.expired, "option should not be expired");
+
+    const finalConfig = await program.account.config.fetch(configPda);
+    assert.strictEqual(
+      finalConfig.optionCount.toString(),
+      initialConfig.optionCount.addn(1).toString(),
+      "option count mismatch"
+    );
+    assert.strictEqual(
+      finalConfig.totalOptionAmount.toString(),
+      initialConfig.totalOptionAmount.add(optionData.amount).toString(),
+      "total option amount mismatch"
+    );
+
+    // no receipt is left behind to be claimed
+    const depositReceiptPda = findDepositReceiptPda(
+      program,
+      depositor.publicKey,
+      result.nonce
+    );
+    const receiptInfo = await provider.connection.getAccountInfo(
+      depositReceiptPda
+    );
+    assert.isNull(receiptInfo, "no deposit receipt should be created");
+  });
+
+  it("issues a separate option for each deposit by the same user", async () => {
+    const first = await depositWithOption(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+    const second = await depositWithOption(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+
+    assert.ok(
+      !first.optionMint.equals(second.optionMint),
+      "each deposit should get its own option mint"
+    );
+    for (const result of [first, second]) {
+      const optionAccount = await getAccount(
+        provider.connection,
+        result.depositorOptionAta
+      );
+      assert.strictEqual(
+        optionAccount.amount.toString(),
+        "1",
+        "depositor should hold each option NFT"
+      );
+    }
+  });
+
+  it("fails when deposits are locked", async () => {
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false,
+      true, // set deposits locked
+      null,
+      null
+    );
+
+    try {
+      await depositWithOption(
+        program,
+        provider,
+        lockedDepositor,
+        cnMint,
+        ptMint,
+        depositAmount
+      );
+      assert.fail("deposit should have failed due to deposit lock");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (deposit lock)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "DepositsLocked",
+        "error code mismatch (deposit lock)"
+      );
+    }
+  });
+
+  after(async () => {
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false,
+      false,
+      null,
+      null
+    );
+  });
+});
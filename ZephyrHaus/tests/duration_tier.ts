@@ -0,0 +1,336 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { getAccount, getAssociatedTokenAddress } from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  DEFAULT_DURATION_TIERS,
+  initializeProtocol,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  deposit,
+  depositWithOption,
+  convert,
+  findDurationTierPda,
+  addDurationTier,
+  updateDurationTier,
+  retireDurationTier,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("duration tiers", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate(); // create a new depositor for tests
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const oneMonth = 30 * 24 * 60 * 60; // custom tier added by these tests
+  const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+  let configPda: PublicKey;
+  let protocolPtAta: PublicKey;
+  let depositorCnAta: PublicKey;
+  let oneMonthTierPda: PublicKey;
+  let oneMonthReceiptPda: PublicKey;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    protocolPtAta = await getAssociatedTokenAddress(ptMint, configPda, true);
+    depositorCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      depositor.publicKey,
+      true
+    );
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null,
+      null
+    );
+  });
+
+  it("has the default tiers enabled and uncapped", async () => {
+    for (const expected of DEFAULT_DURATION_TIERS) {
+      const tier = await program.account.durationTier.fetch(
+        findDurationTierPda(program, expected.duration)
+      );
+      assert.strictEqual(
+        tier.duration,
+        expected.duration,
+        "tier duration mismatch"
+      );
+      assert.isTrue(tier.enabled, "default tier should be enabled");
+      assert.isNull(tier.cap, "default tier should be uncapped");
+      assert.strictEqual(
+        tier.conversionMultiplierBps,
+        expected.conversionMultiplierBps,
+        "tier conversion multiplier mismatch"
+      );
+    }
+  });
+
+  it("scales PT by the tier's conversion multiplier", async () => {
+    // longest default tier pays out the most PT per CN
+    const { duration, conversionMultiplierBps } =
+      DEFAULT_DURATION_TIERS[DEFAULT_DURATION_TIERS.length - 1];
+    const protocolPtBefore = (
+      await getAccount(provider.connection, protocolPtAta)
+    ).amount;
+
+    const option = await depositWithOption(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      duration
+    );
+
+    const optionData = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.strictEqual(
+      optionData.conversionMultiplierBps,
+      conversionMultiplierBps,
+      "option should record the tier's multiplier"
+    );
+
+    // PT backing the option is minted rounded up
+    const cnAmount = BigInt(optionData.amount.toString());
+    const bps = BigInt(conversionMultiplierBps);
+    const expectedPtMinted = (cnAmount * bps + BigInt(9_999)) / BigInt(10_000);
+    const protocolPtAfter = (
+      await getAccount(provider.connection, protocolPtAta)
+    ).amount;
+    assert.strictEqual(
+      (protocolPtAfter - protocolPtBefore).toString(),
+      expectedPtMinted.toString(),
+      "protocol PT should be scaled by the multiplier"
+    );
+
+    // converting pays out PT scaled by the multiplier, rounded down
+    const depositorPtAta = await getAssociatedTokenAddress(
+      ptMint,
+      depositor.publicKey,
+      true
+    );
+    let depositorPtBefore = BigInt(0);
+    try {
+      const ptAccount = await getAccount(provider.connection, depositorPtAta);
+      depositorPtBefore = ptAccount.amount;
+    } catch (e) {
+      /* ATA doesn't exist yet */
+    }
+    await convert(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      option,
+      optionData.amount
+    );
+    const depositorPtAfter = (
+      await getAccount(provider.connection, depositorPtAta)
+    ).amount;
+    assert.strictEqual(
+      (depositorPtAfter - depositorPtBefore).toString(),
+      ((cnAmount * bps) / BigInt(10_000)).toString(),
+      "converted PT should be scaled by the multiplier"
+    );
+  });
+
+  it("fails to add a tier from a non-authority", async () => {
+    try {
+      await addDurationTier(program, provider, depositor, oneMonth, null);
+      assert.fail("add duration tier should have failed for non-authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unauthorized)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (unauthorized)"
+      );
+    }
+  });
+
+  it("adds a tier and issues deposits against it", async () => {
+    oneMonthTierPda = await addDurationTier(
+      program,
+      provider,
+      initializer.payer,
+      oneMonth,
+      null
+    );
+
+    const { depositReceiptPda } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta,
+      oneMonth
+    );
+
+    oneMonthReceiptPda = depositReceiptPda;
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    const tier = await program.account.durationTier.fetch(oneMonthTierPda);
+    assert.strictEqual(
+      tier.totalIssued.toString(),
+      receipt.amount.toString(),
+      "tier total issued should match the receipt amount"
+    );
+
+    // the option duration comes from the tier, expiration is set at mint
+    assert.strictEqual(
+      receipt.optionDuration,
+      oneMonth,
+      "receipt option duration should be one month"
+    );
+  });
+
+  it("fails to deposit over the tier cap", async () => {
+    const tier = await program.account.durationTier.fetch(oneMonthTierPda);
+    // leave room for a single token only
+    await updateDurationTier(
+      program,
+      provider,
+      initializer.payer,
+      oneMonth,
+      null,
+      tier.totalIssued.addn(1)
+    );
+
+    try {
+      await deposit(
+        program,
+        provider,
+        depositor,
+        cnMint,
+        ptMint,
+        depositAmount,
+        protocolPtAta,
+        depositorCnAta,
+        oneMonth
+      );
+      assert.fail("deposit should have failed over the tier cap");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (tier cap)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "DurationTierCapExceeded",
+        "error code mismatch (tier cap)"
+      );
+    }
+  });
+
+  it("fails to deposit into a disabled tier", async () => {
+    await updateDurationTier(
+      program,
+      provider,
+      initializer.payer,
+      oneMonth,
+      false,
+      new anchor.BN(0) // remove the cap
+    );
+    const tier = await program.account.durationTier.fetch(oneMonthTierPda);
+    assert.isFalse(tier.enabled, "tier should be disabled");
+    assert.isNull(tier.cap, "tier cap should be removed");
+
+    try {
+      await deposit(
+        program,
+        provider,
+        depositor,
+        cnMint,
+        ptMint,
+        depositAmount,
+        protocolPtAta,
+        depositorCnAta,
+        oneMonth
+      );
+      assert.fail("deposit should have failed for a disabled tier");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (disabled tier)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "DurationTierDisabled",
+        "error code mismatch (disabled tier)"
+      );
+    }
+  });
+
+  it("retires a tier without touching issued receipts", async () => {
+    await retireDurationTier(program, provider, initializer.payer, oneMonth);
+
+    const tierInfo = await provider.connection.getAccountInfo(oneMonthTierPda);
+    assert.isNull(tierInfo, "retired tier should be closed");
+
+    // the receipt issued in the tier keeps its terms
+    const receipt = await program.account.depositReceipt.fetch(
+      oneMonthReceiptPda
+    );
+    assert.strictEqual(
+      receipt.optionDuration,
+      oneMonth,
+      "issued receipt should keep its option duration"
+    );
+
+    try {
+      await deposit(
+        program,
+        provider,
+        depositor,
+        cnMint,
+        ptMint,
+        depositAmount,
+        protocolPtAta,
+        depositorCnAta,
+        oneMonth
+      );
+      assert.fail("deposit should have failed for a retired tier");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (retired tier)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "AccountNotInitialized",
+        "error code mismatch (retired tier)"
+      );
+    }
+  });
+});
@@ -0,0 +1,144 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddress } from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  THREE_MONTHS_SECONDS,
+  initializeProtocol,
+  requestAirdrop,
+  updateLocks,
+  deposit,
+  fetchCpiEvents,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("events", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate(); // create a new depositor for tests
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+  });
+
+  it("emits LocksUpdated with the resulting locks", async () => {
+    const signature = await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null
+    );
+
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "locksUpdated");
+    assert.ok(event, "LocksUpdated should be emitted");
+
+    const config = await program.account.config.fetch(configPda);
+    assert.isTrue(
+      event.data.authority.equals(initializer.publicKey),
+      "event authority mismatch"
+    );
+    assert.isFalse(event.data.locked, "event global lock mismatch");
+    assert.isFalse(event.data.depositLocked, "event deposit lock mismatch");
+    assert.strictEqual(
+      event.data.convertLocked,
+      config.convertLocked,
+      "event convert lock mismatch"
+    );
+    assert.strictEqual(
+      event.data.redeemLocked,
+      config.redeemLocked,
+      "event redeem lock mismatch"
+    );
+  });
+
+  it("emits Deposited with the minted amounts and NAV", async () => {
+    const { depositReceiptPda, nonce, signature } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      await getAssociatedTokenAddress(ptMint, configPda, true),
+      await getAssociatedTokenAddress(cnMint, depositor.publicKey, true)
+    );
+
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "deposited");
+    assert.ok(event, "Deposited should be emitted");
+
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    assert.isTrue(
+      event.data.depositor.equals(depositor.publicKey),
+      "event depositor mismatch"
+    );
+    assert.isNull(event.data.mint, "SOL deposits have no mint");
+    assert.isTrue(
+      event.data.amount.lte(depositAmount),
+      "event amount should be net of fees"
+    );
+    assert.strictEqual(
+      event.data.nonce.toString(),
+      nonce.toString(),
+      "event nonce mismatch"
+    );
+    assert.strictEqual(
+      event.data.cnMinted.toString(),
+      receipt.amount.toString(),
+      "event CN minted mismatch"
+    );
+    assert.strictEqual(
+      event.data.nav.toString(),
+      treasury.nav.toString(),
+      "event NAV mismatch"
+    );
+    assert.strictEqual(
+      event.data.optionDuration,
+      THREE_MONTHS_SECONDS,
+      "event option duration mismatch"
+    );
+    assert.isNull(
+      event.data.expiration,
+      "expiration is only set once the option is issued"
+    );
+    assert.strictEqual(
+      event.data.claimDeadline.toString(),
+      receipt.claimDeadline.toString(),
+      "event claim deadline mismatch"
+    );
+  });
+});
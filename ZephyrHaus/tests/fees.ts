@@ -0,0 +1,235 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { getAccount, getAssociatedTokenAddress } from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  updateFees,
+  claimFees,
+  deposit,
+  findFeeVaultPda,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("protocol fees (with hardcoded mints)", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate(); // create a new depositor for tests
+  const feeRecipient = Keypair.generate();
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const feeBps = 100; // 1%
+  const depositAmount = new anchor.BN(0.5 * LAMPORTS_PER_SOL); // 0.5 SOL
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+  let feeVaultPda: PublicKey;
+  let protocolPtAta: PublicKey;
+  let depositorCnAta: PublicKey;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+    feeVaultPda = findFeeVaultPda(program);
+    protocolPtAta = await getAssociatedTokenAddress(ptMint, configPda, true);
+    depositorCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      depositor.publicKey,
+      true
+    );
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null,
+      null
+    );
+  });
+
+  it("updates the fees and fee recipient", async () => {
+    await updateFees(
+      program,
+      provider,
+      initializer.payer,
+      feeBps,
+      feeBps,
+      feeRecipient.publicKey
+    );
+
+    const configAccount = await program.account.config.fetch(configPda);
+    assert.strictEqual(configAccount.fee, feeBps, "deposit fee mismatch");
+    assert.strictEqual(
+      configAccount.conversionFee,
+      feeBps,
+      "conversion fee mismatch"
+    );
+    assert.ok(
+      configAccount.feeRecipient.equals(feeRecipient.publicKey),
+      "fee recipient mismatch"
+    );
+  });
+
+  it("charges the deposit fee into the fee vault", async () => {
+    const initialFeeVaultBalance = await provider.connection.getBalance(
+      feeVaultPda
+    );
+    const initialTreasuryBalance = await provider.connection.getBalance(
+      treasuryPda
+    );
+
+    await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+
+    const expectedFee = depositAmount
+      .muln(feeBps)
+      .addn(9_999)
+      .divn(10_000)
+      .toNumber(); // rounded up
+    const netAmount = depositAmount.toNumber() - expectedFee;
+
+    const finalFeeVaultBalance = await provider.connection.getBalance(
+      feeVaultPda
+    );
+    assert.strictEqual(
+      finalFeeVaultBalance,
+      initialFeeVaultBalance + expectedFee,
+      "fee vault balance mismatch"
+    );
+    const finalTreasuryBalance = await provider.connection.getBalance(
+      treasuryPda
+    );
+    assert.strictEqual(
+      finalTreasuryBalance,
+      initialTreasuryBalance + netAmount,
+      "treasury should only receive the deposit net of fees"
+    );
+
+    // CN is minted on the net amount
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    const expectedTokens = new anchor.BN(netAmount)
+      .mul(new anchor.BN(1_000_000_000))
+      .div(treasury.nav);
+    const cnAccount = await getAccount(provider.connection, depositorCnAta);
+    assert.strictEqual(
+      cnAccount.amount.toString(),
+      expectedTokens.toString(),
+      "depositor CN balance mismatch"
+    );
+
+    const feeVault = await program.account.feeVault.fetch(feeVaultPda);
+    assert.isTrue(
+      feeVault.totalCollectedSol.gten(expectedFee),
+      "fee vault should track collected fees"
+    );
+  });
+
+  it("fails to set a fee above the maximum", async () => {
+    try {
+      await updateFees(program, provider, initializer.payer, 1_001, null);
+      assert.fail("update fees should have failed with a fee above the max");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (fee too high)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "FeeTooHigh",
+        "error code mismatch (fee too high)"
+      );
+    }
+  });
+
+  it("fails to claim fees from a non-authority", async () => {
+    try {
+      await claimFees(program, provider, depositor, feeRecipient.publicKey);
+      assert.fail("claim fees should have failed for non-authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unauthorized)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (unauthorized)"
+      );
+    }
+  });
+
+  it("claims the collected fees to the fee recipient", async () => {
+    const feeVaultInfo = await provider.connection.getAccountInfo(feeVaultPda);
+    const rentExemptMinimum =
+      await provider.connection.getMinimumBalanceForRentExemption(
+        feeVaultInfo.data.length
+      );
+    const claimable = feeVaultInfo.lamports - rentExemptMinimum;
+
+    await claimFees(
+      program,
+      provider,
+      initializer.payer,
+      feeRecipient.publicKey
+    );
+
+    const recipientBalance = await provider.connection.getBalance(
+      feeRecipient.publicKey
+    );
+    assert.strictEqual(
+      recipientBalance,
+      claimable,
+      "fee recipient should receive all claimable fees"
+    );
+    const finalFeeVaultBalance = await provider.connection.getBalance(
+      feeVaultPda
+    );
+    assert.strictEqual(
+      finalFeeVaultBalance,
+      rentExemptMinimum,
+      "fee vault should keep only its rent"
+    );
+  });
+
+  after(async () => {
+    // disable fees so other suites are unaffected
+    await updateFees(
+      program,
+      provider,
+      initializer.payer,
+      0,
+      0,
+      initializer.publicKey
+    );
+  });
+});
@@ -12,6 +12,9 @@ import {
   TOKEN_METADATA_PROGRAM_ID,
   findMasterEditionPda,
   localSendAndConfirmTransaction,
+  findFeeVaultPda,
+  findRolesPda,
+  findEventAuthorityPda,
   debugEnableLogs,
 } from "./utils";
 import {
@@ -113,6 +116,14 @@ describe("initialize instruction (with hardcoded mints)", () => {
       "config Collection mint mismatch"
     );
     assert.isNull(configAccount.fee, "config fee should be None initially");
+    assert.isNull(
+      configAccount.conversionFee,
+      "config conversion fee should be None initially"
+    );
+    assert.ok(
+      configAccount.feeRecipient.equals(initializer.publicKey),
+      "config fee recipient should default to the initializer"
+    );
     assert.isFalse(configAccount.locked, "config global lock should be false");
     assert.isTrue(
       configAccount.depositLocked,
@@ -122,6 +133,10 @@ describe("initialize instruction (with hardcoded mints)", () => {
       configAccount.convertLocked,
       "config convert lock should be true"
     );
+    assert.isTrue(
+      configAccount.redeemLocked,
+      "config redeem lock should be true"
+    );
     assert.strictEqual(configAccount.bump, configBump, "config bump mismatch");
 
     // verify treasury account
@@ -183,10 +198,14 @@ describe("initialize instruction (with hardcoded mints)", () => {
           collectionMintAta: collectionMintAta,
           config: configPda,
           treasury: treasuryPda,
+          feeVault: findFeeVaultPda(program),
+          roles: findRolesPda(program),
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          eventAuthority: findEventAuthorityPda(program),
+          program: program.programId,
         })
         .transaction();
       await localSendAndConfirmTransaction(
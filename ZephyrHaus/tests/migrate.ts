@@ -0,0 +1,542 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert, expect } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddress } from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  expectError,
+  requestAirdrop,
+  updateLocks,
+  deposit,
+  depositWithOption,
+  migrateConfig,
+  migrateTreasury,
+  migrateOptionData,
+  migrateDepositReceipt,
+  migrateLegacyDepositReceipt,
+  findLegacyDepositReceiptPda,
+  initFeeVault,
+  initRoles,
+  setAccount,
+  accountDiscriminator,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+const CURRENT_VERSION = 1;
+const CURRENT_CONFIG_VERSION = 2;
+const CURRENT_RECEIPT_VERSION = 2;
+// a version 1 receipt ends at its version byte, before the deposited asset
+const RECEIPT_V1_LEN = 50;
+
+// version 0 accounts are written straight into the validator, for 0.1 SOL
+// expiring on 2100-01-01
+const LEGACY_AMOUNT = new anchor.BN(100_000_000);
+const LEGACY_EXPIRATION = new anchor.BN(4_102_444_800);
+
+describe("migrate", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate();
+  const other = Keypair.generate(); // not the authority
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+  const legacyOptionMint = Keypair.generate().publicKey;
+  const legacyDepositor = Keypair.generate().publicKey;
+  const legacyClaimedDepositor = Keypair.generate().publicKey;
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+
+  // writes `data` as a program account at `address`, as version 0 left it,
+  // holding `extraLamports` above its rent-exempt minimum
+  const writeLegacyAccount = async (
+    address: PublicKey,
+    data: Buffer,
+    extraLamports = 0
+  ) => {
+    await setAccount(provider, address, {
+      lamports:
+        (await provider.connection.getMinimumBalanceForRentExemption(
+          data.length
+        )) + extraLamports,
+      data,
+      owner: program.programId,
+    });
+  };
+
+  // the version 0 receipt layout: initialized, nft_issued, amount, expiration
+  // and bump
+  const writeLegacyReceipt = async (owner: PublicKey, claimed: boolean) => {
+    const [address, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit_receipt"), owner.toBuffer()],
+      program.programId
+    );
+    await writeLegacyAccount(
+      address,
+      Buffer.concat([
+        accountDiscriminator("DepositReceipt"),
+        Buffer.from([1, claimed ? 1 : 0]),
+        LEGACY_AMOUNT.toArrayLike(Buffer, "le", 8),
+        LEGACY_EXPIRATION.toArrayLike(Buffer, "le", 8),
+        Buffer.from([bump]),
+      ])
+    );
+  };
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, other.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null
+    );
+
+    // the version 0 option data layout: mint, amount, expiration and bump
+    const [optionDataPda, optionDataBump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("option_data"), legacyOptionMint.toBuffer()],
+      program.programId
+    );
+    await writeLegacyAccount(
+      optionDataPda,
+      Buffer.concat([
+        accountDiscriminator("OptionData"),
+        legacyOptionMint.toBuffer(),
+        LEGACY_AMOUNT.toArrayLike(Buffer, "le", 8),
+        LEGACY_EXPIRATION.toArrayLike(Buffer, "le", 8),
+        Buffer.from([optionDataBump]),
+      ]),
+      LAMPORTS_PER_SOL // more than the current layout's rent
+    );
+    await writeLegacyReceipt(legacyDepositor, false);
+    await writeLegacyReceipt(legacyClaimedDepositor, true);
+  });
+
+  it("creates config and treasury on the current version", async () => {
+    const config = await program.account.config.fetch(configPda);
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    assert.strictEqual(
+      config.version,
+      CURRENT_CONFIG_VERSION,
+      "config version"
+    );
+    assert.strictEqual(treasury.version, CURRENT_VERSION, "treasury version");
+  });
+
+  it("rejects a version 0 config outside of migrate_config", async () => {
+    const current = await provider.connection.getAccountInfo(configPda);
+    const config = await program.account.config.fetch(configPda);
+    const [, configBump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    // the version 0 config layout: authority, mints, fee, option totals,
+    // deposit nonce, locks and bump
+    await writeLegacyAccount(
+      configPda,
+      Buffer.concat([
+        accountDiscriminator("Config"),
+        Buffer.from([1]),
+        initializer.publicKey.toBuffer(),
+        config.cnMint.toBuffer(),
+        config.ptMint.toBuffer(),
+        config.collectionMint.toBuffer(),
+        Buffer.from([0]),
+        config.optionCount.toArrayLike(Buffer, "le", 8),
+        config.totalOptionAmount.toArrayLike(Buffer, "le", 8),
+        config.depositNonce.toArrayLike(Buffer, "le", 8),
+        Buffer.from([0, 0, 0, configBump]),
+      ])
+    );
+
+    try {
+      // it's shorter than the current layout, so it fails to load before its
+      // version is checked
+      await expectError(
+        updateLocks(
+          program,
+          provider,
+          initializer.payer,
+          configPda,
+          false,
+          null,
+          null
+        ),
+        "AccountDidNotDeserialize"
+      );
+    } finally {
+      await setAccount(provider, configPda, {
+        lamports: current.lamports,
+        data: current.data,
+        owner: current.owner,
+      });
+    }
+  });
+
+  it("fails to migrate the config from a non-authority", async () => {
+    await expectError(migrateConfig(program, provider, other), "Unauthorized");
+    await expectError(
+      migrateTreasury(program, provider, other),
+      "Unauthorized"
+    );
+  });
+
+  it("fails to migrate a current config or treasury", async () => {
+    await expectError(
+      migrateConfig(program, provider, initializer.payer),
+      "AlreadyMigrated"
+    );
+    await expectError(
+      migrateTreasury(program, provider, initializer.payer),
+      "AlreadyMigrated"
+    );
+  });
+
+  it("issues receipts and options on the current version", async () => {
+    const protocolPtAta = await getAssociatedTokenAddress(
+      ptMint,
+      configPda,
+      true
+    );
+    const depositorCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      depositor.publicKey,
+      true
+    );
+    const { depositReceiptPda, nonce } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    assert.strictEqual(
+      receipt.version,
+      CURRENT_RECEIPT_VERSION,
+      "receipt version"
+    );
+    await expectError(
+      migrateDepositReceipt(
+        program,
+        provider,
+        initializer.payer,
+        depositor.publicKey,
+        nonce
+      ),
+      "AlreadyMigrated"
+    );
+
+    const option = await depositWithOption(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+    const optionData = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.strictEqual(optionData.version, CURRENT_VERSION, "option version");
+    await expectError(
+      migrateOptionData(program, provider, other, option.optionMint),
+      "Unauthorized"
+    );
+    await expectError(
+      migrateOptionData(
+        program,
+        provider,
+        initializer.payer,
+        option.optionMint
+      ),
+      "AlreadyMigrated"
+    );
+  });
+
+  it("fails to recreate the fee vault or roles account", async () => {
+    // both were created by initialize, system program create fails on them
+    for (const init of [
+      () => initFeeVault(program, provider, other),
+      () => initFeeVault(program, provider, initializer.payer),
+      () => initRoles(program, provider, initializer.payer),
+    ]) {
+      try {
+        await init();
+        assert.fail("init should fail for an existing account");
+      } catch (err) {
+        expect(err.toString()).to.match(
+          /already in use|custom program error: 0x0/i
+        );
+      }
+    }
+  });
+
+  it("migrates a version 0 option data", async () => {
+    const [optionDataPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("option_data"), legacyOptionMint.toBuffer()],
+      program.programId
+    );
+    const before = await provider.connection.getAccountInfo(optionDataPda);
+    assert.isNotNull(before, "legacy option data should be written");
+
+    await expectError(
+      migrateOptionData(program, provider, other, legacyOptionMint),
+      "Unauthorized"
+    );
+    await migrateOptionData(
+      program,
+      provider,
+      initializer.payer,
+      legacyOptionMint
+    );
+
+    const after = await provider.connection.getAccountInfo(optionDataPda);
+    const rentDue =
+      (await provider.connection.getMinimumBalanceForRentExemption(
+        after.data.length
+      )) -
+      (await provider.connection.getMinimumBalanceForRentExemption(
+        before.data.length
+      ));
+    assert.strictEqual(
+      after.lamports - before.lamports,
+      rentDue,
+      "the authority pays the rent of the added bytes"
+    );
+
+    const optionData = await program.account.optionData.fetch(optionDataPda);
+    assert.strictEqual(optionData.version, CURRENT_VERSION, "option version");
+    assert.isTrue(optionData.mint.equals(legacyOptionMint), "mint kept");
+    assert.isTrue(optionData.amount.eq(LEGACY_AMOUNT), "amount kept");
+    assert.isTrue(
+      optionData.expiration.eq(LEGACY_EXPIRATION),
+      "expiration kept"
+    );
+    assert.strictEqual(
+      optionData.conversionMultiplierBps,
+      10_000,
+      "version 0 converted one to one"
+    );
+    assert.isTrue(
+      optionData.rentPayer.equals(initializer.publicKey),
+      "rent goes to the authority"
+    );
+
+    await expectError(
+      migrateOptionData(
+        program,
+        provider,
+        initializer.payer,
+        legacyOptionMint
+      ),
+      "AlreadyMigrated"
+    );
+  });
+
+  it("migrates a version 1 deposit receipt", async () => {
+    const protocolPtAta = await getAssociatedTokenAddress(
+      ptMint,
+      configPda,
+      true
+    );
+    const depositorCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      depositor.publicKey,
+      true
+    );
+    const { depositReceiptPda, nonce } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta
+    );
+    const current = await provider.connection.getAccountInfo(
+      depositReceiptPda
+    );
+    // cut the receipt back to the version 1 layout
+    const data = Buffer.from(current.data.subarray(0, RECEIPT_V1_LEN));
+    data[RECEIPT_V1_LEN - 1] = 1;
+    await writeLegacyAccount(depositReceiptPda, data);
+
+    await migrateDepositReceipt(
+      program,
+      provider,
+      initializer.payer,
+      depositor.publicKey,
+      nonce
+    );
+
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    assert.strictEqual(
+      receipt.version,
+      CURRENT_RECEIPT_VERSION,
+      "receipt version"
+    );
+    assert.isNull(receipt.assetMint, "version 1 receipts only took SOL");
+    assert.isTrue(
+      receipt.assetAmount.eq(receipt.valueLamports),
+      "the SOL deposited is the deposit's value"
+    );
+    assert.isNull(receipt.allowlistRoot, "no allowlist root stored");
+  });
+
+  it("moves a version 0 deposit receipt to a nonce", async () => {
+    const legacyPda = findLegacyDepositReceiptPda(program, legacyDepositor);
+    const legacy = await provider.connection.getAccountInfo(legacyPda);
+    assert.isNotNull(legacy, "legacy receipt should be written");
+
+    await expectError(
+      migrateLegacyDepositReceipt(program, provider, other, legacyDepositor),
+      "Unauthorized"
+    );
+    const configBefore = await program.account.config.fetch(configPda);
+    const { depositReceiptPda, nonce } = await migrateLegacyDepositReceipt(
+      program,
+      provider,
+      initializer.payer,
+      legacyDepositor
+    );
+
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    assert.strictEqual(
+      receipt.version,
+      CURRENT_RECEIPT_VERSION,
+      "receipt version"
+    );
+    assert.isTrue(receipt.nonce.eq(nonce), "receipt nonce");
+    assert.isFalse(receipt.nftIssued, "still unclaimed");
+    assert.isTrue(receipt.amount.eq(LEGACY_AMOUNT), "amount kept");
+    assert.isTrue(
+      receipt.valueLamports.eq(LEGACY_AMOUNT),
+      "one lamport per CN"
+    );
+    assert.isTrue(
+      receipt.claimDeadline.eq(LEGACY_EXPIRATION),
+      "claimable until the old expiration"
+    );
+    assert.isNull(receipt.assetMint, "version 0 only took SOL");
+
+    const configAfter = await program.account.config.fetch(configPda);
+    assert.isTrue(
+      configAfter.depositNonce.eq(configBefore.depositNonce.addn(1)),
+      "deposit nonce advanced"
+    );
+    assert.isTrue(
+      configAfter.totalReceiptAmount.eq(
+        configBefore.totalReceiptAmount.add(LEGACY_AMOUNT)
+      ),
+      "the moved receipt counts as unclaimed"
+    );
+    assert.isNull(
+      await provider.connection.getAccountInfo(legacyPda),
+      "legacy receipt closed"
+    );
+    assert.strictEqual(
+      await provider.connection.getBalance(legacyDepositor),
+      legacy.lamports,
+      "legacy rent goes back to the depositor"
+    );
+
+    await expectError(
+      migrateLegacyDepositReceipt(
+        program,
+        provider,
+        initializer.payer,
+        legacyDepositor
+      ),
+      "AccountOwnedByWrongProgram"
+    );
+  });
+
+  it("closes a claimed version 0 deposit receipt", async () => {
+    const legacyPda = findLegacyDepositReceiptPda(
+      program,
+      legacyClaimedDepositor
+    );
+    const legacy = await provider.connection.getAccountInfo(legacyPda);
+    assert.isNotNull(legacy, "legacy receipt should be written");
+
+    const configBefore = await program.account.config.fetch(configPda);
+    const { depositReceiptPda } = await migrateLegacyDepositReceipt(
+      program,
+      provider,
+      initializer.payer,
+      legacyClaimedDepositor
+    );
+
+    assert.isNull(
+      await provider.connection.getAccountInfo(depositReceiptPda),
+      "no receipt for a claimed option"
+    );
+    const configAfter = await program.account.config.fetch(configPda);
+    assert.isTrue(
+      configAfter.depositNonce.eq(configBefore.depositNonce),
+      "deposit nonce untouched"
+    );
+    assert.isNull(
+      await provider.connection.getAccountInfo(legacyPda),
+      "legacy receipt closed"
+    );
+    assert.strictEqual(
+      await provider.connection.getBalance(legacyClaimedDepositor),
+      legacy.lamports,
+      "legacy rent goes back to the depositor"
+    );
+  });
+
+  it("fails to move a legacy receipt that doesn't exist", async () => {
+    // receipts are keyed by the deposit nonce now, so the legacy address of a
+    // depositor who only deposited since holds nothing
+    await expectError(
+      migrateLegacyDepositReceipt(
+        program,
+        provider,
+        initializer.payer,
+        depositor.publicKey
+      ),
+      "AccountOwnedByWrongProgram"
+    );
+  });
+});
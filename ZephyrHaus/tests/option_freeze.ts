@@ -0,0 +1,403 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  approve,
+  createAssociatedTokenAccount,
+  getAccount,
+  revoke,
+  transfer,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  expectError,
+  requestAirdrop,
+  updateLocks,
+  addDurationTier,
+  findDurationTierPda,
+  depositWithOption,
+  convert,
+  splitOption,
+  grantRole,
+  revokeRole,
+  freezeOption,
+  thawOption,
+  fetchCpiEvents,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+const SHORT_DURATION_SECONDS = 2; // expires while the suite runs
+const CLIFF_SECONDS = 3;
+
+describe("option freeze", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const holder = Keypair.generate(); // holds the options being frozen
+  const manager = Keypair.generate(); // holds the metadata manager role
+  const other = Keypair.generate(); // neither authority nor role holder
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+  let configPda: PublicKey;
+  let option: Awaited<ReturnType<typeof depositWithOption>>;
+
+  const isFrozen = async (ata: PublicKey) =>
+    (await getAccount(provider.connection, ata, undefined, TOKEN_PROGRAM_ID))
+      .isFrozen;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, holder.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, manager.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, other.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      false // set converts unlocked
+    );
+
+    const shortTier = await provider.connection.getAccountInfo(
+      findDurationTierPda(program, SHORT_DURATION_SECONDS)
+    );
+    if (shortTier === null) {
+      await addDurationTier(
+        program,
+        provider,
+        initializer.payer,
+        SHORT_DURATION_SECONDS,
+        null
+      );
+    }
+
+    option = await depositWithOption(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+  });
+
+  after(async () => {
+    await revokeRole(program, provider, initializer.payer, manager.publicKey, {
+      metadataManager: {},
+    });
+  });
+
+  it("approves the config as delegate of new option NFTs", async () => {
+    const ata = await getAccount(
+      provider.connection,
+      option.depositorOptionAta,
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    assert.isTrue(
+      ata.delegate.equals(configPda),
+      "config should be the option ATA's delegate"
+    );
+    assert.isFalse(ata.isFrozen, "new options should not be frozen");
+  });
+
+  it("fails to freeze an active option from a non-authority", async () => {
+    await expectError(
+      freezeOption(
+        program,
+        provider,
+        other,
+        option.optionMint,
+        holder.publicKey
+      ),
+      "Unauthorized"
+    );
+  });
+
+  it("freezes an option and blocks conversion", async () => {
+    const signature = await freezeOption(
+      program,
+      provider,
+      initializer.payer,
+      option.optionMint,
+      holder.publicKey
+    );
+    assert.isTrue(
+      await isFrozen(option.depositorOptionAta),
+      "option ATA should be frozen"
+    );
+
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "optionFrozen");
+    assert.ok(event, "OptionFrozen should be emitted");
+    assert.isTrue(
+      event.data.frozenBy.equals(initializer.publicKey),
+      "event frozen by mismatch"
+    );
+    assert.isNull(event.data.unlockAt, "event unlock time mismatch");
+
+    await expectError(
+      convert(
+        program,
+        provider,
+        holder,
+        cnMint,
+        ptMint,
+        option,
+        depositAmount.divn(1000)
+      ),
+      "OptionFrozen"
+    );
+  });
+
+  it("fails to thaw without a cliff from a non-authority", async () => {
+    await expectError(
+      thawOption(program, provider, other, option.optionMint, holder.publicKey),
+      "Unauthorized"
+    );
+  });
+
+  it("lets a metadata manager thaw and freeze options", async () => {
+    await grantRole(program, provider, initializer.payer, manager.publicKey, {
+      metadataManager: {},
+    });
+
+    await thawOption(
+      program,
+      provider,
+      manager,
+      option.optionMint,
+      holder.publicKey
+    );
+    assert.isFalse(
+      await isFrozen(option.depositorOptionAta),
+      "option ATA should be thawed"
+    );
+
+    await freezeOption(
+      program,
+      provider,
+      manager,
+      option.optionMint,
+      holder.publicKey
+    );
+    assert.isTrue(
+      await isFrozen(option.depositorOptionAta),
+      "option ATA should be frozen again"
+    );
+    await thawOption(
+      program,
+      provider,
+      manager,
+      option.optionMint,
+      holder.publicKey
+    );
+  });
+
+  it("lets anyone thaw an option once its cliff passes", async () => {
+    const unlockAt = new anchor.BN(
+      Math.floor(Date.now() / 1000) + CLIFF_SECONDS
+    );
+    await freezeOption(
+      program,
+      provider,
+      initializer.payer,
+      option.optionMint,
+      holder.publicKey,
+      unlockAt
+    );
+    const optionData = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.strictEqual(
+      optionData.unlockAt.toString(),
+      unlockAt.toString(),
+      "unlock time should be stored on the option"
+    );
+
+    await expectError(
+      thawOption(program, provider, other, option.optionMint, holder.publicKey),
+      "CliffNotReached"
+    );
+
+    await new Promise((resolve) =>
+      setTimeout(resolve, (CLIFF_SECONDS + 1) * 1000)
+    );
+    await thawOption(
+      program,
+      provider,
+      other,
+      option.optionMint,
+      holder.publicKey
+    );
+
+    assert.isFalse(
+      await isFrozen(option.depositorOptionAta),
+      "option ATA should be thawed after the cliff"
+    );
+    const optionAfter = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.isNull(optionAfter.unlockAt, "unlock time should be cleared");
+  });
+
+  it("lets anyone freeze an expired option for good", async () => {
+    const shortOption = await depositWithOption(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      depositAmount,
+      SHORT_DURATION_SECONDS
+    );
+    await new Promise((resolve) =>
+      setTimeout(resolve, (SHORT_DURATION_SECONDS + 1) * 1000)
+    );
+
+    await freezeOption(
+      program,
+      provider,
+      other,
+      shortOption.optionMint,
+      holder.publicKey
+    );
+    assert.isTrue(
+      await isFrozen(shortOption.depositorOptionAta),
+      "expired option ATA should be frozen"
+    );
+
+    await expectError(
+      thawOption(
+        program,
+        provider,
+        initializer.payer,
+        shortOption.optionMint,
+        holder.publicKey
+      ),
+      "OptionExpired"
+    );
+  });
+
+  it("blocks a holder who revoked the config as delegate", async () => {
+    // without the delegate the option could no longer be frozen
+    await revoke(
+      provider.connection,
+      holder,
+      option.depositorOptionAta,
+      holder,
+      [],
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+
+    await expectError(
+      convert(
+        program,
+        provider,
+        holder,
+        cnMint,
+        ptMint,
+        option,
+        depositAmount.divn(1000)
+      ),
+      "OptionNotDelegated"
+    );
+
+    await approve(
+      provider.connection,
+      holder,
+      option.depositorOptionAta,
+      configPda,
+      holder,
+      1,
+      [],
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    await convert(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      option,
+      depositAmount.divn(1000)
+    );
+  });
+
+  it("blocks a sent option until the config is its delegate", async () => {
+    const otherOptionAta = await createAssociatedTokenAccount(
+      provider.connection,
+      other,
+      option.optionMint,
+      other.publicKey,
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    await transfer(
+      provider.connection,
+      holder,
+      option.depositorOptionAta,
+      otherOptionAta,
+      holder,
+      1,
+      [],
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+
+    await expectError(
+      splitOption(program, provider, other, option, depositAmount.divn(1000)),
+      "OptionNotDelegated"
+    );
+
+    // once the new holder approves the config, the option can be frozen again
+    await approve(
+      provider.connection,
+      other,
+      otherOptionAta,
+      configPda,
+      other,
+      1,
+      [],
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    await freezeOption(
+      program,
+      provider,
+      initializer.payer,
+      option.optionMint,
+      other.publicKey
+    );
+    assert.isTrue(
+      await isFrozen(otherOptionAta),
+      "transferred option ATA should be frozen"
+    );
+  });
+});
@@ -0,0 +1,150 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  requestAirdrop,
+  updateLocks,
+  addDurationTier,
+  findDurationTierPda,
+  depositWithOption,
+  convert,
+  fetchOptionMetadata,
+  refreshOptionMetadata,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+const SHORT_DURATION_SECONDS = 2; // expires while the suite runs
+
+describe("option metadata", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const holder = Keypair.generate(); // holds the options
+  const cranker = Keypair.generate(); // refreshes metadata it doesn't own
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+  let configPda: PublicKey;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, holder.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, cranker.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      false // set converts unlocked
+    );
+
+    const shortTier = await provider.connection.getAccountInfo(
+      findDurationTierPda(program, SHORT_DURATION_SECONDS)
+    );
+    if (shortTier === null) {
+      await addDurationTier(
+        program,
+        provider,
+        initializer.payer,
+        SHORT_DURATION_SECONDS,
+        null
+      );
+    }
+  });
+
+  it("shows the remaining amount after a partial conversion", async () => {
+    const option = await depositWithOption(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+    const created = await fetchOptionMetadata(provider, option.optionMint);
+    assert.strictEqual(created.name, "zOption", "initial name mismatch");
+
+    const optionBefore = await program.account.optionData.fetch(
+      option.optionData
+    );
+    await convert(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      option,
+      optionBefore.amount.divn(2)
+    );
+
+    const optionAfter = await program.account.optionData.fetch(
+      option.optionData
+    );
+    const metadata = await fetchOptionMetadata(provider, option.optionMint);
+    assert.strictEqual(metadata.name, "zOption", "active name mismatch");
+    assert.include(
+      metadata.uri,
+      option.optionData.toBase58(),
+      "URI should point at the OptionData PDA"
+    );
+    assert.include(
+      metadata.uri,
+      `amount=${optionAfter.amount.toString()}`,
+      "URI should show the remaining amount"
+    );
+    assert.include(
+      metadata.uri,
+      `expiration=${optionAfter.expiration.toString()}`,
+      "URI should show the expiration"
+    );
+    assert.include(metadata.uri, "status=active", "URI status mismatch");
+  });
+
+  it("lets anyone mark an expired option's metadata", async () => {
+    const option = await depositWithOption(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      depositAmount,
+      SHORT_DURATION_SECONDS
+    );
+    await new Promise((resolve) =>
+      setTimeout(resolve, (SHORT_DURATION_SECONDS + 1) * 1000)
+    );
+
+    await refreshOptionMetadata(program, provider, cranker, option.optionMint);
+
+    const metadata = await fetchOptionMetadata(provider, option.optionMint);
+    assert.strictEqual(
+      metadata.name,
+      "zOption (expired)",
+      "expired name mismatch"
+    );
+    assert.include(metadata.uri, "status=expired", "URI status mismatch");
+  });
+});
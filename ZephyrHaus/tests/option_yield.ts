@@ -0,0 +1,306 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  createAssociatedTokenAccount,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  depositWithOption,
+  convert,
+  distributeYield,
+  claimOptionYield,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+const YIELD_PRECISION = new anchor.BN("1000000000000");
+
+describe("option yield", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const holder = Keypair.generate(); // holds the option earning yield
+  const other = Keypair.generate(); // does not hold the holder's option
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.5 * LAMPORTS_PER_SOL);
+  const yieldAmount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+  let option: Awaited<ReturnType<typeof depositWithOption>>;
+
+  // yield earned by `amount` between its checkpoint and `yieldPerUnit`
+  const earned = (
+    amount: anchor.BN,
+    yieldPerUnit: anchor.BN,
+    checkpoint: anchor.BN
+  ) => amount.mul(yieldPerUnit.sub(checkpoint)).div(YIELD_PRECISION);
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, holder.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, other.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      false // set converts unlocked
+    );
+
+    option = await depositWithOption(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+  });
+
+  it("checkpoints new options at the current yield per unit", async () => {
+    const config = await program.account.config.fetch(configPda);
+    const optionData = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.strictEqual(
+      optionData.yieldCheckpoint.toString(),
+      config.yieldPerUnit.toString(),
+      "new option should only earn yield distributed after issue"
+    );
+    assert.strictEqual(
+      optionData.accruedYield.toNumber(),
+      0,
+      "new option should have no accrued yield"
+    );
+  });
+
+  it("fails to distribute yield from a non-authority", async () => {
+    try {
+      await distributeYield(program, provider, other, yieldAmount);
+      assert.fail("distribute yield should have failed for non-authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unauthorized)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (unauthorized)"
+      );
+    }
+  });
+
+  it("distributes yield over the outstanding option amount", async () => {
+    const configBefore = await program.account.config.fetch(configPda);
+    const treasuryBefore = await program.account.treasury.fetch(treasuryPda);
+
+    await distributeYield(program, provider, initializer.payer, yieldAmount);
+
+    const configAfter = await program.account.config.fetch(configPda);
+    const treasuryAfter = await program.account.treasury.fetch(treasuryPda);
+    assert.strictEqual(
+      configAfter.yieldPerUnit.sub(configBefore.yieldPerUnit).toString(),
+      yieldAmount
+        .mul(YIELD_PRECISION)
+        .div(configBefore.totalOptionAmount)
+        .toString(),
+      "yield per unit mismatch"
+    );
+    assert.strictEqual(
+      treasuryAfter.reservedYield.sub(treasuryBefore.reservedYield).toString(),
+      yieldAmount.toString(),
+      "distributed yield should be reserved"
+    );
+  });
+
+  it("pays the holder the yield their option earned", async () => {
+    const config = await program.account.config.fetch(configPda);
+    const optionBefore = await program.account.optionData.fetch(
+      option.optionData
+    );
+    const treasuryBefore = await program.account.treasury.fetch(treasuryPda);
+    const treasuryBalanceBefore = await provider.connection.getBalance(
+      treasuryPda
+    );
+    const expected = earned(
+      optionBefore.amount,
+      config.yieldPerUnit,
+      optionBefore.yieldCheckpoint
+    );
+    assert.isTrue(expected.gtn(0), "option should have earned yield");
+
+    await claimOptionYield(program, provider, holder, option.optionMint);
+
+    const treasuryBalanceAfter = await provider.connection.getBalance(
+      treasuryPda
+    );
+    assert.strictEqual(
+      treasuryBalanceBefore - treasuryBalanceAfter,
+      expected.toNumber(),
+      "treasury should pay out the earned yield"
+    );
+    const treasuryAfter = await program.account.treasury.fetch(treasuryPda);
+    assert.strictEqual(
+      treasuryBefore.reservedYield.sub(treasuryAfter.reservedYield).toString(),
+      expected.toString(),
+      "claimed yield should leave the reserve"
+    );
+    const optionAfter = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.strictEqual(
+      optionAfter.accruedYield.toNumber(),
+      0,
+      "accrued yield should be cleared"
+    );
+    assert.strictEqual(
+      optionAfter.yieldCheckpoint.toString(),
+      config.yieldPerUnit.toString(),
+      "checkpoint should move to the current yield per unit"
+    );
+  });
+
+  it("fails to claim with no yield accrued", async () => {
+    try {
+      await claimOptionYield(program, provider, holder, option.optionMint);
+      assert.fail("claim should have failed with nothing accrued");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (no yield)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "NoYieldToClaim",
+        "error code mismatch (no yield)"
+      );
+    }
+  });
+
+  it("fails to claim for an option the signer does not hold", async () => {
+    await distributeYield(program, provider, initializer.payer, yieldAmount);
+    // an empty ATA for the option mint does not prove ownership
+    await createAssociatedTokenAccount(
+      provider.connection,
+      other,
+      option.optionMint,
+      other.publicKey,
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+
+    try {
+      await claimOptionYield(program, provider, other, option.optionMint);
+      assert.fail("claim should have failed for a non-holder");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (not holder)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "NotOptionHolder",
+        "error code mismatch (not holder)"
+      );
+    }
+  });
+
+  it("settles yield before a partial conversion", async () => {
+    const config = await program.account.config.fetch(configPda);
+    const optionBefore = await program.account.optionData.fetch(
+      option.optionData
+    );
+    const expected = optionBefore.accruedYield.add(
+      earned(
+        optionBefore.amount,
+        config.yieldPerUnit,
+        optionBefore.yieldCheckpoint
+      )
+    );
+
+    await convert(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      option,
+      optionBefore.amount.divn(2)
+    );
+
+    const optionAfter = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.strictEqual(
+      optionAfter.accruedYield.toString(),
+      expected.toString(),
+      "yield should be settled on the amount before conversion"
+    );
+    assert.strictEqual(
+      optionAfter.yieldCheckpoint.toString(),
+      config.yieldPerUnit.toString(),
+      "checkpoint should move to the current yield per unit"
+    );
+  });
+
+  it("pays out settled yield on a full conversion", async () => {
+    await distributeYield(program, provider, initializer.payer, yieldAmount);
+    const config = await program.account.config.fetch(configPda);
+    const optionBefore = await program.account.optionData.fetch(
+      option.optionData
+    );
+    const treasuryBefore = await program.account.treasury.fetch(treasuryPda);
+    const expected = optionBefore.accruedYield.add(
+      earned(
+        optionBefore.amount,
+        config.yieldPerUnit,
+        optionBefore.yieldCheckpoint
+      )
+    );
+
+    await convert(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      option,
+      optionBefore.amount
+    );
+
+    // nothing is left on the option, so its data is closed with the NFT
+    assert.isNull(
+      await provider.connection.getAccountInfo(option.optionData),
+      "option data should be closed on a full conversion"
+    );
+    const treasuryAfter = await program.account.treasury.fetch(treasuryPda);
+    assert.strictEqual(
+      treasuryBefore.reservedYield.sub(treasuryAfter.reservedYield).toString(),
+      expected.toString(),
+      "paid yield should leave the reserve"
+    );
+  });
+});
@@ -0,0 +1,338 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert, expect } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { getAccount, getAssociatedTokenAddress } from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  THREE_MONTHS_SECONDS,
+  addDurationTier,
+  findDurationTierPda,
+  initializeProtocol,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  deposit,
+  initializeOption,
+  redeem,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("redeem instruction (with hardcoded mints)", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const redeemer = Keypair.generate(); // create a new redeemer for tests
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+  let protocolPtAta: PublicKey;
+  let redeemerCnAta: PublicKey;
+  let optionMint: PublicKey;
+  const depositAmount = new anchor.BN(0.5 * LAMPORTS_PER_SOL); // 0.5 SOL
+  const redeemAmount = new anchor.BN(0.2 * LAMPORTS_PER_SOL); // 0.2 CN
+  const doubleTierDuration = 6 * THREE_MONTHS_SECONDS; // 2x conversion tier
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, redeemer.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+    protocolPtAta = await getAssociatedTokenAddress(ptMint, configPda, true);
+    redeemerCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      redeemer.publicKey,
+      true
+    );
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      false, // set converts unlocked
+      false // set redeems unlocked
+    );
+
+    // give the redeemer some CN to work with
+    const { nonce } = await deposit(
+      program,
+      provider,
+      redeemer,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      redeemerCnAta
+    );
+    ({ optionMint } = await initializeOption(
+      program,
+      provider,
+      redeemer,
+      nonce
+    ));
+
+    const doubleTier = await provider.connection.getAccountInfo(
+      findDurationTierPda(program, doubleTierDuration)
+    );
+    if (doubleTier === null) {
+      await addDurationTier(
+        program,
+        provider,
+        initializer.payer,
+        doubleTierDuration,
+        null,
+        20_000
+      );
+    }
+  });
+
+  it("burns CN and the option's PT and pays out SOL at NAV", async () => {
+    const initialTreasuryBalance = await provider.connection.getBalance(
+      treasuryPda
+    );
+    const initialRedeemerSol = await provider.connection.getBalance(
+      redeemer.publicKey
+    );
+    const initialRedeemerCn = (
+      await getAccount(provider.connection, redeemerCnAta)
+    ).amount;
+    const initialProtocolPt = (
+      await getAccount(provider.connection, protocolPtAta)
+    ).amount;
+
+    await redeem(
+      program,
+      provider,
+      redeemer,
+      cnMint,
+      ptMint,
+      redeemAmount,
+      optionMint
+    );
+
+    // the payout is the redeemed amount at the NAV recorded by the redeem.
+    // earlier suites (yield reserves, tier multipliers) can move it off 1:1
+    const { nav } = await program.account.treasury.fetch(treasuryPda);
+    const expectedPayout = redeemAmount
+      .mul(nav)
+      .div(new anchor.BN(1_000_000_000));
+    const finalTreasuryBalance = await provider.connection.getBalance(
+      treasuryPda
+    );
+    assert.strictEqual(
+      finalTreasuryBalance,
+      initialTreasuryBalance - expectedPayout.toNumber(),
+      "treasury balance mismatch"
+    );
+    const finalRedeemerSol = await provider.connection.getBalance(
+      redeemer.publicKey
+    );
+    expect(finalRedeemerSol).to.be.greaterThan(
+      initialRedeemerSol,
+      "redeemer SOL should increase"
+    );
+
+    const finalRedeemerCn = (
+      await getAccount(provider.connection, redeemerCnAta)
+    ).amount;
+    assert.strictEqual(
+      finalRedeemerCn.toString(),
+      (initialRedeemerCn - BigInt(redeemAmount.toString())).toString(),
+      "redeemer CN balance mismatch"
+    );
+    const finalProtocolPt = (
+      await getAccount(provider.connection, protocolPtAta)
+    ).amount;
+    assert.strictEqual(
+      finalProtocolPt.toString(),
+      (initialProtocolPt - BigInt(redeemAmount.toString())).toString(),
+      "protocol PT balance mismatch"
+    );
+
+    const [optionDataPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("option_data"), optionMint.toBuffer()],
+      program.programId
+    );
+    const optionData = await program.account.optionData.fetch(optionDataPda);
+    assert.strictEqual(
+      optionData.amount.toString(),
+      depositAmount.sub(redeemAmount).toString(),
+      "option amount should drop by the CN redeemed"
+    );
+  });
+
+  it("repays a 2x tier deposit in full and burns the 2x PT", async () => {
+    const depositor = Keypair.generate();
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+    const depositorCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      depositor.publicKey,
+      true
+    );
+
+    const { nonce, depositReceiptPda } = await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      protocolPtAta,
+      depositorCnAta,
+      doubleTierDuration
+    );
+    // what reached the treasury, after any deposit fee
+    const { valueLamports } = await program.account.depositReceipt.fetch(
+      depositReceiptPda
+    );
+    const option = await initializeOption(program, provider, depositor, nonce);
+    const cnAmount = new anchor.BN(
+      (await getAccount(provider.connection, depositorCnAta)).amount.toString()
+    );
+
+    const initialTreasuryBalance = await provider.connection.getBalance(
+      treasuryPda
+    );
+    const initialProtocolPt = (
+      await getAccount(provider.connection, protocolPtAta)
+    ).amount;
+
+    await redeem(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      cnAmount,
+      option.optionMint
+    );
+
+    // the 2x PT minted for the option doesn't dilute the CN, so the whole
+    // deposit comes back (less rounding)
+    const payout =
+      initialTreasuryBalance -
+      (await provider.connection.getBalance(treasuryPda));
+    expect(payout).to.be.at.most(valueLamports.toNumber());
+    expect(payout).to.be.closeTo(valueLamports.toNumber(), 10);
+
+    const finalProtocolPt = (
+      await getAccount(provider.connection, protocolPtAta)
+    ).amount;
+    assert.strictEqual(
+      (initialProtocolPt - finalProtocolPt).toString(),
+      cnAmount.muln(2).toString(),
+      "the option's 2x PT should be burned"
+    );
+    const optionData = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.strictEqual(
+      optionData.amount.toString(),
+      "0",
+      "option should be used up"
+    );
+  });
+
+  it("fails redeem with zero amount", async () => {
+    try {
+      await redeem(
+        program,
+        provider,
+        redeemer,
+        cnMint,
+        ptMint,
+        new anchor.BN(0)
+      );
+      assert.fail("redeem should have failed with zero amount");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (zero amount)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "ZeroAmount",
+        "error code mismatch (zero amount)"
+      );
+    }
+  });
+
+  it("fails redeem for more CN than the redeemer holds", async () => {
+    try {
+      await redeem(
+        program,
+        provider,
+        redeemer,
+        cnMint,
+        ptMint,
+        new anchor.BN(10 * LAMPORTS_PER_SOL)
+      );
+      assert.fail("redeem should have failed due to insufficient CN");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (insufficient CN)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "InsufficientCnBalance",
+        "error code mismatch (insufficient CN)"
+      );
+    }
+  });
+
+  it("fails redeem when redemptions are locked (but protocol unlocked)", async () => {
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false,
+      null,
+      null,
+      true // set redeems locked
+    );
+
+    try {
+      await redeem(program, provider, redeemer, cnMint, ptMint, redeemAmount);
+      assert.fail("redeem should have failed due to redeem lock");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (redeem lock)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "RedemptionsLocked",
+        "error code mismatch (redeem lock)"
+      );
+    }
+  });
+
+  after(async () => {
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false,
+      false,
+      false,
+      false
+    );
+  });
+});
@@ -0,0 +1,189 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  expectError,
+  requestAirdrop,
+  updateLocks,
+  updateFees,
+  updateDepositCaps,
+  grantRole,
+  revokeRole,
+  findRolesPda,
+  fetchCpiEvents,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("roles", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const guardian = Keypair.generate(); // holds the pause guardian role
+  const feeManager = Keypair.generate(); // holds the fee manager role
+  const other = Keypair.generate(); // holds no role
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+
+  let configPda: PublicKey;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, guardian.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, feeManager.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, other.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+  });
+
+  after(async () => {
+    // leave the protocol unlocked and the roles account empty for other suites
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false,
+      false,
+      false,
+      false
+    );
+    await revokeRole(program, provider, initializer.payer, guardian.publicKey, {
+      pauseGuardian: {},
+    });
+  });
+
+  it("fails to grant a role from a non-authority", async () => {
+    await expectError(
+      grantRole(program, provider, other, other.publicKey, {
+        feeManager: {},
+      }),
+      "Unauthorized"
+    );
+  });
+
+  it("grants roles and emits RoleGranted", async () => {
+    const signature = await grantRole(
+      program,
+      provider,
+      initializer.payer,
+      guardian.publicKey,
+      { pauseGuardian: {} }
+    );
+    await grantRole(
+      program,
+      provider,
+      initializer.payer,
+      feeManager.publicKey,
+      { feeManager: {} }
+    );
+
+    const roles = await program.account.roles.fetch(findRolesPda(program));
+    assert.isTrue(
+      roles.members.some((m) => m.key.equals(guardian.publicKey)),
+      "guardian should be a member"
+    );
+
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "roleGranted");
+    assert.ok(event, "RoleGranted should be emitted");
+    assert.isTrue(
+      event.data.member.equals(guardian.publicKey),
+      "event member mismatch"
+    );
+    assert.deepEqual(event.data.role, { pauseGuardian: {} });
+  });
+
+  it("lets the pause guardian lock but not unlock", async () => {
+    await updateLocks(
+      program,
+      provider,
+      guardian,
+      configPda,
+      null,
+      true, // lock deposits
+      null,
+      null
+    );
+    const config = await program.account.config.fetch(configPda);
+    assert.isTrue(config.depositLocked, "deposits should be locked");
+
+    await expectError(
+      updateLocks(program, provider, guardian, configPda, null, false, null),
+      "GuardianCanOnlyLock"
+    );
+  });
+
+  it("keeps the pause guardian out of other admin instructions", async () => {
+    await expectError(
+      updateFees(program, provider, guardian, 10, null),
+      "Unauthorized"
+    );
+  });
+
+  it("lets the fee manager update fees", async () => {
+    await updateFees(program, provider, feeManager, 10, null);
+    let config = await program.account.config.fetch(configPda);
+    assert.strictEqual(config.fee, 10, "fee should be updated");
+
+    await updateFees(program, provider, feeManager, 0, null);
+    config = await program.account.config.fetch(configPda);
+    assert.isNull(config.fee, "fee should be disabled again");
+  });
+
+  it("keeps authority-only instructions from role holders", async () => {
+    await expectError(
+      updateDepositCaps(program, provider, feeManager, null, null, null),
+      "Unauthorized"
+    );
+  });
+
+  it("fails for signers without a role", async () => {
+    await expectError(
+      updateLocks(program, provider, other, configPda, true, null, null),
+      "Unauthorized"
+    );
+  });
+
+  it("revokes a role", async () => {
+    await revokeRole(
+      program,
+      provider,
+      initializer.payer,
+      feeManager.publicKey,
+      { feeManager: {} }
+    );
+    const roles = await program.account.roles.fetch(findRolesPda(program));
+    assert.isFalse(
+      roles.members.some((m) => m.key.equals(feeManager.publicKey)),
+      "members without roles should be dropped"
+    );
+
+    await expectError(
+      updateFees(program, provider, feeManager, 10, null),
+      "Unauthorized"
+    );
+    await expectError(
+      revokeRole(program, provider, initializer.payer, feeManager.publicKey, {
+        feeManager: {},
+      }),
+      "RoleNotGranted"
+    );
+  });
+});
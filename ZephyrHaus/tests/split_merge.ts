@@ -0,0 +1,289 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  createAssociatedTokenAccount,
+  getAccount,
+  getMint,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  THREE_MONTHS_SECONDS,
+  initializeProtocol,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  depositWithOption,
+  splitOption,
+  mergeOptions,
+  fetchCpiEvents,
+  fetchOptionMetadata,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("split and merge options", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const holder = Keypair.generate(); // holds the options being split and merged
+  const other = Keypair.generate(); // does not hold the holder's option
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.5 * LAMPORTS_PER_SOL);
+
+  let configPda: PublicKey;
+  let option: Awaited<ReturnType<typeof depositWithOption>>;
+  let splitOff: Awaited<ReturnType<typeof splitOption>>;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, holder.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, other.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      null
+    );
+
+    option = await depositWithOption(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+  });
+
+  it("fails to split the option's full amount", async () => {
+    const optionData = await program.account.optionData.fetch(
+      option.optionData
+    );
+    try {
+      await splitOption(program, provider, holder, option, optionData.amount);
+      assert.fail("split should have failed for the full amount");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (invalid amount)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "InvalidSplitAmount",
+        "error code mismatch (invalid amount)"
+      );
+    }
+  });
+
+  it("fails to split an option the signer does not hold", async () => {
+    // an empty ATA for the option mint does not prove ownership
+    await createAssociatedTokenAccount(
+      provider.connection,
+      other,
+      option.optionMint,
+      other.publicKey,
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+
+    try {
+      await splitOption(program, provider, other, option, new anchor.BN(1));
+      assert.fail("split should have failed for a non-holder");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (not holder)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "NotOptionHolder",
+        "error code mismatch (not holder)"
+      );
+    }
+  });
+
+  it("splits part of an option onto a new option NFT", async () => {
+    const configBefore = await program.account.config.fetch(configPda);
+    const optionBefore = await program.account.optionData.fetch(
+      option.optionData
+    );
+    const amount = optionBefore.amount.divn(4);
+
+    splitOff = await splitOption(program, provider, holder, option, amount);
+
+    const configAfter = await program.account.config.fetch(configPda);
+    assert.strictEqual(
+      configAfter.optionCount.sub(configBefore.optionCount).toNumber(),
+      1,
+      "split should add one option"
+    );
+    assert.strictEqual(
+      configAfter.totalOptionAmount.toString(),
+      configBefore.totalOptionAmount.toString(),
+      "split should not change the total option amount"
+    );
+
+    const optionAfter = await program.account.optionData.fetch(
+      option.optionData
+    );
+    const newOption = await program.account.optionData.fetch(
+      splitOff.optionData
+    );
+    assert.strictEqual(
+      optionAfter.amount.toString(),
+      optionBefore.amount.sub(amount).toString(),
+      "split option should keep the rest of its amount"
+    );
+    assert.strictEqual(
+      newOption.amount.toString(),
+      amount.toString(),
+      "new option amount mismatch"
+    );
+    assert.strictEqual(
+      newOption.expiration.toString(),
+      optionBefore.expiration.toString(),
+      "new option should keep the expiration"
+    );
+    assert.strictEqual(
+      newOption.conversionMultiplierBps,
+      optionBefore.conversionMultiplierBps,
+      "new option should keep the conversion multiplier"
+    );
+    assert.isTrue(
+      newOption.yieldCheckpoint.eq(configAfter.yieldPerUnit),
+      "new option should only earn yield distributed after the split"
+    );
+
+    const newOptionAta = await getAccount(
+      provider.connection,
+      splitOff.depositorOptionAta,
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    assert.strictEqual(
+      Number(newOptionAta.amount),
+      1,
+      "holder should own the new option NFT"
+    );
+
+    const metadata = await fetchOptionMetadata(provider, option.optionMint);
+    assert.include(
+      metadata.uri,
+      `amount=${optionAfter.amount.toString()}`,
+      "split option metadata should show the remaining amount"
+    );
+
+    const events = await fetchCpiEvents(program, provider, splitOff.signature);
+    const event = events.find((e) => e.name === "optionSplit");
+    assert.ok(event, "OptionSplit should be emitted");
+    assert.isTrue(
+      event.data.newOptionMint.equals(splitOff.optionMint),
+      "event new option mint mismatch"
+    );
+    assert.strictEqual(
+      event.data.remainingAmount.toString(),
+      optionAfter.amount.toString(),
+      "event remaining amount mismatch"
+    );
+  });
+
+  it("fails to merge options with different expirations", async () => {
+    // a longer tier always expires later than the split options
+    const longerOption = await depositWithOption(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      depositAmount,
+      2 * THREE_MONTHS_SECONDS
+    );
+
+    try {
+      await mergeOptions(program, provider, holder, option, longerOption);
+      assert.fail("merge should have failed for different expirations");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (expiration)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "ExpirationMismatch",
+        "error code mismatch (expiration)"
+      );
+    }
+  });
+
+  it("merges the split option back into the original", async () => {
+    const configBefore = await program.account.config.fetch(configPda);
+    const optionBefore = await program.account.optionData.fetch(
+      option.optionData
+    );
+    const splitBefore = await program.account.optionData.fetch(
+      splitOff.optionData
+    );
+
+    await mergeOptions(program, provider, holder, option, splitOff);
+
+    const configAfter = await program.account.config.fetch(configPda);
+    assert.strictEqual(
+      configBefore.optionCount.sub(configAfter.optionCount).toNumber(),
+      1,
+      "merge should remove one option"
+    );
+    assert.strictEqual(
+      configAfter.totalOptionAmount.toString(),
+      configBefore.totalOptionAmount.toString(),
+      "merge should not change the total option amount"
+    );
+
+    const optionAfter = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.strictEqual(
+      optionAfter.amount.toString(),
+      optionBefore.amount.add(splitBefore.amount).toString(),
+      "kept option should hold both amounts"
+    );
+    assert.isNull(
+      await provider.connection.getAccountInfo(splitOff.optionData),
+      "merged option data should be closed"
+    );
+    const mergedMint = await getMint(
+      provider.connection,
+      splitOff.optionMint,
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+    assert.strictEqual(
+      Number(mergedMint.supply),
+      0,
+      "merged option NFT should be burned"
+    );
+
+    const metadata = await fetchOptionMetadata(provider, option.optionMint);
+    assert.include(
+      metadata.uri,
+      `amount=${optionAfter.amount.toString()}`,
+      "kept option metadata should show the merged amount"
+    );
+  });
+});
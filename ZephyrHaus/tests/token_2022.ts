@@ -0,0 +1,561 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createInitializeMintInstruction,
+  createInitializeNonTransferableMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  getAccount,
+  getAssociatedTokenAddress,
+  getMintLen,
+  getTransferFeeAmount,
+  transferChecked,
+} from "@solana/spl-token";
+import {
+  THREE_MONTHS_SECONDS,
+  initializeProtocol,
+  addDurationTier,
+  requestAirdrop,
+  updateLocks,
+  depositWithOption,
+  convert,
+  redeem,
+  syncExpiredOptions,
+  findFeeVaultPda,
+  findRolesPda,
+  findMetadataPda,
+  findMasterEditionPda,
+  findDurationTierPda,
+  localSendAndConfirmTransaction,
+  setAccount,
+  expectError,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+const SHORT_DURATION_SECONDS = 2; // expires while the suite runs
+
+// the config is a singleton, so this suite can't initialize a protocol with
+// Token-2022 mints next to the shared one. it sets the shared protocol's
+// accounts aside, initializes its own and puts them back afterwards.
+describe("Token-2022 CN and PT", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate();
+  const other = Keypair.generate(); // tries to receive the depositor's CN
+
+  const cnMintKeypair = Keypair.generate(); // non-transferable
+  const ptMintKeypair = Keypair.generate(); // withholds a transfer fee
+  const cnMint = cnMintKeypair.publicKey;
+  const ptMint = ptMintKeypair.publicKey;
+  const decimals = 9;
+  const ptFeeBps = 100; // 1%
+  const ptMaxFee = BigInt(LAMPORTS_PER_SOL); // 1 PT
+
+  const depositAmount = new anchor.BN(0.5 * LAMPORTS_PER_SOL); // 0.5 SOL
+  const convertAmount = new anchor.BN(0.2 * LAMPORTS_PER_SOL); // 0.2 CN
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+  let protocolPtAta: PublicKey;
+  let depositorCnAta: PublicKey;
+  let option: Awaited<ReturnType<typeof depositWithOption>>;
+
+  // the shared protocol's accounts, as they were before this suite
+  const saved: {
+    address: PublicKey;
+    account: { lamports: number; data: Buffer; owner: PublicKey } | null;
+  }[] = [];
+
+  // creates a Token-2022 mint with one extension, minted by the config
+  const createMint2022 = async (
+    mint: Keypair,
+    extension: ExtensionType,
+    initializeExtension: TransactionInstruction
+  ) => {
+    const mintLen = getMintLen([extension]);
+    const tx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: initializer.publicKey,
+        newAccountPubkey: mint.publicKey,
+        space: mintLen,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(
+          mintLen
+        ),
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      initializeExtension,
+      createInitializeMintInstruction(
+        mint.publicKey,
+        decimals,
+        configPda,
+        null,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+    await localSendAndConfirmTransaction(provider, tx, initializer.publicKey, [
+      initializer.payer,
+      mint,
+    ]);
+  };
+
+  const tokenBalance = async (address: PublicKey) =>
+    (
+      await getAccount(
+        provider.connection,
+        address,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      )
+    ).amount.toString();
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, other.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    [treasuryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      program.programId
+    );
+    const [collectionMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("collection_mint"), configPda.toBuffer()],
+      program.programId
+    );
+
+    // everything initialize creates is set aside. the duration tiers are
+    // reused, so only their issued totals are put back
+    const created = [
+      configPda,
+      treasuryPda,
+      findFeeVaultPda(program),
+      findRolesPda(program),
+      collectionMint,
+      findMetadataPda(collectionMint),
+      findMasterEditionPda(collectionMint),
+    ];
+    for (const address of [
+      ...created,
+      findDurationTierPda(program, THREE_MONTHS_SECONDS),
+      findDurationTierPda(program, SHORT_DURATION_SECONDS),
+    ]) {
+      const info = await provider.connection.getAccountInfo(address);
+      saved.push({
+        address,
+        account: info && {
+          lamports: info.lamports,
+          data: info.data,
+          owner: info.owner,
+        },
+      });
+    }
+    for (const address of created) {
+      await setAccount(provider, address, null);
+    }
+
+    await createMint2022(
+      cnMintKeypair,
+      ExtensionType.NonTransferable,
+      createInitializeNonTransferableMintInstruction(
+        cnMint,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+    await createMint2022(
+      ptMintKeypair,
+      ExtensionType.TransferFeeConfig,
+      createInitializeTransferFeeConfigInstruction(
+        ptMint,
+        initializer.publicKey,
+        initializer.publicKey,
+        ptFeeBps,
+        ptMaxFee,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+
+    await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      false, // set converts unlocked
+      false // set redemptions unlocked
+    );
+
+    protocolPtAta = await getAssociatedTokenAddress(
+      ptMint,
+      configPda,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    depositorCnAta = await getAssociatedTokenAddress(
+      cnMint,
+      depositor.publicKey,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+  });
+
+  after(async () => {
+    for (const { address, account } of saved) {
+      await setAccount(provider, address, account);
+    }
+  });
+
+  it("initializes with Token-2022 mints", async () => {
+    const config = await program.account.config.fetch(configPda);
+    assert.isTrue(config.cnMint.equals(cnMint), "config CN mint");
+    assert.isTrue(config.ptMint.equals(ptMint), "config PT mint");
+
+    const collectionMint = await provider.connection.getAccountInfo(
+      config.collectionMint
+    );
+    assert.isTrue(
+      collectionMint.owner.equals(TOKEN_2022_PROGRAM_ID),
+      "collection mint should be a Token-2022 mint"
+    );
+  });
+
+  it("deposits into a non-transferable CN", async () => {
+    option = await depositWithOption(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      THREE_MONTHS_SECONDS,
+      null,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    // a fresh treasury prices CN at 1 lamport, and the 3 month tier is 1:1
+    assert.strictEqual(
+      await tokenBalance(depositorCnAta),
+      depositAmount.toString(),
+      "depositor CN"
+    );
+    assert.strictEqual(
+      await tokenBalance(protocolPtAta),
+      depositAmount.toString(),
+      "protocol PT"
+    );
+    assert.strictEqual(
+      await tokenBalance(option.depositorOptionAta),
+      "1",
+      "depositor should hold the option NFT"
+    );
+    const optionMint = await provider.connection.getAccountInfo(
+      option.optionMint
+    );
+    assert.isTrue(
+      optionMint.owner.equals(TOKEN_2022_PROGRAM_ID),
+      "option NFT should be a Token-2022 mint"
+    );
+
+    const otherCnAta = await createAssociatedTokenAccount(
+      provider.connection,
+      other,
+      cnMint,
+      other.publicKey,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    let transferred = true;
+    try {
+      await transferChecked(
+        provider.connection,
+        depositor,
+        depositorCnAta,
+        cnMint,
+        otherCnAta,
+        depositor,
+        1,
+        decimals,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+    } catch (err) {
+      transferred = false;
+    }
+    assert.isFalse(transferred, "CN should not be transferable");
+  });
+
+  it("converts with the PT transfer fee withheld", async () => {
+    const initialProtocolPt = new anchor.BN(await tokenBalance(protocolPtAta));
+
+    await convert(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      option,
+      convertAmount,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    // Token-2022 rounds the fee up
+    const transferFee = convertAmount
+      .mul(new anchor.BN(ptFeeBps))
+      .add(new anchor.BN(9_999))
+      .div(new anchor.BN(10_000));
+    assert.strictEqual(
+      await tokenBalance(protocolPtAta),
+      initialProtocolPt.sub(convertAmount).toString(),
+      "protocol PT should go down by exactly the PT converted"
+    );
+
+    const depositorPtAta = await getAssociatedTokenAddress(
+      ptMint,
+      depositor.publicKey,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const depositorPt = await getAccount(
+      provider.connection,
+      depositorPtAta,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    assert.strictEqual(
+      depositorPt.amount.toString(),
+      convertAmount.sub(transferFee).toString(),
+      "converter receives the PT less the transfer fee"
+    );
+    assert.strictEqual(
+      getTransferFeeAmount(depositorPt).withheldAmount.toString(),
+      transferFee.toString(),
+      "the fee is withheld in the converter's PT account"
+    );
+    assert.strictEqual(
+      await tokenBalance(depositorCnAta),
+      depositAmount.sub(convertAmount).toString(),
+      "converted CN burned"
+    );
+  });
+
+  it("fails conversion when protocol is globally locked", async () => {
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      true,
+      null,
+      null
+    );
+    try {
+      await expectError(
+        convert(
+          program,
+          provider,
+          depositor,
+          cnMint,
+          ptMint,
+          option,
+          new anchor.BN(1),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        "ProtocolLocked"
+      );
+    } finally {
+      await updateLocks(
+        program,
+        provider,
+        initializer.payer,
+        configPda,
+        false,
+        null,
+        null
+      );
+    }
+  });
+
+  it("fails conversion when conversions are locked (but protocol unlocked)", async () => {
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      null,
+      null,
+      true
+    );
+    try {
+      await expectError(
+        convert(
+          program,
+          provider,
+          depositor,
+          cnMint,
+          ptMint,
+          option,
+          new anchor.BN(1),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        "ConversionsLocked"
+      );
+    } finally {
+      await updateLocks(
+        program,
+        provider,
+        initializer.payer,
+        configPda,
+        null,
+        null,
+        false
+      );
+    }
+  });
+
+  it("fails to redeem CN set aside for an option without the option", async () => {
+    // every CN left is the option's, so none can be redeemed on its own
+    await expectError(
+      redeem(
+        program,
+        provider,
+        depositor,
+        cnMint,
+        ptMint,
+        new anchor.BN(1),
+        null,
+        TOKEN_2022_PROGRAM_ID
+      ),
+      "OptionRequired"
+    );
+  });
+
+  it("redeems CN with the option, burning its PT", async () => {
+    const redeemAmount = depositAmount.sub(convertAmount);
+    const initialTreasuryLamports = await provider.connection.getBalance(
+      treasuryPda
+    );
+
+    await redeem(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      redeemAmount,
+      option.optionMint,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    assert.strictEqual(
+      await tokenBalance(depositorCnAta),
+      "0",
+      "redeemed CN burned"
+    );
+    assert.strictEqual(
+      await tokenBalance(protocolPtAta),
+      "0",
+      "the option's PT burned"
+    );
+    // nothing was earned, so the NAV is still 1 lamport per CN
+    const treasuryLamports = await provider.connection.getBalance(treasuryPda);
+    assert.strictEqual(
+      initialTreasuryLamports - treasuryLamports,
+      redeemAmount.toNumber(),
+      "treasury pays out the redeemed CN at NAV"
+    );
+
+    const optionData = await program.account.optionData.fetch(
+      option.optionData
+    );
+    assert.isTrue(optionData.amount.isZero(), "option fully redeemed");
+  });
+
+  it("redeems CN of an expired option without it, burning no PT", async () => {
+    const shortTier = await provider.connection.getAccountInfo(
+      findDurationTierPda(program, SHORT_DURATION_SECONDS)
+    );
+    if (shortTier === null) {
+      await addDurationTier(
+        program,
+        provider,
+        initializer.payer,
+        SHORT_DURATION_SECONDS,
+        null
+      );
+    }
+    const expiring = await depositWithOption(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      SHORT_DURATION_SECONDS,
+      null,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await new Promise((resolve) =>
+      setTimeout(resolve, (SHORT_DURATION_SECONDS + 1) * 1000)
+    );
+    // the expired option's PT is burned, so its CN pairs with nothing
+    await syncExpiredOptions(
+      program,
+      provider,
+      initializer.payer,
+      ptMint,
+      [expiring.optionData],
+      TOKEN_2022_PROGRAM_ID
+    );
+    assert.strictEqual(
+      await tokenBalance(protocolPtAta),
+      "0",
+      "the expired option's PT burned"
+    );
+
+    const cnAmount = new anchor.BN(await tokenBalance(depositorCnAta));
+    await redeem(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      cnAmount,
+      null,
+      TOKEN_2022_PROGRAM_ID
+    );
+    assert.strictEqual(
+      await tokenBalance(depositorCnAta),
+      "0",
+      "redeemed CN burned"
+    );
+  });
+});
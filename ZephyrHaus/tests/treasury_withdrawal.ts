@@ -0,0 +1,507 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  createMint,
+  getAssociatedTokenAddress,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  DEFAULT_DURATION_TIERS,
+  initializeProtocol,
+  localSendAndConfirmTransaction,
+  parseAnchorError,
+  requestAirdrop,
+  updateLocks,
+  deposit,
+  depositWithOption,
+  convert,
+  findTreasuryWithdrawalPda,
+  queueTreasuryWithdrawal,
+  executeTreasuryWithdrawal,
+  cancelTreasuryWithdrawal,
+  updateWithdrawalDelay,
+  addTreasuryAsset,
+  setAssetPrice,
+  depositSpl,
+  grantRole,
+  revokeRole,
+  fetchCpiEvents,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+const DEFAULT_WITHDRAWAL_DELAY = 2 * 24 * 60 * 60;
+
+describe("treasury withdrawal", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const depositor = Keypair.generate(); // deposits and converts, adding backing
+  const recipient = Keypair.generate(); // receives the withdrawn SOL
+  const manager = Keypair.generate(); // holds the treasury manager role
+  const priceAuthority = Keypair.generate(); // pushes the mock asset's price
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+  const depositAmount = new anchor.BN(0.5 * LAMPORTS_PER_SOL);
+  const surplusAmount = 0.05 * LAMPORTS_PER_SOL; // donated to the treasury
+  const withdrawalAmount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+
+  // mirrors Treasury::withdrawable_sol: the idle SOL not reserved for yield
+  // above the SOL paid in for its backing units, negative when below it
+  async function solSurplus(): Promise<number> {
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    const info = await provider.connection.getAccountInfo(treasuryPda);
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(
+        info.data.length
+      );
+    const unreserved = Math.max(
+      info.lamports - rentExempt - treasury.reservedYield.toNumber(),
+      0
+    );
+    return unreserved - treasury.backingSol.toNumber();
+  }
+
+  async function withdrawableSol(): Promise<number> {
+    return Math.max(await solSurplus(), 0);
+  }
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, depositor.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, manager.publicKey, LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, priceAuthority.publicKey, LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+    treasuryPda = initResult.treasuryPda;
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      false // set converts unlocked
+    );
+
+    // SOL that wasn't paid in for any CN is surplus the authority can take.
+    // earlier suites may have paid out more SOL than the backing they released
+    const shortfall = Math.max(-(await solSurplus()), 0);
+    const tx = new Transaction().add(
+      SystemProgram.transfer({
+        fromPubkey: initializer.publicKey,
+        toPubkey: treasuryPda,
+        lamports: surplusAmount + shortfall,
+      })
+    );
+    await localSendAndConfirmTransaction(provider, tx, initializer.publicKey, [
+      initializer.payer,
+    ]);
+  });
+
+  after(async () => {
+    await updateWithdrawalDelay(
+      program,
+      provider,
+      initializer.payer,
+      DEFAULT_WITHDRAWAL_DELAY
+    );
+  });
+
+  it("fails to queue a withdrawal from a non-authority", async () => {
+    try {
+      await queueTreasuryWithdrawal(
+        program,
+        provider,
+        depositor,
+        depositor.publicKey,
+        withdrawalAmount
+      );
+      assert.fail("queue should have failed for non-authority");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (unauthorized)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "Unauthorized",
+        "error code mismatch (unauthorized)"
+      );
+    }
+  });
+
+  it("keeps the SOL behind converted PT reserved", async () => {
+    const withdrawableBefore = await withdrawableSol();
+
+    // a full conversion burns the CN but its SOL stays behind the PT
+    const option = await depositWithOption(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount
+    );
+    const optionData = await program.account.optionData.fetch(
+      option.optionData
+    );
+    await convert(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      option,
+      optionData.amount
+    );
+
+    assert.strictEqual(
+      await withdrawableSol(),
+      withdrawableBefore,
+      "converting should not free any SOL"
+    );
+  });
+
+  it("keeps a deposit above the 1x tier fully reserved", async () => {
+    const withdrawableBefore = await withdrawableSol();
+
+    // the longest default tier mints PT above 1x
+    const tier = DEFAULT_DURATION_TIERS[DEFAULT_DURATION_TIERS.length - 1];
+    assert.isAbove(tier.conversionMultiplierBps, 10_000);
+    await deposit(
+      program,
+      provider,
+      depositor,
+      cnMint,
+      ptMint,
+      depositAmount,
+      await getAssociatedTokenAddress(ptMint, configPda, true),
+      await getAssociatedTokenAddress(cnMint, depositor.publicKey, true),
+      tier.duration
+    );
+
+    const withdrawable = await withdrawableSol();
+    assert.strictEqual(
+      withdrawable,
+      withdrawableBefore,
+      "the deposit should not add any surplus"
+    );
+    try {
+      await queueTreasuryWithdrawal(
+        program,
+        provider,
+        initializer.payer,
+        recipient.publicKey,
+        new anchor.BN(withdrawable + 1)
+      );
+      assert.fail("queue should have failed above the surplus");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (above surplus)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "ExceedsWithdrawableSol",
+        "error code mismatch (above surplus)"
+      );
+    }
+  });
+
+  it("does not count SPL price pushes as withdrawable", async () => {
+    const usdcDecimals = 6;
+    const priceLamports = new anchor.BN(0.005 * LAMPORTS_PER_SOL);
+    const usdcMint = await createMint(
+      provider.connection,
+      initializer.payer,
+      initializer.publicKey,
+      null,
+      usdcDecimals
+    );
+    const depositorUsdc = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      initializer.payer,
+      usdcMint,
+      depositor.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      initializer.payer,
+      usdcMint,
+      depositorUsdc.address,
+      initializer.publicKey,
+      10 ** usdcDecimals
+    );
+    await addTreasuryAsset(
+      program,
+      provider,
+      initializer.payer,
+      usdcMint,
+      priceAuthority.publicKey,
+      priceLamports,
+      new anchor.BN(60 * 60)
+    );
+    await depositSpl(
+      program,
+      provider,
+      depositor,
+      usdcMint,
+      cnMint,
+      ptMint,
+      new anchor.BN(10 ** usdcDecimals)
+    );
+
+    // the USDC is now worth far more than the backing it was deposited for
+    const withdrawableBefore = await withdrawableSol();
+    await setAssetPrice(
+      program,
+      provider,
+      priceAuthority,
+      usdcMint,
+      new anchor.BN(LAMPORTS_PER_SOL)
+    );
+    try {
+      const withdrawable = await withdrawableSol();
+      assert.strictEqual(
+        withdrawable,
+        withdrawableBefore,
+        "the price push should not add any surplus"
+      );
+      try {
+        await queueTreasuryWithdrawal(
+          program,
+          provider,
+          initializer.payer,
+          recipient.publicKey,
+          new anchor.BN(withdrawable + 1)
+        );
+        assert.fail("queue should have failed above the SOL surplus");
+      } catch (err) {
+        const anchorError = parseAnchorError(err);
+        assert.ok(anchorError, "should be an AnchorError (above SOL surplus)");
+        assert.strictEqual(
+          anchorError.error.errorCode.code,
+          "ExceedsWithdrawableSol",
+          "error code mismatch (above SOL surplus)"
+        );
+      }
+    } finally {
+      await setAssetPrice(
+        program,
+        provider,
+        priceAuthority,
+        usdcMint,
+        priceLamports
+      );
+    }
+  });
+
+  it("fails to queue a withdrawal below the CN backing", async () => {
+    // more than the treasury holds is always above the surplus
+    const treasuryBalance = await provider.connection.getBalance(treasuryPda);
+    try {
+      await queueTreasuryWithdrawal(
+        program,
+        provider,
+        initializer.payer,
+        recipient.publicKey,
+        new anchor.BN(treasuryBalance)
+      );
+      assert.fail("queue should have failed above the withdrawable SOL");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (exceeds withdrawable)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "ExceedsWithdrawableSol",
+        "error code mismatch (exceeds withdrawable)"
+      );
+    }
+  });
+
+  it("fails to execute a withdrawal before its delay", async () => {
+    const signature = await queueTreasuryWithdrawal(
+      program,
+      provider,
+      initializer.payer,
+      recipient.publicKey,
+      withdrawalAmount
+    );
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    const withdrawal = await program.account.treasuryWithdrawal.fetch(
+      findTreasuryWithdrawalPda(program)
+    );
+    assert.isTrue(
+      withdrawal.recipient.equals(recipient.publicKey),
+      "recipient mismatch"
+    );
+    assert.strictEqual(
+      withdrawal.amount.toString(),
+      withdrawalAmount.toString(),
+      "amount mismatch"
+    );
+    assert.strictEqual(
+      withdrawal.executableAt.sub(withdrawal.queuedAt).toString(),
+      treasury.withdrawalDelay.toString(),
+      "withdrawal should be timelocked by the treasury delay"
+    );
+
+    const events = await fetchCpiEvents(program, provider, signature);
+    const event = events.find((e) => e.name === "treasuryWithdrawalQueued");
+    assert.ok(event, "TreasuryWithdrawalQueued should be emitted");
+
+    try {
+      await executeTreasuryWithdrawal(
+        program,
+        provider,
+        initializer.payer,
+        recipient.publicKey
+      );
+      assert.fail("execute should have failed before the delay");
+    } catch (err) {
+      const anchorError = parseAnchorError(err);
+      assert.ok(anchorError, "should be an AnchorError (timelocked)");
+      assert.strictEqual(
+        anchorError.error.errorCode.code,
+        "WithdrawalTimelocked",
+        "error code mismatch (timelocked)"
+      );
+    }
+  });
+
+  it("cancels a queued withdrawal", async () => {
+    await cancelTreasuryWithdrawal(program, provider, initializer.payer);
+
+    const withdrawalInfo = await provider.connection.getAccountInfo(
+      findTreasuryWithdrawalPda(program)
+    );
+    assert.isNull(withdrawalInfo, "withdrawal account should be closed");
+  });
+
+  it("returns the rent to the treasury manager that queued it", async () => {
+    await grantRole(program, provider, initializer.payer, manager.publicKey, {
+      treasuryManager: {},
+    });
+    try {
+      await queueTreasuryWithdrawal(
+        program,
+        provider,
+        manager,
+        recipient.publicKey,
+        withdrawalAmount
+      );
+
+      const withdrawalPda = findTreasuryWithdrawalPda(program);
+      const withdrawal = await program.account.treasuryWithdrawal.fetch(
+        withdrawalPda
+      );
+      assert.isTrue(
+        withdrawal.queuedBy.equals(manager.publicKey),
+        "withdrawal should record the manager that queued it"
+      );
+      const rent = await provider.connection.getBalance(withdrawalPda);
+      const managerBalanceBefore = await provider.connection.getBalance(
+        manager.publicKey
+      );
+
+      // cancelled by the authority, but the manager paid the rent
+      await cancelTreasuryWithdrawal(program, provider, initializer.payer);
+
+      const managerBalanceAfter = await provider.connection.getBalance(
+        manager.publicKey
+      );
+      assert.strictEqual(
+        managerBalanceAfter - managerBalanceBefore,
+        rent,
+        "the manager should get the rent back"
+      );
+    } finally {
+      await revokeRole(
+        program,
+        provider,
+        initializer.payer,
+        manager.publicKey,
+        { treasuryManager: {} }
+      );
+    }
+  });
+
+  it("executes a withdrawal once its delay has passed", async () => {
+    await updateWithdrawalDelay(program, provider, initializer.payer, 0);
+    await queueTreasuryWithdrawal(
+      program,
+      provider,
+      initializer.payer,
+      recipient.publicKey,
+      withdrawalAmount
+    );
+
+    const treasuryBefore = await program.account.treasury.fetch(treasuryPda);
+    const treasuryBalanceBefore = await provider.connection.getBalance(
+      treasuryPda
+    );
+    const recipientBalanceBefore = await provider.connection.getBalance(
+      recipient.publicKey
+    );
+
+    await executeTreasuryWithdrawal(
+      program,
+      provider,
+      initializer.payer,
+      recipient.publicKey
+    );
+
+    const treasuryBalanceAfter = await provider.connection.getBalance(
+      treasuryPda
+    );
+    const recipientBalanceAfter = await provider.connection.getBalance(
+      recipient.publicKey
+    );
+    assert.strictEqual(
+      treasuryBalanceBefore - treasuryBalanceAfter,
+      withdrawalAmount.toNumber(),
+      "treasury should pay out the withdrawal"
+    );
+    assert.strictEqual(
+      recipientBalanceAfter - recipientBalanceBefore,
+      withdrawalAmount.toNumber(),
+      "recipient should receive the withdrawal"
+    );
+
+    const treasuryAfter = await program.account.treasury.fetch(treasuryPda);
+    assert.strictEqual(
+      treasuryAfter.totalDepositedSol.toString(),
+      treasuryBefore.totalDepositedSol.toString(),
+      "withdrawing surplus should leave the deposits' accounting alone"
+    );
+    const withdrawalInfo = await provider.connection.getAccountInfo(
+      findTreasuryWithdrawalPda(program)
+    );
+    assert.isNull(withdrawalInfo, "withdrawal account should be closed");
+  });
+});
@@ -0,0 +1,143 @@
+// renounces the shared config's authority for good, so this suite has to run
+// after every other one. mocha loads test files in alphabetical order.
+import * as anchor from "@coral-xyz/anchor";
+import { Program, Wallet } from "@coral-xyz/anchor";
+import { InvestInSol } from "../target/types/invest_in_sol";
+import { assert } from "chai";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  CN_MINT_ADDRESS,
+  PT_MINT_ADDRESS,
+  initializeProtocol,
+  expectError,
+  requestAirdrop,
+  updateLocks,
+  grantRole,
+  depositWithOption,
+  freezeOption,
+  thawOption,
+  renounceAuthority,
+  debugEnableLogs,
+} from "./utils";
+
+debugEnableLogs();
+
+describe("renounce authority", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.InvestInSol as Program<InvestInSol>;
+  const initializer = provider.wallet as Wallet; // use provider's wallet as initializer/authority
+  const guardian = Keypair.generate(); // holds the pause guardian role
+  const manager = Keypair.generate(); // holds the metadata manager role
+  const holder = Keypair.generate(); // holds an option frozen until a cliff
+
+  const cnMint = CN_MINT_ADDRESS;
+  const ptMint = PT_MINT_ADDRESS;
+
+  let configPda: PublicKey;
+  let option: Awaited<ReturnType<typeof depositWithOption>>;
+
+  before(async () => {
+    await requestAirdrop(provider, initializer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, guardian.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, manager.publicKey, 2 * LAMPORTS_PER_SOL);
+    await requestAirdrop(provider, holder.publicKey, 2 * LAMPORTS_PER_SOL);
+
+    const initResult = await initializeProtocol(
+      program,
+      provider,
+      initializer.payer,
+      cnMint,
+      ptMint
+    );
+    configPda = initResult.configPda;
+
+    await grantRole(program, provider, initializer.payer, guardian.publicKey, {
+      pauseGuardian: {},
+    });
+    await grantRole(program, provider, initializer.payer, manager.publicKey, {
+      metadataManager: {},
+    });
+
+    await updateLocks(
+      program,
+      provider,
+      initializer.payer,
+      configPda,
+      false, // set globally unlocked
+      false, // set deposits unlocked
+      false // set converts unlocked
+    );
+    option = await depositWithOption(
+      program,
+      provider,
+      holder,
+      cnMint,
+      ptMint,
+      new anchor.BN(0.1 * LAMPORTS_PER_SOL)
+    );
+    // the cliff is far enough out to never pass while the suite runs
+    await freezeOption(
+      program,
+      provider,
+      initializer.payer,
+      option.optionMint,
+      holder.publicKey,
+      new anchor.BN(Math.floor(Date.now() / 1000) + 60 * 60)
+    );
+  });
+
+  it("lets a role holder act while the authority is set", async () => {
+    await updateLocks(
+      program,
+      provider,
+      guardian,
+      configPda,
+      null,
+      null,
+      true, // lock converts
+      null
+    );
+
+    const config = await program.account.config.fetch(configPda);
+    assert.isTrue(config.convertLocked, "guardian should lock converts");
+  });
+
+  it("stops honouring roles once the authority is renounced", async () => {
+    await renounceAuthority(program, provider, initializer.payer);
+
+    const config = await program.account.config.fetch(configPda);
+    assert.isNull(config.authority, "authority should be renounced");
+
+    await expectError(
+      updateLocks(program, provider, guardian, configPda, true, null, null),
+      "AuthorityRenounced"
+    );
+    await expectError(
+      updateLocks(
+        program,
+        provider,
+        initializer.payer,
+        configPda,
+        false,
+        null,
+        null
+      ),
+      "AuthorityRenounced"
+    );
+  });
+
+  it("stops a metadata manager thawing before the cliff once renounced", async () => {
+    await expectError(
+      thawOption(
+        program,
+        provider,
+        manager,
+        option.optionMint,
+        holder.publicKey
+      ),
+      "AuthorityRenounced"
+    );
+  });
+});